@@ -0,0 +1,135 @@
+use std::collections::hash_map::RandomState;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use hashlink::LinkedHashMap;
+
+const SIZE: u64 = 100_000;
+
+fn filled() -> LinkedHashMap<u64, u64> {
+    let mut map = LinkedHashMap::new();
+    for i in 0..SIZE {
+        map.insert(i, i);
+    }
+    map
+}
+
+fn bench_insert(c: &mut Criterion) {
+    c.bench_function("insert", |b| {
+        b.iter(|| {
+            let mut map = LinkedHashMap::new();
+            for i in 0..SIZE {
+                map.insert(i, i);
+            }
+            black_box(map)
+        })
+    });
+}
+
+fn bench_iter(c: &mut Criterion) {
+    let mut map = filled();
+    // Shuffle the list order relative to insertion order, so that iteration does not simply walk
+    // memory front to back.
+    for i in (0..SIZE).step_by(3) {
+        map.to_back(&i);
+    }
+
+    c.bench_function("iter", |b| {
+        b.iter(|| {
+            black_box(&map)
+                .values()
+                .fold(0u64, |a, &v| a.wrapping_add(v))
+        })
+    });
+}
+
+fn bench_churn(c: &mut Criterion) {
+    let mut map = filled();
+    let mut next = SIZE;
+
+    c.bench_function("insert_remove_churn", |b| {
+        b.iter(|| {
+            for _ in 0..1000 {
+                map.pop_front();
+                map.insert(next, next);
+                next += 1;
+            }
+        })
+    });
+}
+
+// Long string keys with SipHash, where rehashing keys is expensive.  Run these with and without the
+// `cached-hashes` feature to compare.
+fn string_keys() -> Vec<String> {
+    (0..SIZE / 10).map(|i| format!("{:0>64}", i)).collect()
+}
+
+fn filled_strings(keys: &[String]) -> LinkedHashMap<String, u64, RandomState> {
+    let mut map = LinkedHashMap::with_hasher(RandomState::new());
+    for (i, k) in keys.iter().enumerate() {
+        map.insert(k.clone(), i as u64);
+    }
+    map
+}
+
+fn bench_string_grow(c: &mut Criterion) {
+    let keys = string_keys();
+
+    c.bench_function("string_grow", |b| {
+        b.iter_batched(
+            || keys.clone(),
+            |keys| {
+                let mut map = LinkedHashMap::with_hasher(RandomState::new());
+                for (i, k) in keys.into_iter().enumerate() {
+                    map.insert(k, i);
+                }
+                black_box(map)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn bench_string_retain(c: &mut Criterion) {
+    let keys = string_keys();
+
+    c.bench_function("string_retain", |b| {
+        b.iter_batched(
+            || filled_strings(&keys),
+            |mut map| {
+                map.retain_with_order(|_, &mut v| v % 2 == 0);
+                black_box(map)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn bench_string_shrink(c: &mut Criterion) {
+    let keys = string_keys();
+
+    c.bench_function("string_shrink_to_fit", |b| {
+        b.iter_batched(
+            || {
+                let mut map = filled_strings(&keys);
+                map.reserve(map.len() * 4);
+                map
+            },
+            |mut map| {
+                map.shrink_to_fit();
+                black_box(map)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_iter,
+    bench_churn,
+    bench_string_grow,
+    bench_string_retain,
+    bench_string_shrink
+);
+criterion_main!(benches);
@@ -0,0 +1,218 @@
+// The containers in this crate are generic over an `Allocator`, which is only actually pluggable
+// when the `allocator-api2` feature is enabled.  Without it, hashbrown does not expose its own
+// allocator trait, so we provide a stand-in `Allocator` that only `Global` implements, along with
+// thin wrappers around the collections we use internally that ignore their allocator parameter.
+// This way the rest of the crate can be written against a single API.
+
+pub(crate) use self::inner::{hash_table, HashTable, Vec};
+pub use self::inner::{Allocator, Global};
+
+#[cfg(feature = "allocator-api2")]
+mod inner {
+    pub use allocator_api2::alloc::{Allocator, Global};
+    pub(crate) use allocator_api2::vec::Vec;
+    pub(crate) use hashbrown::hash_table::{self, HashTable};
+}
+
+#[cfg(not(feature = "allocator-api2"))]
+mod inner {
+    use core::{
+        marker::PhantomData,
+        ops::{Deref, DerefMut},
+    };
+
+    mod sealed {
+        pub trait Sealed {}
+    }
+
+    /// Stand-in for the `Allocator` trait from `allocator-api2`, which is only implemented by
+    /// [`Global`].
+    ///
+    /// Enable the `allocator-api2` feature to use custom allocators.
+    pub trait Allocator: sealed::Sealed {}
+
+    /// The global memory allocator.
+    #[derive(Copy, Clone, Default, Debug)]
+    pub struct Global;
+
+    impl sealed::Sealed for Global {}
+    impl Allocator for Global {}
+
+    pub(crate) struct Vec<T, A> {
+        vec: alloc::vec::Vec<T>,
+        alloc: A,
+    }
+
+    impl<T, A> Vec<T, A> {
+        #[inline]
+        pub(crate) fn new_in(alloc: A) -> Self {
+            Vec {
+                vec: alloc::vec::Vec::new(),
+                alloc,
+            }
+        }
+
+        #[inline]
+        pub(crate) fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+            Vec {
+                vec: alloc::vec::Vec::with_capacity(capacity),
+                alloc,
+            }
+        }
+
+        #[inline]
+        pub(crate) fn allocator(&self) -> &A {
+            &self.alloc
+        }
+    }
+
+    impl<T, A> Deref for Vec<T, A> {
+        type Target = alloc::vec::Vec<T>;
+
+        #[inline]
+        fn deref(&self) -> &Self::Target {
+            &self.vec
+        }
+    }
+
+    impl<T, A> DerefMut for Vec<T, A> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.vec
+        }
+    }
+
+    #[repr(transparent)]
+    pub(crate) struct HashTable<T, A> {
+        table: hashbrown::HashTable<T>,
+        marker: PhantomData<A>,
+    }
+
+    impl<T, A> HashTable<T, A> {
+        #[inline]
+        pub(crate) fn new_in(_alloc: A) -> Self {
+            HashTable {
+                table: hashbrown::HashTable::new(),
+                marker: PhantomData,
+            }
+        }
+
+        #[inline]
+        pub(crate) fn with_capacity_in(capacity: usize, _alloc: A) -> Self {
+            HashTable {
+                table: hashbrown::HashTable::with_capacity(capacity),
+                marker: PhantomData,
+            }
+        }
+
+        #[inline]
+        fn from_mut(table: &mut hashbrown::HashTable<T>) -> &mut Self {
+            // Safe because `HashTable` is a transparent wrapper.
+            unsafe { &mut *(table as *mut hashbrown::HashTable<T> as *mut Self) }
+        }
+
+        #[inline]
+        pub(crate) fn find_entry(
+            &mut self,
+            hash: u64,
+            eq: impl FnMut(&T) -> bool,
+        ) -> Result<hash_table::OccupiedEntry<'_, T, A>, hash_table::AbsentEntry<'_, T, A>>
+        {
+            match self.table.find_entry(hash, eq) {
+                Ok(entry) => Ok(hash_table::OccupiedEntry {
+                    entry,
+                    marker: PhantomData,
+                }),
+                Err(entry) => Err(hash_table::AbsentEntry {
+                    entry,
+                    marker: PhantomData,
+                }),
+            }
+        }
+
+        #[inline]
+        pub(crate) fn insert_unique(
+            &mut self,
+            hash: u64,
+            value: T,
+            hasher: impl Fn(&T) -> u64,
+        ) -> hash_table::OccupiedEntry<'_, T, A> {
+            hash_table::OccupiedEntry {
+                entry: self.table.insert_unique(hash, value, hasher),
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<T, A> Deref for HashTable<T, A> {
+        type Target = hashbrown::HashTable<T>;
+
+        #[inline]
+        fn deref(&self) -> &Self::Target {
+            &self.table
+        }
+    }
+
+    impl<T, A> DerefMut for HashTable<T, A> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.table
+        }
+    }
+
+    pub(crate) mod hash_table {
+        use core::{fmt, marker::PhantomData};
+
+        use super::HashTable;
+
+        pub(crate) struct OccupiedEntry<'a, T, A> {
+            pub(super) entry: hashbrown::hash_table::OccupiedEntry<'a, T>,
+            pub(super) marker: PhantomData<A>,
+        }
+
+        impl<'a, T, A> OccupiedEntry<'a, T, A> {
+            #[inline]
+            pub(crate) fn get(&self) -> &T {
+                self.entry.get()
+            }
+
+            #[inline]
+            pub(crate) fn get_mut(&mut self) -> &mut T {
+                self.entry.get_mut()
+            }
+
+            #[inline]
+            pub(crate) fn into_mut(self) -> &'a mut T {
+                self.entry.into_mut()
+            }
+
+            #[inline]
+            pub(crate) fn remove(self) -> (T, ()) {
+                (self.entry.remove().0, ())
+            }
+
+            #[inline]
+            pub(crate) fn into_table(self) -> &'a mut HashTable<T, A> {
+                HashTable::from_mut(self.entry.into_table())
+            }
+        }
+
+        pub(crate) struct AbsentEntry<'a, T, A> {
+            pub(super) entry: hashbrown::hash_table::AbsentEntry<'a, T>,
+            pub(super) marker: PhantomData<A>,
+        }
+
+        impl<T, A> fmt::Debug for AbsentEntry<'_, T, A> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("AbsentEntry")
+            }
+        }
+
+        impl<'a, T, A> AbsentEntry<'a, T, A> {
+            #[inline]
+            pub(crate) fn into_table(self) -> &'a mut HashTable<T, A> {
+                HashTable::from_mut(self.entry.into_table())
+            }
+        }
+    }
+}
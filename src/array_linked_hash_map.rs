@@ -0,0 +1,938 @@
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ops::{Index, IndexMut},
+};
+
+use crate::{DefaultHashBuilder, Equivalent};
+
+/// The error returned when inserting a new entry into a full fixed capacity container.
+///
+/// Holds whatever could not be inserted, so that it is not lost.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CapacityError<T>(pub T);
+
+/// A `LinkedHashMap` with a fixed capacity of `N` entries, which never allocates.
+///
+/// Entries are kept in an array of `N` nodes which link to each other by index, and are found
+/// through an open addressing index with `2 * N` slots, all stored inline.  Entries are ordered
+/// exactly as they are in a `LinkedHashMap`, but inserting a new entry into a full map returns a
+/// `CapacityError` rather than growing the map.
+///
+/// This is available without the `alloc` feature.
+pub struct ArrayLinkedHashMap<K, V, const N: usize, S = DefaultHashBuilder> {
+    hash_builder: S,
+    // Linear probing table holding the index of a node, or `NIL` for an empty slot.  It has twice
+    // as many slots as there are nodes so it is never more than half full, and removals shift
+    // later entries back rather than leaving tombstones.
+    index: [[u32; 2]; N],
+    nodes: [Node; N],
+    // The entry of every node which is in the list is initialized.
+    entries: [MaybeUninit<(K, V)>; N],
+    // First and last node of the list, or `NIL` if the map is empty.
+    head: u32,
+    tail: u32,
+    // Singly linked list of free nodes, threaded through their `next` links, or `NIL` if there are
+    // none.  Nodes at or past `used` have never been used and are free without being on this list.
+    free: u32,
+    used: u32,
+    len: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Node {
+    hash: u64,
+    prev: u32,
+    next: u32,
+}
+
+const NIL: u32 = u32::MAX;
+
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
+impl<K, V, const N: usize> ArrayLinkedHashMap<K, V, N> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, const N: usize, S> ArrayLinkedHashMap<K, V, N, S> {
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        assert!(N < NIL as usize, "ArrayLinkedHashMap capacity overflow");
+        Self {
+            hash_builder,
+            index: [[NIL; 2]; N],
+            nodes: [Node {
+                hash: 0,
+                prev: NIL,
+                next: NIL,
+            }; N],
+            // An array of `MaybeUninit` does not require initialization.
+            entries: unsafe { MaybeUninit::uninit().assume_init() },
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            used: 0,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of entries this map can hold, which is always `N`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns whether inserting a new entry would fail.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        let mut node = self.head;
+        self.index = [[NIL; 2]; N];
+        self.head = NIL;
+        self.tail = NIL;
+        self.free = NIL;
+        self.used = 0;
+        self.len = 0;
+        // Every node is already free, so if dropping an entry panics the rest are leaked rather
+        // than dropped twice.
+        while node != NIL {
+            let next = self.nodes[node as usize].next;
+            unsafe { self.entries[node as usize].assume_init_drop() };
+            node = next;
+        }
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: &self.nodes,
+            entries: &self.entries,
+            head: self.head,
+            tail: self.tail,
+            remaining: self.len(),
+        }
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            nodes: &self.nodes,
+            entries: self.entries.as_mut_ptr(),
+            head: self.head,
+            tail: self.tail,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    #[inline]
+    pub fn front(&self) -> Option<(&K, &V)> {
+        if self.head == NIL {
+            return None;
+        }
+        let (key, value) = unsafe { self.entry(self.head) };
+        Some((key, value))
+    }
+
+    #[inline]
+    pub fn back(&self) -> Option<(&K, &V)> {
+        if self.tail == NIL {
+            return None;
+        }
+        let (key, value) = unsafe { self.entry(self.tail) };
+        Some((key, value))
+    }
+
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        if self.head == NIL {
+            return None;
+        }
+        Some(self.take_node(self.head))
+    }
+
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<(K, V)> {
+        if self.tail == NIL {
+            return None;
+        }
+        Some(self.take_node(self.tail))
+    }
+
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut node = self.head;
+        while node != NIL {
+            let next = self.nodes[node as usize].next;
+            let (key, value) = unsafe { self.entries[node as usize].assume_init_mut() };
+            if !f(key, value) {
+                drop(self.take_node(node));
+            }
+            node = next;
+        }
+    }
+
+    // Maps a hash to the slot in `index` where probing for it starts.
+    #[inline]
+    fn home_slot(hash: u64) -> usize {
+        // Multiply-shift range reduction, which uses the high bits of the hash and avoids a
+        // division.
+        ((hash as u128 * (2 * N) as u128) >> 64) as usize
+    }
+
+    #[inline]
+    fn next_slot(slot: usize) -> usize {
+        if slot + 1 == 2 * N {
+            0
+        } else {
+            slot + 1
+        }
+    }
+
+    #[inline]
+    fn slot(&self, slot: usize) -> u32 {
+        self.index[slot / 2][slot % 2]
+    }
+
+    #[inline]
+    fn set_slot(&mut self, slot: usize, node: u32) {
+        self.index[slot / 2][slot % 2] = node;
+    }
+
+    // Caller must ensure that the node is in the list.
+    #[inline]
+    unsafe fn entry(&self, node: u32) -> &(K, V) {
+        self.entries[node as usize].assume_init_ref()
+    }
+
+    #[inline]
+    fn find_node<Q>(&self, hash: u64, k: &Q) -> Option<u32>
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        if self.len == 0 {
+            return None;
+        }
+        let mut slot = Self::home_slot(hash);
+        loop {
+            let node = self.slot(slot);
+            if node == NIL {
+                return None;
+            }
+            if self.nodes[node as usize].hash == hash
+                && k.equivalent(unsafe { &self.entry(node).0 })
+            {
+                return Some(node);
+            }
+            slot = Self::next_slot(slot);
+        }
+    }
+
+    // Removes the given node, which must be in the list, from the index and the list and returns
+    // its entry.
+    fn take_node(&mut self, node: u32) -> (K, V) {
+        let mut slot = Self::home_slot(self.nodes[node as usize].hash);
+        while self.slot(slot) != node {
+            slot = Self::next_slot(slot);
+        }
+        self.clear_slot(slot);
+        self.unlink(node);
+        self.nodes[node as usize].next = self.free;
+        self.free = node;
+        self.len -= 1;
+        unsafe { self.entries[node as usize].assume_init_read() }
+    }
+
+    // Empties the given slot of the index, then shifts back every following entry in the same probe
+    // run which would otherwise no longer be reachable from its home slot.
+    fn clear_slot(&mut self, mut hole: usize) {
+        self.set_slot(hole, NIL);
+        let mut slot = hole;
+        loop {
+            slot = Self::next_slot(slot);
+            let node = self.slot(slot);
+            if node == NIL {
+                break;
+            }
+            // The entry must stay where it is if its home slot lies cyclically within
+            // `(hole, slot]`.
+            let home = Self::home_slot(self.nodes[node as usize].hash);
+            let stays = if hole <= slot {
+                hole < home && home <= slot
+            } else {
+                hole < home || home <= slot
+            };
+            if !stays {
+                self.set_slot(hole, node);
+                self.set_slot(slot, NIL);
+                hole = slot;
+            }
+        }
+    }
+
+    // Adds an entry for a key which is not yet in the map to the back of the list.  The map must
+    // not be full.
+    fn push_new(&mut self, hash: u64, k: K, v: V) -> u32 {
+        debug_assert!(!self.is_full());
+        let node = if self.free != NIL {
+            let node = self.free;
+            self.free = self.nodes[node as usize].next;
+            node
+        } else {
+            self.used += 1;
+            self.used - 1
+        };
+        self.entries[node as usize].write((k, v));
+        self.nodes[node as usize].hash = hash;
+
+        let mut slot = Self::home_slot(hash);
+        while self.slot(slot) != NIL {
+            slot = Self::next_slot(slot);
+        }
+        self.set_slot(slot, node);
+        self.link_back(node);
+        self.len += 1;
+        node
+    }
+
+    #[inline]
+    fn unlink(&mut self, node: u32) {
+        let Node { prev, next, .. } = self.nodes[node as usize];
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.nodes[prev as usize].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.nodes[next as usize].prev = prev;
+        }
+    }
+
+    #[inline]
+    fn link_back(&mut self, node: u32) {
+        self.nodes[node as usize].prev = self.tail;
+        self.nodes[node as usize].next = NIL;
+        if self.tail == NIL {
+            self.head = node;
+        } else {
+            self.nodes[self.tail as usize].next = node;
+        }
+        self.tail = node;
+    }
+
+    #[inline]
+    fn link_front(&mut self, node: u32) {
+        self.nodes[node as usize].prev = NIL;
+        self.nodes[node as usize].next = self.head;
+        if self.head == NIL {
+            self.tail = node;
+        } else {
+            self.nodes[self.head as usize].prev = node;
+        }
+        self.head = node;
+    }
+}
+
+impl<K, V, const N: usize, S> ArrayLinkedHashMap<K, V, N, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(hash_key(&self.hash_builder, k), k)?;
+        let (key, value) = unsafe { self.entry(node) };
+        Some((key, value))
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_key_value(k).is_some()
+    }
+
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(hash_key(&self.hash_builder, k), k)?;
+        Some(unsafe { &mut self.entries[node as usize].assume_init_mut().1 })
+    }
+
+    /// Inserts the given key / value pair at the *back* of the list.
+    ///
+    /// Returns the previously set value, if one existed prior to this call, in which case the
+    /// existing entry is moved to the back.  If the key is not in the map and the map is full, the
+    /// key / value pair is returned in a `CapacityError` and the map is left unchanged.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        let hash = hash_key(&self.hash_builder, &k);
+        match self.find_node(hash, &k) {
+            Some(node) => {
+                self.unlink(node);
+                self.link_back(node);
+                let value = unsafe { &mut self.entries[node as usize].assume_init_mut().1 };
+                Ok(Some(mem::replace(value, v)))
+            }
+            None if self.is_full() => Err(CapacityError((k, v))),
+            None => {
+                self.push_new(hash, k, v);
+                Ok(None)
+            }
+        }
+    }
+
+    /// If the given key is not in this map, inserts the key / value pair at the *back* of the list
+    /// and returns `None`, otherwise, replaces the existing value with the given value *without*
+    /// moving the entry and returns the previous value.
+    ///
+    /// Fails just like `ArrayLinkedHashMap::insert` if a new entry does not fit.
+    #[inline]
+    pub fn replace(&mut self, k: K, v: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        let hash = hash_key(&self.hash_builder, &k);
+        match self.find_node(hash, &k) {
+            Some(node) => {
+                let value = unsafe { &mut self.entries[node as usize].assume_init_mut().1 };
+                Ok(Some(mem::replace(value, v)))
+            }
+            None if self.is_full() => Err(CapacityError((k, v))),
+            None => {
+                self.push_new(hash, k, v);
+                Ok(None)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(hash_key(&self.hash_builder, k), k)?;
+        Some(self.take_node(node))
+    }
+
+    /// If an entry with this key exists, move it to the front of the list and return a reference to
+    /// the value.
+    #[inline]
+    pub fn to_front<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(hash_key(&self.hash_builder, k), k)?;
+        self.unlink(node);
+        self.link_front(node);
+        Some(unsafe { &mut self.entries[node as usize].assume_init_mut().1 })
+    }
+
+    /// If an entry with this key exists, move it to the back of the list and return a reference to
+    /// the value.
+    #[inline]
+    pub fn to_back<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(hash_key(&self.hash_builder, k), k)?;
+        self.unlink(node);
+        self.link_back(node);
+        Some(unsafe { &mut self.entries[node as usize].assume_init_mut().1 })
+    }
+
+    // Like `insert`, but if a new entry does not fit, first removes the entry at the front of the
+    // list to make room for it.
+    pub(crate) fn insert_evicting(&mut self, k: K, v: V) -> Option<V> {
+        let hash = hash_key(&self.hash_builder, &k);
+        if let Some(node) = self.find_node(hash, &k) {
+            self.unlink(node);
+            self.link_back(node);
+            let value = unsafe { &mut self.entries[node as usize].assume_init_mut().1 };
+            return Some(mem::replace(value, v));
+        }
+        // With no room at all, the new entry is evicted straight away.
+        if !self.is_full() || self.pop_front().is_some() {
+            self.push_new(hash, k, v);
+        }
+        None
+    }
+}
+
+impl<K, V, const N: usize, S> Drop for ArrayLinkedHashMap<K, V, N, S> {
+    #[inline]
+    fn drop(&mut self) {
+        if mem::needs_drop::<(K, V)>() {
+            self.clear();
+        }
+    }
+}
+
+impl<K, V, const N: usize, S: Default> Default for ArrayLinkedHashMap<K, V, N, S> {
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, const N: usize, S> fmt::Debug for ArrayLinkedHashMap<K, V, N, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, const N: usize, S: BuildHasher> PartialEq
+    for ArrayLinkedHashMap<K, V, N, S>
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, const N: usize, S: BuildHasher> Eq for ArrayLinkedHashMap<K, V, N, S> {}
+
+impl<K: Hash + Eq, V: Hash, const N: usize, S: BuildHasher> Hash
+    for ArrayLinkedHashMap<K, V, N, S>
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, h: &mut H) {
+        for e in self.iter() {
+            e.hash(h);
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize, S, Q> Index<&'a Q> for ArrayLinkedHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: Hash + Equivalent<K> + ?Sized,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, index: &'a Q) -> &V {
+        self.get(index).expect("no entry found for key")
+    }
+}
+
+impl<'a, K, V, const N: usize, S, Q> IndexMut<&'a Q> for ArrayLinkedHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: Hash + Equivalent<K> + ?Sized,
+{
+    #[inline]
+    fn index_mut(&mut self, index: &'a Q) -> &mut V {
+        self.get_mut(index).expect("no entry found for key")
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize, S: Clone> Clone for ArrayLinkedHashMap<K, V, N, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut map = Self::with_hasher(self.hash_builder.clone());
+        let mut node = self.head;
+        while node != NIL {
+            let (key, value) = unsafe { self.entry(node) };
+            map.push_new(self.nodes[node as usize].hash, key.clone(), value.clone());
+            node = self.nodes[node as usize].next;
+        }
+        map
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node],
+    entries: &'a [MaybeUninit<(K, V)>],
+    head: u32,
+    tail: u32,
+    remaining: usize,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.head as usize;
+        self.head = self.nodes[node].next;
+        let (key, value) = unsafe { self.entries[node].assume_init_ref() };
+        Some((key, value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.tail as usize;
+        self.tail = self.nodes[node].prev;
+        let (key, value) = unsafe { self.entries[node].assume_init_ref() };
+        Some((key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    nodes: &'a [Node],
+    entries: *mut MaybeUninit<(K, V)>,
+    head: u32,
+    tail: u32,
+    remaining: usize,
+    marker: PhantomData<(&'a K, &'a mut V)>,
+}
+
+impl<K, V> IterMut<'_, K, V> {
+    #[inline]
+    fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: self.nodes,
+            entries: unsafe { core::slice::from_raw_parts(self.entries, self.nodes.len()) },
+            head: self.head,
+            tail: self.tail,
+            remaining: self.remaining,
+        }
+    }
+}
+
+unsafe impl<K: Sync, V: Send> Send for IterMut<'_, K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for IterMut<'_, K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IterMut<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.head as usize;
+        self.head = self.nodes[node].next;
+        let (key, value) = unsafe { (*self.entries.add(node)).assume_init_mut() };
+        Some((key, value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a mut V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.tail as usize;
+        self.tail = self.nodes[node].prev;
+        let (key, value) = unsafe { (*self.entries.add(node)).assume_init_mut() };
+        Some((key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+pub struct IntoIter<K, V, const N: usize, S> {
+    map: ArrayLinkedHashMap<K, V, N, S>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize, S> fmt::Debug for IntoIter<K, V, N, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.map.iter()).finish()
+    }
+}
+
+impl<K, V, const N: usize, S> Iterator for IntoIter<K, V, N, S> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.map.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len(), Some(self.map.len()))
+    }
+}
+
+impl<K, V, const N: usize, S> DoubleEndedIterator for IntoIter<K, V, N, S> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        self.map.pop_back()
+    }
+}
+
+impl<K, V, const N: usize, S> ExactSizeIterator for IntoIter<K, V, N, S> {}
+
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for Keys<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a K> {
+        self.inner.next().map(|e| e.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a K> {
+        self.inner.next_back().map(|e| e.0)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for Values<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|e| e.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|e| e.1)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ValuesMut<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.inner.iter().map(|e| e.1))
+            .finish()
+    }
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.inner.next().map(|e| e.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back().map(|e| e.1)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a ArrayLinkedHashMap<K, V, N, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a mut ArrayLinkedHashMap<K, V, N, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize, S> IntoIterator for ArrayLinkedHashMap<K, V, N, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N, S>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V, N, S> {
+        IntoIter { map: self }
+    }
+}
+
+#[inline]
+fn hash_key<S, Q>(s: &S, k: &Q) -> u64
+where
+    S: BuildHasher,
+    Q: Hash + ?Sized,
+{
+    let mut hasher = s.build_hasher();
+    k.hash(&mut hasher);
+    hasher.finish()
+}
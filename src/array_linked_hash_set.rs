@@ -0,0 +1,329 @@
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+};
+
+use crate::array_linked_hash_map::{self, ArrayLinkedHashMap, CapacityError};
+use crate::{DefaultHashBuilder, Equivalent};
+
+/// A `LinkedHashSet` with a fixed capacity of `N` values, which never allocates.
+///
+/// See `ArrayLinkedHashMap` for how values are stored.  Inserting a new value into a full set
+/// returns a `CapacityError` rather than growing the set.
+///
+/// This is available without the `alloc` feature.
+pub struct ArrayLinkedHashSet<T, const N: usize, S = DefaultHashBuilder> {
+    map: ArrayLinkedHashMap<T, (), N, S>,
+}
+
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
+impl<T, const N: usize> ArrayLinkedHashSet<T, N> {
+    #[inline]
+    pub fn new() -> Self {
+        ArrayLinkedHashSet {
+            map: ArrayLinkedHashMap::new(),
+        }
+    }
+}
+
+impl<T, const N: usize, S> ArrayLinkedHashSet<T, N, S> {
+    #[inline]
+    pub fn with_hasher(hasher: S) -> Self {
+        ArrayLinkedHashSet {
+            map: ArrayLinkedHashMap::with_hasher(hasher),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the maximum number of values this set can hold, which is always `N`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Returns whether inserting a new value would fail.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.map.is_full()
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.keys(),
+        }
+    }
+
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.map.front().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.map.back().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.map.pop_front().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.map.pop_back().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|k, _| f(k));
+    }
+}
+
+impl<T, const N: usize, S> ArrayLinkedHashSet<T, N, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Equivalent<T> + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: Hash + Equivalent<T> + ?Sized,
+    {
+        self.map.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// Inserts the given value into the set.
+    ///
+    /// If the set did not have this value present, inserts it at the *back* of the internal linked
+    /// list and returns true, otherwise it moves the existing value to the *back* of the internal
+    /// linked list and returns false.  If the value is not present and the set is full, the value
+    /// is returned in a `CapacityError` and the set is left unchanged.
+    #[inline]
+    pub fn insert(&mut self, value: T) -> Result<bool, CapacityError<T>> {
+        match self.map.insert(value, ()) {
+            Ok(old) => Ok(old.is_none()),
+            Err(CapacityError((value, ()))) => Err(CapacityError(value)),
+        }
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Equivalent<T> + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    #[inline]
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        Q: Hash + Equivalent<T> + ?Sized,
+    {
+        self.map.remove_entry(value).map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn to_front<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Equivalent<T> + ?Sized,
+    {
+        self.map.to_front(value).is_some()
+    }
+
+    #[inline]
+    pub fn to_back<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Equivalent<T> + ?Sized,
+    {
+        self.map.to_back(value).is_some()
+    }
+}
+
+impl<T: Clone, const N: usize, S: Clone> Clone for ArrayLinkedHashSet<T, N, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArrayLinkedHashSet {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<T, const N: usize, S> PartialEq for ArrayLinkedHashSet<T, N, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T, const N: usize, S> Eq for ArrayLinkedHashSet<T, N, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, const N: usize, S> Hash for ArrayLinkedHashSet<T, N, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for e in self {
+            e.hash(state);
+        }
+    }
+}
+
+impl<T, const N: usize, S> fmt::Debug for ArrayLinkedHashSet<T, N, S>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize, S: Default> Default for ArrayLinkedHashSet<T, N, S> {
+    #[inline]
+    fn default() -> Self {
+        ArrayLinkedHashSet {
+            map: ArrayLinkedHashMap::default(),
+        }
+    }
+}
+
+impl<'a, T, const N: usize, S> IntoIterator for &'a ArrayLinkedHashSet<T, N, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, const N: usize, S> IntoIterator for ArrayLinkedHashSet<T, N, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N, S>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T, N, S> {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+pub struct Iter<'a, K> {
+    iter: array_linked_hash_map::Keys<'a, K, ()>,
+}
+
+impl<K> Clone for Iter<'_, K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for Iter<'_, K> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, K> Iterator for Iter<'a, K> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a K> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for Iter<'a, K> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a K> {
+        self.iter.next_back()
+    }
+}
+
+impl<K> ExactSizeIterator for Iter<'_, K> {}
+
+pub struct IntoIter<K, const N: usize, S> {
+    iter: array_linked_hash_map::IntoIter<K, (), N, S>,
+}
+
+impl<K: fmt::Debug, const N: usize, S> fmt::Debug for IntoIter<K, N, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter.fmt(f)
+    }
+}
+
+impl<K, const N: usize, S> Iterator for IntoIter<K, N, S> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, const N: usize, S> DoubleEndedIterator for IntoIter<K, N, S> {
+    #[inline]
+    fn next_back(&mut self) -> Option<K> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K, const N: usize, S> ExactSizeIterator for IntoIter<K, N, S> {}
@@ -0,0 +1,241 @@
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash},
+    iter::FromIterator,
+};
+
+use crate::array_linked_hash_map::ArrayLinkedHashMap;
+use crate::{DefaultHashBuilder, Equivalent};
+
+pub use crate::array_linked_hash_map::{IntoIter, Iter, IterMut};
+
+/// An `LruCache` holding at most `N` entries, which never allocates.
+///
+/// Entries are stored in an `ArrayLinkedHashMap` from least to most recently used, and inserting
+/// a new entry into a full cache evicts the least recently used one, just like `LruCache::insert`.
+///
+/// This is available without the `alloc` feature.
+pub struct ArrayLruCache<K, V, const N: usize, S = DefaultHashBuilder> {
+    map: ArrayLinkedHashMap<K, V, N, S>,
+}
+
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
+impl<K, V, const N: usize> ArrayLruCache<K, V, N> {
+    #[inline]
+    pub fn new() -> Self {
+        ArrayLruCache {
+            map: ArrayLinkedHashMap::new(),
+        }
+    }
+}
+
+impl<K, V, const N: usize, S> ArrayLruCache<K, V, N, S> {
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        ArrayLruCache {
+            map: ArrayLinkedHashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Returns the maximum number of entries this cache holds before evicting, which is always `N`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.retain(f);
+    }
+
+    /// Remove the least recently used entry and return it.
+    ///
+    /// If the `ArrayLruCache` is empty this will return None.
+    #[inline]
+    pub fn remove_lru(&mut self) -> Option<(K, V)> {
+        self.map.pop_front()
+    }
+}
+
+impl<K: Eq + Hash, V, const N: usize, S> ArrayLruCache<K, V, N, S>
+where
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Insert a new value into the `ArrayLruCache`.
+    ///
+    /// If necessary, will remove the value at the front of the LRU list to make room.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.map.insert_evicting(k, v)
+    }
+
+    /// Get the value for the given key, *without* marking the value as recently used and moving it
+    /// to the back of the LRU list.
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.get(k)
+    }
+
+    /// Get the value for the given key mutably, *without* marking the value as recently used and
+    /// moving it to the back of the LRU list.
+    #[inline]
+    pub fn peek_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.get_mut(k)
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_mut(k).map(|v| &*v)
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.to_back(k)
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.remove(k)
+    }
+
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.remove_entry(k)
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize, S: Clone> Clone for ArrayLruCache<K, V, N, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArrayLruCache {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K, V, const N: usize, S: Default> Default for ArrayLruCache<K, V, N, S> {
+    #[inline]
+    fn default() -> Self {
+        ArrayLruCache {
+            map: ArrayLinkedHashMap::default(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, const N: usize, S: BuildHasher> Extend<(K, V)> for ArrayLruCache<K, V, N, S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, const N: usize, S: BuildHasher + Default> FromIterator<(K, V)>
+    for ArrayLruCache<K, V, N, S>
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut cache = Self::default();
+        cache.extend(iter);
+        cache
+    }
+}
+
+impl<K, V, const N: usize, S> IntoIterator for ArrayLruCache<K, V, N, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N, S>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V, N, S> {
+        self.map.into_iter()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a ArrayLruCache<K, V, N, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a mut ArrayLruCache<K, V, N, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize, S> fmt::Debug for ArrayLruCache<K, V, N, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter().rev()).finish()
+    }
+}
@@ -1,24 +1,76 @@
 #![no_std]
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "alloc")]
+mod allocator;
+pub mod array_linked_hash_map;
+pub mod array_linked_hash_set;
+pub mod array_lru_cache;
+#[cfg(feature = "alloc")]
 pub mod linked_hash_map;
+#[cfg(feature = "alloc")]
 pub mod linked_hash_set;
+#[cfg(feature = "alloc")]
 pub mod lru_cache;
+#[cfg(feature = "rayon")]
+pub mod rayon;
 #[cfg(feature = "serde_impl")]
 pub mod serde;
-
+#[cfg(feature = "alloc")]
+pub mod small_linked_hash_map;
+#[cfg(feature = "alloc")]
+pub mod ttl_lru_cache;
+#[cfg(feature = "alloc")]
+pub mod weighted_lru_cache;
+
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
 use core::hash::{BuildHasher, Hasher};
 
+#[cfg(feature = "alloc")]
+pub use allocator::{Allocator, Global};
+pub use array_linked_hash_map::ArrayLinkedHashMap;
+pub use array_linked_hash_set::ArrayLinkedHashSet;
+pub use array_lru_cache::ArrayLruCache;
+#[cfg(feature = "alloc")]
 pub use linked_hash_map::LinkedHashMap;
+#[cfg(feature = "alloc")]
 pub use linked_hash_set::LinkedHashSet;
+#[cfg(feature = "alloc")]
 pub use lru_cache::LruCache;
+#[cfg(feature = "alloc")]
+pub use small_linked_hash_map::SmallLinkedHashMap;
+#[cfg(feature = "alloc")]
+pub use ttl_lru_cache::TtlLruCache;
+#[cfg(feature = "alloc")]
+pub use weighted_lru_cache::WeightedLruCache;
+
+// Without `alloc` we cannot depend on hashbrown, so use the hasher it would have selected directly.
+#[cfg(feature = "alloc")]
+type DefaultHashBuilderImpl = hashbrown::DefaultHashBuilder;
+#[cfg(all(not(feature = "alloc"), feature = "foldhash"))]
+type DefaultHashBuilderImpl = foldhash::fast::RandomState;
+
+/// Key equivalence trait, used for looking up entries by a type other than the key type.
+///
+/// This is the trait from the `equivalent` crate, shared with hashbrown and indexmap, so the same
+/// impls work for all of them.
+pub use equivalent::Equivalent;
 
 /// Default hash builder, matches hashbrown's default hasher.
 ///
 /// See [`DefaultHasher`] for more details.
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
 #[derive(Clone, Copy, Default, Debug)]
-pub struct DefaultHashBuilder(hashbrown::DefaultHashBuilder);
+pub struct DefaultHashBuilder(DefaultHashBuilderImpl);
+
+/// Without the `alloc` or `foldhash` features there is no default hasher, so this cannot be
+/// constructed, and containers must be given a hasher through `with_hasher`.
+#[cfg(not(any(feature = "alloc", feature = "foldhash")))]
+#[derive(Clone, Copy, Debug)]
+pub enum DefaultHashBuilder {}
 
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
 impl BuildHasher for DefaultHashBuilder {
     type Hasher = DefaultHasher;
 
@@ -29,9 +81,11 @@ impl BuildHasher for DefaultHashBuilder {
 }
 
 /// Default hasher, as selected by hashbrown.
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
 #[derive(Clone)]
-pub struct DefaultHasher(<hashbrown::DefaultHashBuilder as BuildHasher>::Hasher);
+pub struct DefaultHasher(<DefaultHashBuilderImpl as BuildHasher>::Hasher);
 
+#[cfg(any(feature = "alloc", feature = "foldhash"))]
 impl Hasher for DefaultHasher {
     #[inline]
     fn write(&mut self, bytes: &[u8]) {
@@ -1,6 +1,5 @@
 use core::{
     alloc::Layout,
-    borrow::Borrow,
     cmp::Ordering,
     fmt,
     hash::{BuildHasher, Hash, Hasher},
@@ -11,16 +10,31 @@ use core::{
     ptr::{self, NonNull},
 };
 
-use alloc::boxed::Box;
-use hashbrown::hash_table::{self, HashTable};
+use crate::allocator::{hash_table, Allocator, Global, HashTable, Vec};
+use crate::{DefaultHashBuilder, Equivalent};
 
-use crate::DefaultHashBuilder;
+#[cfg(feature = "indexed")]
+mod order;
 
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum TryReserveError {
     CapacityOverflow,
     AllocError { layout: Layout },
 }
 
+/// Decides what happens when moving an entry into a `LinkedHashMap` that already contains its key,
+/// see `LinkedHashMap::append` and `LinkedHashMap::prepend`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Keep the existing entry as it is, and drop the incoming one.
+    KeepOurs,
+    /// Replace the value of the existing entry with the incoming value, without moving the entry.
+    TakeTheirs,
+    /// Replace the value of the existing entry with the incoming value, and move the entry to
+    /// where the incoming entry would have been placed.
+    TakeTheirsAndMove,
+}
+
 /// A version of `HashMap` that has a user controllable order for its entries.
 ///
 /// It achieves this by keeping its entries in an internal linked list and using a `HashMap` to
@@ -36,62 +50,90 @@ pub enum TryReserveError {
 /// * Methods that have the word `insert` will insert a new entry ot the back of the list, and if
 ///   that method might replace an entry, that method will *also move that existing entry to the
 ///   back*.
-pub struct LinkedHashMap<K, V, S = DefaultHashBuilder> {
-    table: HashTable<NonNull<Node<K, V>>>,
+///
+/// Entries are stored in a single contiguous slab of nodes which link to each other by `u32`
+/// index, so a `LinkedHashMap` can hold at most `u32::MAX - 1` entries.
+pub struct LinkedHashMap<K, V, S = DefaultHashBuilder, A: Allocator = Global> {
+    table: HashTable<u32, A>,
     // We always need to keep our custom hash builder outside of the HashTable, because it doesn't
     // know how to do any hashing itself.
     hash_builder: S,
-    // Circular linked list of nodes.  If `values` is non-null, it will point to a "guard node"
-    // which will never have an initialized key or value, `values.prev` will contain the last key /
-    // value in the list, `values.next` will contain the first key / value in the list.
-    values: Option<NonNull<Node<K, V>>>,
-    // *Singly* linked list of free nodes.  The `prev` pointers in the free list should be assumed
-    // invalid.
-    free: Option<NonNull<Node<K, V>>>,
+    // Slab of nodes forming a circular linked list, where nodes refer to each other by index.  If
+    // `values` is non-empty, the node at index `GUARD` is a "guard node" which will never have an
+    // initialized key or value, its `prev` link will contain the last key / value in the list, its
+    // `next` link will contain the first key / value in the list.
+    values: Vec<Node<K, V>, A>,
+    // *Singly* linked list of free slots in `values`, or `NIL` if there are none.  The `prev` links
+    // in the free list should be assumed invalid.
+    free: u32,
 }
 
 impl<K, V> LinkedHashMap<K, V> {
     #[inline]
     pub fn new() -> Self {
-        Self {
-            hash_builder: DefaultHashBuilder::default(),
-            table: HashTable::new(),
-            values: None,
-            free: None,
-        }
+        Self::with_hasher(DefaultHashBuilder::default())
     }
 
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
-        Self {
-            hash_builder: DefaultHashBuilder::default(),
-            table: HashTable::with_capacity(capacity),
-            values: None,
-            free: None,
-        }
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
     }
 }
 
 impl<K, V, S> LinkedHashMap<K, V, S> {
     #[inline]
     pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_hasher_in(hash_builder, Global)
+    }
+
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher_in(capacity, hash_builder, Global)
+    }
+}
+
+impl<K, V, A: Allocator + Clone> LinkedHashMap<K, V, DefaultHashBuilder, A> {
+    /// Creates an empty `LinkedHashMap` which allocates its hash table and its entries with the
+    /// given allocator.
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_hasher_in(DefaultHashBuilder::default(), alloc)
+    }
+
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self::with_capacity_and_hasher_in(capacity, DefaultHashBuilder::default(), alloc)
+    }
+}
+
+impl<K, V, S, A: Allocator + Clone> LinkedHashMap<K, V, S, A> {
+    #[inline]
+    pub fn with_hasher_in(hash_builder: S, alloc: A) -> Self {
         Self {
             hash_builder,
-            table: HashTable::new(),
-            values: None,
-            free: None,
+            table: HashTable::new_in(alloc.clone()),
+            values: Vec::new_in(alloc),
+            free: NIL,
         }
     }
 
     #[inline]
-    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+    pub fn with_capacity_and_hasher_in(capacity: usize, hash_builder: S, alloc: A) -> Self {
         Self {
             hash_builder,
-            table: HashTable::with_capacity(capacity),
-            values: None,
-            free: None,
+            table: HashTable::with_capacity_in(capacity, alloc.clone()),
+            values: values_with_capacity_in(capacity, alloc),
+            free: NIL,
         }
     }
+}
+
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A> {
+    /// Returns a reference to the allocator used by this map.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.values.allocator()
+    }
 
     #[inline]
     pub fn len(&self) -> usize {
@@ -106,29 +148,26 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
     #[inline]
     pub fn clear(&mut self) {
         self.table.clear();
-        if let Some(mut values) = self.values {
+        if !self.values.is_empty() {
             unsafe {
-                drop_value_nodes(values);
-                values.as_mut().links.value = ValueLinks {
-                    prev: values,
-                    next: values,
-                };
+                // Every slot but the guard becomes free, so we can simply truncate the slab.  We
+                // unlink everything *before* dropping any keys or values, in case one of them
+                // panics.
+                let values = self.values.as_mut_ptr();
+                let tail = (*values).links.prev;
+                (*values).unlink_all();
+                self.values.truncate(1);
+                self.free = NIL;
+                drop_value_nodes(values, tail);
             }
         }
     }
 
     #[inline]
-    pub fn iter(&self) -> Iter<K, V> {
-        let (head, tail) = if let Some(values) = self.values {
-            unsafe {
-                let ValueLinks { next, prev } = values.as_ref().links.value;
-                (next.as_ptr(), prev.as_ptr())
-            }
-        } else {
-            (ptr::null_mut(), ptr::null_mut())
-        };
-
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let (head, tail) = guard_links(&self.values);
         Iter {
+            values: self.values.as_ptr(),
             head,
             tail,
             remaining: self.len(),
@@ -137,17 +176,10 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
     }
 
     #[inline]
-    pub fn iter_mut(&mut self) -> IterMut<K, V> {
-        let (head, tail) = if let Some(values) = self.values {
-            unsafe {
-                let ValueLinks { next, prev } = values.as_ref().links.value;
-                (Some(next), Some(prev))
-            }
-        } else {
-            (None, None)
-        };
-
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let (head, tail) = guard_links(&self.values);
         IterMut {
+            values: self.values.as_mut_ptr(),
             head,
             tail,
             remaining: self.len(),
@@ -157,22 +189,17 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
 
     #[inline]
     pub fn drain(&mut self) -> Drain<'_, K, V> {
-        unsafe {
-            let (head, tail) = if let Some(mut values) = self.values {
-                let ValueLinks { next, prev } = values.as_ref().links.value;
-                values.as_mut().links.value = ValueLinks {
-                    next: values,
-                    prev: values,
-                };
-                (Some(next), Some(prev))
-            } else {
-                (None, None)
-            };
-            let len = self.len();
+        let (head, tail) = guard_links(&self.values);
+        if let Some(guard) = self.values.first_mut() {
+            guard.unlink_all();
+        }
+        let len = self.len();
 
-            self.table.clear();
+        self.table.clear();
 
+        unsafe {
             Drain {
+                values: NonNull::new_unchecked(self.values.as_mut_ptr()),
                 free: (&mut self.free).into(),
                 head,
                 tail,
@@ -183,17 +210,17 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
     }
 
     #[inline]
-    pub fn keys(&self) -> Keys<K, V> {
+    pub fn keys(&self) -> Keys<'_, K, V> {
         Keys { inner: self.iter() }
     }
 
     #[inline]
-    pub fn values(&self) -> Values<K, V> {
+    pub fn values(&self) -> Values<'_, K, V> {
         Values { inner: self.iter() }
     }
 
     #[inline]
-    pub fn values_mut(&mut self) -> ValuesMut<K, V> {
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
         ValuesMut {
             inner: self.iter_mut(),
         }
@@ -205,8 +232,8 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
             return None;
         }
         unsafe {
-            let front = (*self.values.as_ptr()).links.value.next.as_ptr();
-            let (key, value) = (*front).entry_ref();
+            let front = self.values[GUARD as usize].links.next;
+            let (key, value) = (*node_at(self.values.as_ptr(), front)).entry_ref();
             Some((key, value))
         }
     }
@@ -217,25 +244,69 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
             return None;
         }
         unsafe {
-            let back = &*(*self.values.as_ptr()).links.value.prev.as_ptr();
-            let (key, value) = (*back).entry_ref();
+            let back = self.values[GUARD as usize].links.prev;
+            let (key, value) = (*node_at(self.values.as_ptr(), back)).entry_ref();
             Some((key, value))
         }
     }
 
+    /// Returns the `Cursor` over the front node.
+    ///
+    /// Note: The `Cursor` is pointing to the _guard_ node in an empty `LinkedHashMap` and will
+    ///       always return `None` as its current element, regardless of any move in any direction.
+    #[inline]
+    pub fn cursor_front(&self) -> Cursor<'_, K, V> {
+        let mut c = self.cursor(GUARD);
+        c.move_next();
+        c
+    }
+
+    /// Returns the `Cursor` over the back node.
+    ///
+    /// Note: The `Cursor` is pointing to the _guard_ node in an empty `LinkedHashMap` and will
+    ///       always return `None` as its current element, regardless of any move in any direction.
+    #[inline]
+    pub fn cursor_back(&self) -> Cursor<'_, K, V> {
+        let mut c = self.cursor(GUARD);
+        c.move_prev();
+        c
+    }
+
+    #[inline]
+    fn cursor(&self, cur: u32) -> Cursor<'_, K, V> {
+        Cursor {
+            cur,
+            values: &self.values,
+        }
+    }
+
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.retain_evicting(f, |_, _| {});
+    }
+
+    /// Like `LinkedHashMap::retain`, but moves every removed entry into `evicted` rather than
+    /// dropping it.
     #[inline]
-    pub fn retain<F>(&mut self, mut f: F)
+    pub(crate) fn retain_evicting<F, G>(&mut self, mut f: F, evicted: G)
     where
         F: FnMut(&K, &mut V) -> bool,
+        G: FnMut(K, V),
     {
+        let values = self.values.as_mut_ptr();
         let free = self.free;
         let mut drop_filtered_values = DropFilteredValues {
+            values,
             free: &mut self.free,
             cur_free: free,
+            end_free: None,
         };
 
         self.table.retain(|&mut node| unsafe {
-            let (k, v) = (*node.as_ptr()).entry_mut();
+            let (k, v) = (*node_at_mut(values, node)).entry_mut();
             if f(k, v) {
                 true
             } else {
@@ -243,6 +314,7 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
                 false
             }
         });
+        drop_filtered_values.take_each(evicted);
     }
 
     #[inline]
@@ -254,21 +326,307 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
     pub fn capacity(&self) -> usize {
         self.table.capacity()
     }
+
+    /// Returns the number of unused nodes held on the internal free list.
+    ///
+    /// Removing an entry keeps its node around on the free list, and inserting a new entry reuses
+    /// a free node before allocating a new one.
+    #[inline]
+    pub fn free_nodes(&self) -> usize {
+        self.values.len().saturating_sub(1) - self.len()
+    }
+
+    /// Returns the total number of bytes currently allocated by the map, including the hash table,
+    /// the live nodes, the free nodes and any spare capacity of the node slab.
+    ///
+    /// This does not include any memory owned by the keys and values themselves.
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.table.allocation_size() + self.values.capacity() * mem::size_of::<Node<K, V>>()
+    }
+
+    /// Allocates nodes until the internal free list holds at least `additional` nodes.
+    ///
+    /// Together with `LinkedHashMap::reserve`, this ensures that the next `additional` inserts of
+    /// new keys will not allocate.  Free nodes are only ever released by
+    /// `LinkedHashMap::shrink_to_fit`, `LinkedHashMap::shrink_to` and
+    /// `LinkedHashMap::shrink_free_list`.  `LinkedHashMap::clear` also empties the free list, but
+    /// keeps the memory of every node as spare capacity, so inserting afterwards still does not
+    /// allocate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total number of nodes would overflow the map's maximum capacity.
+    #[inline]
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        let missing = additional.saturating_sub(self.free_nodes());
+        if missing == 0 {
+            return;
+        }
+
+        ensure_guard_node(&mut self.values);
+        let total = self
+            .values
+            .len()
+            .checked_add(missing)
+            .expect("capacity overflow");
+        if total > NIL as usize {
+            panic!("capacity overflow");
+        }
+
+        self.values.reserve(missing);
+        for _ in 0..missing {
+            let index = self.values.len() as u32;
+            self.values.push(Node::new(Links {
+                next: NIL,
+                prev: NIL,
+            }));
+            unsafe { push_free(self.values.as_mut_ptr(), &mut self.free, index) };
+        }
+    }
+
+    /// Sorts the entries of the map with the given comparison function.
+    ///
+    /// This sort is stable, and only relinks the existing entries of the internal linked list,
+    /// without rehashing any keys.
+    #[inline]
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        A: Clone,
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.sort_nodes(|values, nodes| {
+            nodes.sort_unstable_by(|&(ai, a), &(bi, b)| unsafe {
+                let (ak, av) = (*node_at(values, a)).entry_ref();
+                let (bk, bv) = (*node_at(values, b)).entry_ref();
+                cmp(ak, av, bk, bv).then(ai.cmp(&bi))
+            })
+        });
+    }
+
+    /// Sorts the entries of the map with the given key extraction function.
+    ///
+    /// This sort is stable, and only relinks the existing entries of the internal linked list,
+    /// without rehashing any keys.
+    #[inline]
+    pub fn sort_by_key<T, F>(&mut self, mut f: F)
+    where
+        A: Clone,
+        T: Ord,
+        F: FnMut(&K, &V) -> T,
+    {
+        self.sort_nodes(|values, nodes| {
+            nodes.sort_unstable_by(|&(ai, a), &(bi, b)| unsafe {
+                let (ak, av) = (*node_at(values, a)).entry_ref();
+                let (bk, bv) = (*node_at(values, b)).entry_ref();
+                f(ak, av).cmp(&f(bk, bv)).then(ai.cmp(&bi))
+            })
+        });
+    }
+
+    /// Sorts the entries of the map by their keys.
+    ///
+    /// This sort is stable, and only relinks the existing entries of the internal linked list,
+    /// without rehashing any keys.
+    #[inline]
+    pub fn sort_keys(&mut self)
+    where
+        A: Clone,
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| k1.cmp(k2));
+    }
+
+    /// Sorts the entries of the map with the given comparison function, but might not preserve
+    /// the order of equal entries.
+    ///
+    /// Only relinks the existing entries of the internal linked list, without rehashing any keys.
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, mut cmp: F)
+    where
+        A: Clone,
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.sort_nodes(|values, nodes| {
+            nodes.sort_unstable_by(|&(_, a), &(_, b)| unsafe {
+                let (ak, av) = (*node_at(values, a)).entry_ref();
+                let (bk, bv) = (*node_at(values, b)).entry_ref();
+                cmp(ak, av, bk, bv)
+            })
+        });
+    }
+
+    /// Sorts the entries of the map with the given key extraction function, calling it only once
+    /// per entry.
+    ///
+    /// This sort is stable, and only relinks the existing entries of the internal linked list,
+    /// without rehashing any keys.
+    #[inline]
+    pub fn sort_by_cached_key<T, F>(&mut self, mut f: F)
+    where
+        A: Clone,
+        T: Ord,
+        F: FnMut(&K, &V) -> T,
+    {
+        let alloc = self.allocator().clone();
+        self.sort_nodes(|values, nodes| {
+            let mut keyed = Vec::with_capacity_in(nodes.len(), alloc);
+            for &(i, n) in nodes.iter() {
+                let (k, v) = unsafe { (*node_at(values, n)).entry_ref() };
+                keyed.push((f(k, v), i, n));
+            }
+            keyed.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+            for (node, &(_, i, n)) in nodes.iter_mut().zip(keyed.iter()) {
+                *node = (i, n);
+            }
+        });
+    }
+
+    /// Reverses the order of the entries in the internal linked list.
+    #[inline]
+    pub fn reverse(&mut self) {
+        if self.len() < 2 {
+            return;
+        }
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            let mut cur = GUARD;
+            loop {
+                let links = &mut (*node_at_mut(values, cur)).links;
+                mem::swap(&mut links.next, &mut links.prev);
+                // After the swap, `prev` is the node that used to come next.
+                cur = links.prev;
+                if cur == GUARD {
+                    break;
+                }
+            }
+            #[cfg(feature = "indexed")]
+            order::rebuild(values);
+        }
+    }
+
+    /// Rotates the internal linked list so that the first `n` entries move to the back, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the map.
+    #[inline]
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.len();
+        assert!(n <= len, "rotate_left: {} is out of bounds", n);
+        if n == 0 || n == len {
+            return;
+        }
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            // Find the entry that will become the new front, walking from whichever end is closer.
+            let new_front = if n <= len / 2 {
+                let mut cur = (*values).links.next;
+                for _ in 0..n {
+                    cur = (*node_at(values, cur)).links.next;
+                }
+                cur
+            } else {
+                let mut cur = GUARD;
+                for _ in n..len {
+                    cur = (*node_at(values, cur)).links.prev;
+                }
+                cur
+            };
+            move_guard_before(values, new_front);
+        }
+    }
+
+    /// Rotates the internal linked list so that the last `n` entries move to the front, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the map.
+    #[inline]
+    pub fn rotate_right(&mut self, n: usize) {
+        let len = self.len();
+        assert!(n <= len, "rotate_right: {} is out of bounds", n);
+        self.rotate_left(len - n);
+    }
+
+    // Collects the nodes of the list in order, each paired with its position, into a buffer from
+    // the map's allocator, lets `sort` reorder them, then relinks the list to match.  If `sort`
+    // panics, the list is left untouched.
+    //
+    // The stable sorts break ties by position and use the unstable sort, which unlike the stable
+    // one needs no scratch space from the global allocator.
+    fn sort_nodes<F>(&mut self, sort: F)
+    where
+        A: Clone,
+        F: FnOnce(*const Node<K, V>, &mut [(u32, u32)]),
+    {
+        if self.len() < 2 {
+            return;
+        }
+        let mut nodes = Vec::with_capacity_in(self.len(), self.allocator().clone());
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            let mut cur = (*values).links.next;
+            let mut position = 0;
+            while cur != GUARD {
+                nodes.push((position, cur));
+                position += 1;
+                cur = (*node_at(values, cur)).links.next;
+            }
+            sort(values, &mut nodes);
+            relink_nodes(values, nodes.iter().map(|&(_, n)| n));
+        }
+    }
 }
 
-impl<K, V, S> LinkedHashMap<K, V, S>
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A>
 where
     K: Eq + Hash,
     S: BuildHasher,
 {
     #[inline]
-    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
-        match self.raw_entry_mut().from_key(&key) {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S, A> {
+        let hash = hash_key(&self.hash_builder, &key);
+        match self.raw_entry_mut().from_key_hashed_nocheck(hash, &key) {
             RawEntryMut::Occupied(occupied) => Entry::Occupied(OccupiedEntry {
-                key,
+                key: Some(key),
                 raw_entry: occupied,
             }),
             RawEntryMut::Vacant(vacant) => Entry::Vacant(VacantEntry {
+                hash,
+                key,
+                raw_entry: vacant,
+            }),
+        }
+    }
+
+    /// Similar to `LinkedHashMap::entry`, but if the entry is vacant, first makes sure that there is
+    /// room to insert it, returning an error rather than panicking or aborting if this fails.
+    ///
+    /// Inserting into a vacant entry returned by this method will never allocate.
+    #[inline]
+    pub fn try_entry(&mut self, key: K) -> Result<Entry<'_, K, V, S, A>, TryReserveError> {
+        if self.find_node(&key).is_none() {
+            self.try_reserve(1)?;
+        }
+        Ok(self.entry(key))
+    }
+
+    /// Similar to `LinkedHashMap::entry`, but takes a borrowed form of the key, and only converts
+    /// it into an owned key if a new entry is inserted.
+    #[inline]
+    pub fn entry_ref<'b, Q>(&mut self, key: &'b Q) -> EntryRef<'_, 'b, K, Q, V, S, A>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = hash_key(&self.hash_builder, key);
+        match self.raw_entry_mut().from_key_hashed_nocheck(hash, key) {
+            RawEntryMut::Occupied(occupied) => EntryRef::Occupied(OccupiedEntryRef {
+                key,
+                raw_entry: occupied,
+            }),
+            RawEntryMut::Vacant(vacant) => EntryRef::Vacant(VacantEntryRef {
+                hash,
                 key,
                 raw_entry: vacant,
             }),
@@ -278,8 +636,7 @@ where
     #[inline]
     pub fn get<Q>(&self, k: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.raw_entry().from_key(k).map(|(_, v)| v)
     }
@@ -287,8 +644,7 @@ where
     #[inline]
     pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.raw_entry().from_key(k)
     }
@@ -296,8 +652,7 @@ where
     #[inline]
     pub fn contains_key<Q>(&self, k: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.get(k).is_some()
     }
@@ -305,8 +660,7 @@ where
     #[inline]
     pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         match self.raw_entry_mut().from_key(k) {
             RawEntryMut::Occupied(occupied) => Some(occupied.into_mut()),
@@ -314,6 +668,38 @@ where
         }
     }
 
+    /// Returns mutable references to the values for all of the given keys at once.
+    ///
+    /// Returns `None` if any of the keys is missing, or if any two of the keys refer to the same
+    /// entry.
+    #[inline]
+    pub fn get_many_mut<Q, const N: usize>(&mut self, ks: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        unsafe { self.get_many_nodes_mut(ks, true, false, |_| {}) }
+    }
+
+    /// Returns mutable references to the values for all of the given keys at once, without
+    /// checking that the keys refer to distinct entries.
+    ///
+    /// Returns `None` if any of the keys is missing.
+    ///
+    /// # Safety
+    ///
+    /// No two of the given keys may refer to the same entry, otherwise the returned references
+    /// would alias.
+    #[inline]
+    pub unsafe fn get_many_unchecked_mut<Q, const N: usize>(
+        &mut self,
+        ks: [&Q; N],
+    ) -> Option<[&mut V; N]>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_many_nodes_mut(ks, false, false, |_| {})
+    }
+
     /// Inserts the given key / value pair at the *back* of the internal linked list.
     ///
     /// Returns the previously set value, if one existed prior to this call.  After this call,
@@ -332,6 +718,21 @@ where
         }
     }
 
+    /// Similar to `LinkedHashMap::insert`, but returns an error rather than panicking or aborting
+    /// if room for a new entry cannot be allocated.
+    ///
+    /// If an error is returned, the map is left unchanged.
+    #[inline]
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<Option<V>, TryReserveError> {
+        match self.try_entry(k)? {
+            Entry::Occupied(mut occupied) => Ok(Some(occupied.insert(v))),
+            Entry::Vacant(vacant) => {
+                vacant.insert(v);
+                Ok(None)
+            }
+        }
+    }
+
     /// If the given key is not in this map, inserts the key / value pair at the *back* of the
     /// internal linked list and returns `None`, otherwise, replaces the existing value with the
     /// given value *without* moving the entry in the internal linked list and returns the previous
@@ -350,8 +751,7 @@ where
     #[inline]
     pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         match self.raw_entry_mut().from_key(k) {
             RawEntryMut::Occupied(occupied) => Some(occupied.remove()),
@@ -362,8 +762,7 @@ where
     #[inline]
     pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         match self.raw_entry_mut().from_key(k) {
             RawEntryMut::Occupied(occupied) => Some(occupied.remove_entry()),
@@ -376,17 +775,8 @@ where
         if self.is_empty() {
             return None;
         }
-        unsafe {
-            let front = (*self.values.as_ptr()).links.value.next;
-            let hash = hash_node(&self.hash_builder, front);
-            match self
-                .raw_entry_mut()
-                .from_hash(hash, |k| k.eq(front.as_ref().key_ref()))
-            {
-                RawEntryMut::Occupied(occupied) => Some(occupied.remove_entry()),
-                RawEntryMut::Vacant(_) => None,
-            }
-        }
+        let front = self.values[GUARD as usize].links.next;
+        Some(self.take_node(front))
     }
 
     #[inline]
@@ -394,17 +784,8 @@ where
         if self.is_empty() {
             return None;
         }
-        unsafe {
-            let back = (*self.values.as_ptr()).links.value.prev;
-            let hash = hash_node(&self.hash_builder, back);
-            match self
-                .raw_entry_mut()
-                .from_hash(hash, |k| k.eq(back.as_ref().key_ref()))
-            {
-                RawEntryMut::Occupied(occupied) => Some(occupied.remove_entry()),
-                RawEntryMut::Vacant(_) => None,
-            }
-        }
+        let back = self.values[GUARD as usize].links.prev;
+        Some(self.take_node(back))
     }
 
     /// If an entry with this key exists, move it to the front of the list and return a reference to
@@ -412,8 +793,7 @@ where
     #[inline]
     pub fn to_front<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         match self.raw_entry_mut().from_key(k) {
             RawEntryMut::Occupied(mut occupied) => {
@@ -429,8 +809,7 @@ where
     #[inline]
     pub fn to_back<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         match self.raw_entry_mut().from_key(k) {
             RawEntryMut::Occupied(mut occupied) => {
@@ -441,80 +820,435 @@ where
         }
     }
 
+    /// If entries with both keys exist, swap their positions in the list and return `true`.
     #[inline]
-    pub fn reserve(&mut self, additional: usize) {
-        let hash_builder = &self.hash_builder;
-        self.table
-            .reserve(additional, move |&n| unsafe { hash_node(hash_builder, n) });
-    }
-
-    #[inline]
-    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        let hash_builder = &self.hash_builder;
-        self.table
-            .try_reserve(additional, move |&n| unsafe { hash_node(hash_builder, n) })
-            .map_err(|e| match e {
-                hashbrown::TryReserveError::CapacityOverflow => TryReserveError::CapacityOverflow,
-                hashbrown::TryReserveError::AllocError { layout } => {
-                    TryReserveError::AllocError { layout }
+    pub fn swap_positions<Q>(&mut self, a: &Q, b: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (a, b) = match (self.find_node(a), self.find_node(b)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return false,
+        };
+        if a != b {
+            unsafe {
+                let values = self.values.as_mut_ptr();
+                let after_a = (*node_at(values, a)).links.next;
+                detach_node(values, a);
+                if after_a == b {
+                    attach_before(values, a, (*node_at(values, b)).links.next);
+                } else {
+                    attach_before(values, a, b);
+                    detach_node(values, b);
+                    attach_before(values, b, after_a);
                 }
-            })
+            }
+        }
+        true
     }
 
+    /// If entries with both keys exist, move the entry for `k` so that it is immediately before
+    /// the entry for `anchor` in the list and return a reference to its value.
     #[inline]
-    pub fn shrink_to_fit(&mut self) {
-        let hash_builder = &self.hash_builder;
+    pub fn move_before<Q>(&mut self, k: &Q, anchor: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(k)?;
+        let anchor = self.find_node(anchor)?;
         unsafe {
-            self.table
-                .shrink_to_fit(move |&n| hash_node(hash_builder, n));
-            drop_free_nodes(self.free.take());
+            let values = self.values.as_mut_ptr();
+            if node != anchor {
+                detach_node(values, node);
+                attach_before(values, node, anchor);
+            }
+            Some(&mut (*node_at_mut(values, node)).entry_mut().1)
         }
     }
 
-    pub fn retain_with_order<F>(&mut self, mut f: F)
+    /// If entries with both keys exist, move the entry for `k` so that it is immediately after
+    /// the entry for `anchor` in the list and return a reference to its value.
+    #[inline]
+    pub fn move_after<Q>(&mut self, k: &Q, anchor: &Q) -> Option<&mut V>
     where
-        F: FnMut(&K, &mut V) -> bool,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        let free = self.free;
-        let mut drop_filtered_values = DropFilteredValues {
-            free: &mut self.free,
-            cur_free: free,
-        };
-
-        if let Some(values) = self.values {
-            unsafe {
-                let mut cur = values.as_ref().links.value.next;
-                while cur != values {
-                    let next = cur.as_ref().links.value.next;
-                    let filter = {
-                        let (k, v) = (*cur.as_ptr()).entry_mut();
-                        !f(k, v)
-                    };
-                    if filter {
-                        let k = (*cur.as_ptr()).key_ref();
-                        let hash = hash_key(&self.hash_builder, k);
-                        self.table
-                            .find_entry(hash, |o| (*o).as_ref().key_ref().eq(k))
-                            .unwrap()
-                            .remove();
-                        drop_filtered_values.drop_later(cur);
-                    }
-                    cur = next;
-                }
+        let node = self.find_node(k)?;
+        let anchor = self.find_node(anchor)?;
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            if node != anchor {
+                detach_node(values, node);
+                attach_before(values, node, (*node_at(values, anchor)).links.next);
             }
+            Some(&mut (*node_at_mut(values, node)).entry_mut().1)
         }
     }
 
-    // Returns the `CursorMut` over the _guard_ node.
-    fn cursor_mut(&mut self) -> CursorMut<K, V, S> {
-        unsafe { ensure_guard_node(&mut self.values) };
-        CursorMut {
-            cur: self.values.as_ptr(),
-            hash_builder: &self.hash_builder,
-            free: &mut self.free,
-            values: &mut self.values,
-            table: &mut self.table,
-        }
+    /// Moves every entry of `other` onto the back of this map, in order, leaving `other` empty.
+    ///
+    /// Entries whose keys are already present in this map are handled according to `policy`.
+    /// Whatever the policy, the existing key is always kept.
+    ///
+    /// This takes time proportional to the length of `other`, and hashes each of its keys once.
+    #[inline]
+    pub fn append(&mut self, other: &mut Self, policy: CollisionPolicy) {
+        self.merge(other, policy, false);
+    }
+
+    /// Moves every entry of `other` onto the front of this map, in order, leaving `other` empty.
+    ///
+    /// Entries whose keys are already present in this map are handled according to `policy`.
+    /// Whatever the policy, the existing key is always kept.
+    ///
+    /// This takes time proportional to the length of `other`, and hashes each of its keys once.
+    #[inline]
+    pub fn prepend(&mut self, other: &mut Self, policy: CollisionPolicy) {
+        self.merge(other, policy, true);
+    }
+
+    /// Splits the map in two at the given key, returning a new map containing the entry for `k`
+    /// and every entry after it.
+    ///
+    /// Returns `None` and leaves the map untouched if `k` is not present.  This takes time
+    /// proportional to the number of entries moved into the returned map.
+    #[inline]
+    pub fn split_off<Q>(&mut self, k: &Q) -> Option<Self>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        S: Clone,
+        A: Clone,
+    {
+        let node = self.find_node(k)?;
+        let mut count = 0;
+        let mut cur = node;
+        while cur != GUARD {
+            count += 1;
+            cur = unsafe { (*node_at(self.values.as_ptr(), cur)).links.next };
+        }
+        Some(self.split_nodes(node, count))
+    }
+
+    /// Splits the map in two, returning a new map containing the first `n` entries and leaving the
+    /// rest in this map.
+    ///
+    /// This takes time proportional to `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the map.
+    #[inline]
+    pub fn split_off_front(&mut self, n: usize) -> Self
+    where
+        S: Clone,
+        A: Clone,
+    {
+        assert!(n <= self.len(), "split_off_front: {} is out of bounds", n);
+        let (front, _) = guard_links(&self.values);
+        self.split_nodes(front, n)
+    }
+
+    // Moves every entry of `other` onto the front or the back of this map.  Each incoming entry is
+    // placed directly after the one placed before it, so neither the order of `other` nor of the
+    // entries already here needs fixing up afterwards.
+    //
+    // The two maps' hash builders are not necessarily equivalent, so each key is hashed once with
+    // ours, which both finds a colliding entry and places a new one.
+    fn merge(&mut self, other: &mut Self, policy: CollisionPolicy, front: bool) {
+        if other.is_empty() {
+            return;
+        }
+        self.reserve(other.len());
+        ensure_guard_node(&mut self.values);
+        // The node the next incoming entry goes after.
+        let mut last = if front {
+            GUARD
+        } else {
+            guard_links(&self.values).1
+        };
+
+        for (k, v) in other.drain() {
+            let hash = hash_key(&self.hash_builder, &k);
+            let node = match self.raw_entry_mut().from_key_hashed_nocheck(hash, &k) {
+                RawEntryMut::Occupied(mut occupied) => match policy {
+                    CollisionPolicy::KeepOurs => continue,
+                    CollisionPolicy::TakeTheirs => {
+                        occupied.replace_value(v);
+                        continue;
+                    }
+                    CollisionPolicy::TakeTheirsAndMove => {
+                        occupied.replace_value(v);
+                        *occupied.entry.get()
+                    }
+                },
+                RawEntryMut::Vacant(vacant) => {
+                    let before = unsafe { (*node_at(vacant.values.as_ptr(), last)).links.next };
+                    last = *vacant.insert_node(hash, k, v, before).entry.get();
+                    continue;
+                }
+            };
+
+            unsafe {
+                let values = self.values.as_mut_ptr();
+                if node != last && (*node_at(values, last)).links.next != node {
+                    detach_node(values, node);
+                    attach_before(values, node, (*node_at(values, last)).links.next);
+                }
+            }
+            last = node;
+        }
+    }
+
+    // Moves `count` entries, starting at `node` and following the list order, into a new map with
+    // a clone of our hash builder.  Since the hash builders are equivalent, each key only needs to
+    // be hashed once.
+    fn split_nodes(&mut self, node: u32, count: usize) -> Self
+    where
+        S: Clone,
+        A: Clone,
+    {
+        unsafe {
+            split_nodes(
+                &self.hash_builder,
+                &mut self.values,
+                &mut self.free,
+                &mut self.table,
+                node,
+                count,
+            )
+        }
+    }
+
+    // Inserts an entry at the back of the list, with a key which must not already be present.
+    unsafe fn push_back_unique(&mut self, hash: u64, k: K, v: V) {
+        ensure_guard_node(&mut self.values);
+        let node = allocate_node(&mut self.values, &mut self.free);
+        let values = self.values.as_mut_ptr();
+        seed_order(values, &self.hash_builder);
+        (*node_at_mut(values, node)).put_entry(hash, (k, v));
+        attach_before(values, node, GUARD);
+        let hash_builder = &self.hash_builder;
+        self.table
+            .insert_unique(hash, node, move |&n| hash_node(hash_builder, values, n));
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let hash_builder = &self.hash_builder;
+        let values = self.values.as_ptr();
+        self.table.reserve(additional, move |&n| unsafe {
+            hash_node(hash_builder, values, n)
+        });
+        reserve_values(&mut self.values, self.table.len(), additional);
+    }
+
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let hash_builder = &self.hash_builder;
+        let values = self.values.as_ptr();
+        self.table
+            .try_reserve(additional, move |&n| unsafe {
+                hash_node(hash_builder, values, n)
+            })
+            .map_err(|e| match e {
+                hashbrown::TryReserveError::CapacityOverflow => TryReserveError::CapacityOverflow,
+                hashbrown::TryReserveError::AllocError { layout } => {
+                    TryReserveError::AllocError { layout }
+                }
+            })?;
+        try_reserve_values(&mut self.values, self.table.len(), additional)
+    }
+
+    /// Similar to `Extend::extend`, but returns an error rather than panicking or aborting if room
+    /// for a new entry cannot be allocated.
+    ///
+    /// The entries are first gathered into a buffer allocated with the map's allocator, and room
+    /// for every new key is reserved before any of them are inserted.  If an error is returned, the
+    /// map is left unchanged and the entries from the iterator are dropped.
+    pub fn try_extend<I: IntoIterator<Item = (K, V)>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), TryReserveError>
+    where
+        A: Clone,
+    {
+        let iter = iter.into_iter();
+        let mut entries = Vec::new_in(self.allocator().clone());
+        try_reserve_buffer(&mut entries, iter.size_hint().0)?;
+        for (k, v) in iter {
+            if entries.len() == entries.capacity() {
+                try_reserve_buffer(&mut entries, 1)?;
+            }
+            entries.push((hash_key(&self.hash_builder, &k), k, v));
+        }
+
+        // Keys repeated within `entries` are counted more than once, which only over-reserves.
+        let missing = entries
+            .iter()
+            .filter(|(hash, k, _)| self.raw_entry().from_key_hashed_nocheck(*hash, k).is_none())
+            .count();
+        self.try_reserve(missing)?;
+
+        for (hash, k, v) in entries.drain(..) {
+            match self.raw_entry_mut().from_key_hashed_nocheck(hash, &k) {
+                RawEntryMut::Occupied(mut occupied) => {
+                    occupied.to_back();
+                    occupied.replace_value(v);
+                }
+                RawEntryMut::Vacant(vacant) => {
+                    vacant.insert_hashed_nocheck(hash, k, v);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Similar to `Clone::clone`, but returns an error rather than panicking or aborting if the
+    /// new map cannot be allocated.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, TryReserveError>
+    where
+        K: Clone,
+        V: Clone,
+        S: Clone,
+        A: Clone,
+    {
+        let mut map = Self::with_hasher_in(self.hash_builder.clone(), self.allocator().clone());
+        map.try_reserve(self.len())?;
+        map.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(map)
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    ///
+    /// This also compacts the internal node slab, releasing every free node and laying out the
+    /// remaining entries contiguously in list order.
+    #[inline]
+    pub fn shrink_to_fit(&mut self)
+    where
+        A: Clone,
+    {
+        let len = self.len();
+        unsafe { compact_values(&mut self.values, &mut self.free, &mut self.table, len, 0) };
+        let hash_builder = &self.hash_builder;
+        let values = self.values.as_ptr();
+        self.table
+            .shrink_to_fit(move |&n| unsafe { hash_node(hash_builder, values, n) });
+    }
+
+    /// Shrinks the capacity of the map with a lower limit.  It will drop down no lower than the
+    /// supplied limit while maintaining the internal rules and possibly leaving some space in
+    /// accordance with the resize policy.
+    ///
+    /// If the node slab has room for more than `min_capacity` entries, it is compacted just like
+    /// with `LinkedHashMap::shrink_to_fit`, releasing every free node.
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize)
+    where
+        A: Clone,
+    {
+        let capacity = min_capacity.max(self.len());
+        if self.values.capacity() > capacity + 1 {
+            unsafe {
+                compact_values(
+                    &mut self.values,
+                    &mut self.free,
+                    &mut self.table,
+                    capacity,
+                    0,
+                )
+            };
+        }
+        let hash_builder = &self.hash_builder;
+        let values = self.values.as_ptr();
+        self.table.shrink_to(min_capacity, move |&n| unsafe {
+            hash_node(hash_builder, values, n)
+        });
+    }
+
+    /// Releases nodes from the internal free list until it holds at most `max_free` nodes.
+    ///
+    /// If any nodes are released, this compacts the internal node slab like
+    /// `LinkedHashMap::shrink_to_fit`, leaving room for exactly the live entries and the remaining
+    /// free nodes.  The hash table is not shrunk.
+    #[inline]
+    pub fn shrink_free_list(&mut self, max_free: usize)
+    where
+        A: Clone,
+    {
+        if self.free_nodes() > max_free {
+            let capacity = self.len() + max_free;
+            unsafe {
+                compact_values(
+                    &mut self.values,
+                    &mut self.free,
+                    &mut self.table,
+                    capacity,
+                    max_free,
+                )
+            };
+        }
+    }
+
+    /// Returns an iterator which walks the map in list order, removing and yielding every entry for
+    /// which `pred` returns `true`.
+    ///
+    /// Entries are only removed as the iterator is advanced.  If it is dropped before being fully
+    /// consumed, the remaining entries are simply kept.
+    #[inline]
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F, S, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            state: ExtractIfState::new(self),
+            pred,
+        }
+    }
+
+    pub fn retain_with_order<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let values = self.values.as_mut_ptr();
+        let free = self.free;
+        let mut drop_filtered_values = DropFilteredValues {
+            values,
+            free: &mut self.free,
+            cur_free: free,
+            end_free: None,
+        };
+
+        if !self.values.is_empty() {
+            unsafe {
+                let mut cur = (*values).links.next;
+                while cur != GUARD {
+                    let next = (*node_at(values, cur)).links.next;
+                    let filter = {
+                        let (k, v) = (*node_at_mut(values, cur)).entry_mut();
+                        !f(k, v)
+                    };
+                    if filter {
+                        let hash = hash_node(&self.hash_builder, values, cur);
+                        self.table.find_entry(hash, |&o| o == cur).unwrap().remove();
+                        drop_filtered_values.drop_later(cur);
+                    }
+                    cur = next;
+                }
+            }
+        }
+    }
+
+    // Returns the `CursorMut` over the _guard_ node.
+    fn cursor_mut(&mut self) -> CursorMut<'_, K, V, S, A> {
+        ensure_guard_node(&mut self.values);
+        CursorMut {
+            cur: GUARD,
+            hash_builder: &self.hash_builder,
+            free: &mut self.free,
+            values: &mut self.values,
+            table: &mut self.table,
+        }
     }
 
     /// Returns the `CursorMut` over the front node.
@@ -522,256 +1256,1011 @@ where
     /// Note: The `CursorMut` is pointing to the _guard_ node in an empty `LinkedHashMap` and
     ///       will always return `None` as its current element, regardless of any move in any
     ///       direction.
-    pub fn cursor_front_mut(&mut self) -> CursorMut<K, V, S> {
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, K, V, S, A> {
         let mut c = self.cursor_mut();
         c.move_next();
         c
     }
 
-    /// Returns the `CursorMut` over the back node.
-    ///
-    /// Note: The `CursorMut` is pointing to the _guard_ node in an empty `LinkedHashMap` and
-    ///       will always return `None` as its current element, regardless of any move in any
-    ///       direction.
-    pub fn cursor_back_mut(&mut self) -> CursorMut<K, V, S> {
-        let mut c = self.cursor_mut();
-        c.move_prev();
-        c
+    /// Returns the `CursorMut` over the back node.
+    ///
+    /// Note: The `CursorMut` is pointing to the _guard_ node in an empty `LinkedHashMap` and
+    ///       will always return `None` as its current element, regardless of any move in any
+    ///       direction.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, K, V, S, A> {
+        let mut c = self.cursor_mut();
+        c.move_prev();
+        c
+    }
+
+    /// Returns an iterator over the entry with key `k` and every entry after it, or `None` if `k`
+    /// is not present.
+    #[inline]
+    pub fn iter_from<Q>(&self, k: &Q) -> Option<Range<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let start = self.find_node(k)?;
+        Some(Range::new(self.values.as_ptr(), start, GUARD))
+    }
+
+    /// Returns an iterator over every entry before the entry with key `k`, or `None` if `k` is
+    /// not present.
+    #[inline]
+    pub fn iter_until<Q>(&self, k: &Q) -> Option<Range<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let end = self.find_node(k)?;
+        let (front, _) = guard_links(&self.values);
+        Some(Range::new(self.values.as_ptr(), front, end))
+    }
+
+    /// Returns an iterator over the entries from the one with key `start`, up to but not including
+    /// the one with key `end`, or `None` if either key is not present.
+    ///
+    /// If `end` comes before `start` in the list, the iterator is empty.  Finding this out walks
+    /// the list outwards from `start`, unless the `indexed` feature is enabled.
+    #[inline]
+    pub fn range<Q>(&self, start: &Q, end: &Q) -> Option<Range<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (start, end) = self.find_range(start, end)?;
+        Some(Range::new(self.values.as_ptr(), start, end))
+    }
+
+    /// Returns a mutable iterator over the entry with key `k` and every entry after it, or `None`
+    /// if `k` is not present.
+    #[inline]
+    pub fn iter_from_mut<Q>(&mut self, k: &Q) -> Option<RangeMut<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let start = self.find_node(k)?;
+        Some(RangeMut::new(self.values.as_mut_ptr(), start, GUARD))
+    }
+
+    /// Returns a mutable iterator over every entry before the entry with key `k`, or `None` if `k`
+    /// is not present.
+    #[inline]
+    pub fn iter_until_mut<Q>(&mut self, k: &Q) -> Option<RangeMut<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let end = self.find_node(k)?;
+        let (front, _) = guard_links(&self.values);
+        Some(RangeMut::new(self.values.as_mut_ptr(), front, end))
+    }
+
+    /// Returns a mutable iterator over the entries from the one with key `start`, up to but not
+    /// including the one with key `end`, or `None` if either key is not present.
+    ///
+    /// If `end` comes before `start` in the list, the iterator is empty.  Finding this out walks
+    /// the list outwards from `start`, unless the `indexed` feature is enabled.
+    #[inline]
+    pub fn range_mut<Q>(&mut self, start: &Q, end: &Q) -> Option<RangeMut<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (start, end) = self.find_range(start, end)?;
+        Some(RangeMut::new(self.values.as_mut_ptr(), start, end))
+    }
+
+    /// Removes the entries from the one with key `start`, up to but not including the one with
+    /// key `end`, and returns them as an iterator.  Returns `None` and leaves the map untouched if
+    /// either key is not present.
+    ///
+    /// The entries are removed from the map immediately, even if the returned iterator is not
+    /// consumed.  If `end` comes before `start` in the list, nothing is removed and the iterator is
+    /// empty.
+    pub fn drain_range<Q>(&mut self, start: &Q, end: &Q) -> Option<Drain<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (start, end) = self.find_range(start, end)?;
+
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            // Removed nodes are chained together in `drain` one at a time, so if hashing a key
+            // panics, every entry removed so far is still dropped along with `drain`.
+            let mut drain = Drain {
+                values: NonNull::new_unchecked(values),
+                free: (&mut self.free).into(),
+                head: start,
+                tail: start,
+                remaining: 0,
+                marker: PhantomData,
+            };
+
+            let mut cur = start;
+            while cur != end {
+                let next = (*node_at(values, cur)).links.next;
+                let hash = hash_node(&self.hash_builder, values, cur);
+                self.table.find_entry(hash, |&o| o == cur).unwrap().remove();
+                detach_node(values, cur);
+                if drain.remaining != 0 {
+                    (*node_at_mut(values, drain.tail)).links.next = cur;
+                    (*node_at_mut(values, cur)).links.prev = drain.tail;
+                }
+                drain.tail = cur;
+                drain.remaining += 1;
+                cur = next;
+            }
+
+            Some(drain)
+        }
+    }
+
+    /// Returns a `Cursor` over the entry with the given key, or `None` if it is not present.
+    #[inline]
+    pub fn cursor_at<Q>(&self, k: &Q) -> Option<Cursor<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(k)?;
+        Some(self.cursor(node))
+    }
+
+    /// Returns a `CursorMut` over the entry with the given key, or `None` if it is not present.
+    #[inline]
+    pub fn cursor_at_mut<Q>(&mut self, k: &Q) -> Option<CursorMut<'_, K, V, S, A>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(k)?;
+        let mut c = self.cursor_mut();
+        c.cur = node;
+        Some(c)
+    }
+
+    // Returns the nodes holding the given keys, with `start` replaced by `end` if it comes after
+    // `end` in the list, so that the range between them is empty.
+    #[inline]
+    fn find_range<Q>(&self, start: &Q, end: &Q) -> Option<(u32, u32)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let start = self.find_node(start)?;
+        let end = self.find_node(end)?;
+        if start != end && unsafe { comes_before(self.values.as_ptr(), end, start) } {
+            Some((end, end))
+        } else {
+            Some((start, end))
+        }
+    }
+
+    // Returns the node holding the given key, if there is one.
+    #[inline]
+    fn find_node<Q>(&self, k: &Q) -> Option<u32>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = hash_key(&self.hash_builder, k);
+        let values = self.values.as_ptr();
+        self.table
+            .find(hash, |&o| unsafe {
+                k.equivalent((*node_at(values, o)).key_ref())
+            })
+            .copied()
+    }
+
+    // Looks up every key, passing whether it was found to `found`, then optionally checks that
+    // they all refer to distinct entries and moves each of them to the back of the list in turn.
+    //
+    // If `check_distinct` is false, the caller must guarantee that the keys are distinct.
+    #[inline]
+    pub(crate) unsafe fn get_many_nodes_mut<Q, const N: usize>(
+        &mut self,
+        ks: [&Q; N],
+        check_distinct: bool,
+        to_back: bool,
+        mut found: impl FnMut(bool),
+    ) -> Option<[&mut V; N]>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let mut nodes = [NIL; N];
+        let mut missing = false;
+        for (node, k) in nodes.iter_mut().zip(ks) {
+            match self.find_node(k) {
+                Some(n) => {
+                    *node = n;
+                    found(true);
+                }
+                None => {
+                    missing = true;
+                    found(false);
+                }
+            }
+        }
+        if missing {
+            return None;
+        }
+
+        if check_distinct {
+            for i in 1..N {
+                if nodes[..i].contains(&nodes[i]) {
+                    return None;
+                }
+            }
+        }
+
+        let values = self.values.as_mut_ptr();
+        if to_back {
+            for &node in &nodes {
+                detach_node(values, node);
+                attach_before(values, node, GUARD);
+            }
+        }
+        Some(core::array::from_fn(|i| {
+            &mut (*node_at_mut(values, nodes[i])).entry_mut().1
+        }))
+    }
+
+    // Removes the given live node from both the table and the list.
+    #[inline]
+    fn take_node(&mut self, node: u32) -> (K, V) {
+        unsafe {
+            let hash = hash_node(&self.hash_builder, self.values.as_ptr(), node);
+            self.table
+                .find_entry(hash, |&o| o == node)
+                .unwrap()
+                .remove();
+            remove_node(self.values.as_mut_ptr(), &mut self.free, node)
+        }
+    }
+}
+
+/// Positional access, available with the `indexed` feature.
+///
+/// With this feature enabled every map also maintains an order statistics tree over its internal
+/// linked list, so that these methods run in `O(log n)` rather than walking the list.  In exchange,
+/// every operation that moves an entry within the list also becomes `O(log n)`.
+#[cfg(feature = "indexed")]
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A> {
+    /// Returns the entry at the given position in the internal linked list, where the front of the
+    /// list is at position 0.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        if index >= self.len() {
+            return None;
+        }
+        unsafe {
+            let values = self.values.as_ptr();
+            let (key, value) = (*node_at(values, order::select(values, index))).entry_ref();
+            Some((key, value))
+        }
+    }
+
+    /// Returns the entry at the given position in the internal linked list, where the front of the
+    /// list is at position 0.
+    #[inline]
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        if index >= self.len() {
+            return None;
+        }
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            let (key, value) = (*node_at_mut(values, order::select(values, index))).entry_mut();
+            Some((&*key, value))
+        }
+    }
+
+    /// Moves the entry at position `from` so that it ends up at position `to`, shifting every entry
+    /// in between by one place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` are out of bounds.
+    #[inline]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        let len = self.len();
+        assert!(from < len, "move_index: from index {} out of bounds", from);
+        assert!(to < len, "move_index: to index {} out of bounds", to);
+        if from == to {
+            return;
+        }
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            let node = order::select(values, from);
+            detach_node(values, node);
+            // With `node` detached, the entry now at `to` is the one that should follow it.
+            let before = if to == len - 1 {
+                GUARD
+            } else {
+                order::select(values, to)
+            };
+            attach_before(values, node, before);
+        }
+    }
+}
+
+#[cfg(feature = "indexed")]
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns the position of the entry with the given key in the internal linked list, where
+    /// the front of the list is at position 0.
+    #[inline]
+    pub fn index_of<Q>(&self, k: &Q) -> Option<usize>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let node = self.find_node(k)?;
+        Some(unsafe { order::position(self.values.as_ptr(), node) })
+    }
+
+    /// Removes and returns the entry at the given position in the internal linked list, shifting
+    /// every entry after it forward by one place.
+    #[inline]
+    pub fn remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.len() {
+            return None;
+        }
+        let node = unsafe { order::select(self.values.as_ptr(), index) };
+        Some(self.take_node(node))
+    }
+}
+
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A>
+where
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S, A> {
+        RawEntryBuilder { map: self }
+    }
+
+    #[inline]
+    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S, A> {
+        RawEntryBuilderMut { map: self }
+    }
+}
+
+impl<K, V, S, A> Default for LinkedHashMap<K, V, S, A>
+where
+    S: Default,
+    A: Allocator + Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            hash_builder: S::default(),
+            table: HashTable::new_in(A::default()),
+            values: Vec::new_in(A::default()),
+            free: NIL,
+        }
+    }
+}
+
+impl<K, V, S, A> FromIterator<(K, V)> for LinkedHashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    A: Allocator + Default,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut map = Self::default();
+        map.reserve(iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S, A: Allocator> fmt::Debug for LinkedHashMap<K, V, S, A>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher, A: Allocator> PartialEq
+    for LinkedHashMap<K, V, S, A>
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher, A: Allocator> Eq for LinkedHashMap<K, V, S, A> {}
+
+impl<K: Hash + Eq + PartialOrd, V: PartialOrd, S: BuildHasher, A: Allocator> PartialOrd
+    for LinkedHashMap<K, V, S, A>
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        self.iter().lt(other)
+    }
+
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        self.iter().le(other)
+    }
+
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        self.iter().ge(other)
+    }
+
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        self.iter().gt(other)
+    }
+}
+
+impl<K: Hash + Eq + Ord, V: Ord, S: BuildHasher, A: Allocator> Ord for LinkedHashMap<K, V, S, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<K: Hash + Eq, V: Hash, S: BuildHasher, A: Allocator> Hash for LinkedHashMap<K, V, S, A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, h: &mut H) {
+        for e in self.iter() {
+            e.hash(h);
+        }
+    }
+}
+
+impl<K, V, S, A: Allocator> Drop for LinkedHashMap<K, V, S, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.values.is_empty() {
+            unsafe {
+                let values = self.values.as_mut_ptr();
+                drop_value_nodes(values, (*values).links.prev);
+            }
+        }
+    }
+}
+
+unsafe impl<K: Send, V: Send, S: Send, A: Allocator + Send> Send for LinkedHashMap<K, V, S, A> {}
+unsafe impl<K: Sync, V: Sync, S: Sync, A: Allocator + Sync> Sync for LinkedHashMap<K, V, S, A> {}
+
+impl<'a, K, V, S, A: Allocator, Q> Index<&'a Q> for LinkedHashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: Hash + Equivalent<K> + ?Sized,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, index: &'a Q) -> &V {
+        self.get(index).expect("no entry found for key")
+    }
+}
+
+impl<'a, K, V, S, A: Allocator, Q> IndexMut<&'a Q> for LinkedHashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: Hash + Equivalent<K> + ?Sized,
+{
+    #[inline]
+    fn index_mut(&mut self, index: &'a Q) -> &mut V {
+        self.get_mut(index).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S, A> Clone for LinkedHashMap<K, V, S, A>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    A: Allocator + Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut map = Self::with_hasher_in(self.hash_builder.clone(), self.allocator().clone());
+        map.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
+        map
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher, A: Allocator> Extend<(K, V)> for LinkedHashMap<K, V, S, A> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V, S, A: Allocator> Extend<(&'a K, &'a V)> for LinkedHashMap<K, V, S, A>
+where
+    K: 'a + Hash + Eq + Copy,
+    V: 'a + Copy,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        for (&k, &v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+pub enum Entry<'a, K, V, S, A: Allocator = Global> {
+    Occupied(OccupiedEntry<'a, K, V, S, A>),
+    Vacant(VacantEntry<'a, K, V, S, A>),
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug for Entry<'_, K, V, S, A> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Entry::Vacant(ref v) => f.debug_tuple("Entry").field(v).finish(),
+            Entry::Occupied(ref o) => f.debug_tuple("Entry").field(o).finish(),
+        }
+    }
+}
+
+impl<'a, K, V, S, A: Allocator> Entry<'a, K, V, S, A> {
+    /// If this entry is vacant, inserts a new entry with the given value and returns a reference to
+    /// it.
+    ///
+    /// If this entry is occupied, this method *moves the occupied entry to the back of the internal
+    /// linked list* and returns a reference to the existing value.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Similar to `Entry::or_insert`, but accepts a function to construct a new value if this entry
+    /// is vacant.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Similar to `Entry::or_insert_with`, but the function is given a reference to the key that
+    /// will be inserted.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Similar to `Entry::or_insert`, but inserts the default value if this entry is vacant.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        K: Hash,
+        V: Default,
+        S: BuildHasher,
+    {
+        self.or_insert_with(V::default)
     }
-}
 
-impl<K, V, S> LinkedHashMap<K, V, S>
-where
-    S: BuildHasher,
-{
+    /// If this entry is occupied, moves it to the back of the internal linked list.  Does nothing
+    /// if this entry is vacant.
     #[inline]
-    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S> {
-        RawEntryBuilder { map: self }
+    pub fn to_back(&mut self) {
+        if let Entry::Occupied(entry) = self {
+            entry.to_back();
+        }
     }
 
+    /// If this entry is occupied, moves it to the front of the internal linked list.  Does nothing
+    /// if this entry is vacant.
     #[inline]
-    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S> {
-        RawEntryBuilderMut { map: self }
+    pub fn to_front(&mut self) {
+        if let Entry::Occupied(entry) = self {
+            entry.to_front();
+        }
     }
-}
 
-impl<K, V, S> Default for LinkedHashMap<K, V, S>
-where
-    S: Default,
-{
     #[inline]
-    fn default() -> Self {
-        Self::with_hasher(S::default())
+    pub fn key(&self) -> &K {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
     }
-}
 
-impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for LinkedHashMap<K, V, S> {
     #[inline]
-    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        let iter = iter.into_iter();
-        let mut map = Self::with_capacity_and_hasher(iter.size_hint().0, S::default());
-        map.extend(iter);
-        map
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
     }
 }
 
-impl<K, V, S> fmt::Debug for LinkedHashMap<K, V, S>
-where
-    K: fmt::Debug,
-    V: fmt::Debug,
-{
+pub struct OccupiedEntry<'a, K, V, S, A: Allocator = Global> {
+    // The key provided to `LinkedHashMap::entry`, or `None` if this entry was returned by
+    // `VacantEntry::insert_entry` or `VacantEntryRef::insert_entry`.
+    key: Option<K>,
+    raw_entry: RawOccupiedEntryMut<'a, K, V, S, A>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug for OccupiedEntry<'_, K, V, S, A> {
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_map().entries(self).finish()
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
     }
 }
 
-impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for LinkedHashMap<K, V, S> {
+impl<'a, K, V, S, A: Allocator> OccupiedEntry<'a, K, V, S, A> {
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.len() == other.len() && self.iter().eq(other)
+    pub fn key(&self) -> &K {
+        self.raw_entry.key()
     }
-}
 
-impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for LinkedHashMap<K, V, S> {}
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        self.raw_entry.remove_entry()
+    }
 
-impl<K: Hash + Eq + PartialOrd, V: PartialOrd, S: BuildHasher> PartialOrd
-    for LinkedHashMap<K, V, S>
-{
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.iter().partial_cmp(other)
+    pub fn get(&self) -> &V {
+        self.raw_entry.get()
     }
 
     #[inline]
-    fn lt(&self, other: &Self) -> bool {
-        self.iter().lt(other)
+    pub fn get_mut(&mut self) -> &mut V {
+        self.raw_entry.get_mut()
     }
 
     #[inline]
-    fn le(&self, other: &Self) -> bool {
-        self.iter().le(other)
+    pub fn into_mut(self) -> &'a mut V {
+        self.raw_entry.into_mut()
     }
 
     #[inline]
-    fn ge(&self, other: &Self) -> bool {
-        self.iter().ge(other)
+    pub fn to_back(&mut self) {
+        self.raw_entry.to_back()
     }
 
     #[inline]
-    fn gt(&self, other: &Self) -> bool {
-        self.iter().gt(other)
+    pub fn to_front(&mut self) {
+        self.raw_entry.to_front()
     }
-}
 
-impl<K: Hash + Eq + Ord, V: Ord, S: BuildHasher> Ord for LinkedHashMap<K, V, S> {
+    /// Replaces this entry's value with the provided value.
+    ///
+    /// Similarly to `LinkedHashMap::insert`, this moves the existing entry to the back of the
+    /// internal linked list.
     #[inline]
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.iter().cmp(other)
+    pub fn insert(&mut self, value: V) -> V {
+        self.raw_entry.to_back();
+        self.raw_entry.replace_value(value)
     }
-}
 
-impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for LinkedHashMap<K, V, S> {
     #[inline]
-    fn hash<H: Hasher>(&self, h: &mut H) {
-        for e in self.iter() {
-            e.hash(h);
+    pub fn remove(self) -> V {
+        self.raw_entry.remove()
+    }
+
+    /// Similar to `OccupiedEntry::replace_entry`, but *does* move the entry to the back of the
+    /// internal linked list.
+    ///
+    /// If there is no key to replace the entry's key with, the entry is not moved either.
+    #[inline]
+    pub fn insert_entry(mut self, value: V) -> Result<(K, V), (Self, V)> {
+        if self.key.is_some() {
+            self.raw_entry.to_back();
         }
+        self.replace_entry(value)
     }
-}
 
-impl<K, V, S> Drop for LinkedHashMap<K, V, S> {
+    /// Returns a `CursorMut` over the current entry.
     #[inline]
-    fn drop(&mut self) {
-        unsafe {
-            if let Some(values) = self.values {
-                drop_value_nodes(values);
-                let _ = Box::from_raw(values.as_ptr());
+    pub fn cursor_mut(self) -> CursorMut<'a, K, V, S, A>
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        self.raw_entry.cursor_mut()
+    }
+
+    /// Replaces the entry's key with the key provided to `LinkedHashMap::entry`, and replaces the
+    /// entry's value with the given `value` parameter.
+    ///
+    /// Does *not* move the entry to the back of the internal linked list.
+    ///
+    /// An entry returned by `VacantEntry::insert_entry` has no other key to replace its key with,
+    /// in which case nothing is replaced, and this entry is returned along with the given value.
+    pub fn replace_entry(mut self, value: V) -> Result<(K, V), (Self, V)> {
+        match self.key.take() {
+            Some(key) => {
+                let old_key = mem::replace(self.raw_entry.key_mut(), key);
+                let old_value = mem::replace(self.raw_entry.get_mut(), value);
+                Ok((old_key, old_value))
             }
-            drop_free_nodes(self.free);
+            None => Err((self, value)),
         }
     }
-}
 
-unsafe impl<K: Send, V: Send, S: Send> Send for LinkedHashMap<K, V, S> {}
-unsafe impl<K: Sync, V: Sync, S: Sync> Sync for LinkedHashMap<K, V, S> {}
+    /// Replaces this entry's key with the key provided to `LinkedHashMap::entry`.
+    ///
+    /// Does *not* move the entry to the back of the internal linked list.
+    ///
+    /// An entry returned by `VacantEntry::insert_entry` has no other key to replace its key with,
+    /// in which case nothing is replaced, and this entry is returned.
+    #[inline]
+    pub fn replace_key(mut self) -> Result<K, Self> {
+        match self.key.take() {
+            Some(key) => Ok(mem::replace(self.raw_entry.key_mut(), key)),
+            None => Err(self),
+        }
+    }
+}
 
-impl<'a, K, V, S, Q> Index<&'a Q> for LinkedHashMap<K, V, S>
-where
-    K: Hash + Eq + Borrow<Q>,
-    S: BuildHasher,
-    Q: Eq + Hash + ?Sized,
-{
-    type Output = V;
+pub struct VacantEntry<'a, K, V, S, A: Allocator = Global> {
+    hash: u64,
+    key: K,
+    raw_entry: RawVacantEntryMut<'a, K, V, S, A>,
+}
 
+impl<K: fmt::Debug, V, S, A: Allocator> fmt::Debug for VacantEntry<'_, K, V, S, A> {
     #[inline]
-    fn index(&self, index: &'a Q) -> &V {
-        self.get(index).expect("no entry found for key")
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
     }
 }
 
-impl<'a, K, V, S, Q> IndexMut<&'a Q> for LinkedHashMap<K, V, S>
-where
-    K: Hash + Eq + Borrow<Q>,
-    S: BuildHasher,
-    Q: Eq + Hash + ?Sized,
-{
+impl<'a, K, V, S, A: Allocator> VacantEntry<'a, K, V, S, A> {
     #[inline]
-    fn index_mut(&mut self, index: &'a Q) -> &mut V {
-        self.get_mut(index).expect("no entry found for key")
+    pub fn key(&self) -> &K {
+        &self.key
     }
-}
 
-impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> Clone for LinkedHashMap<K, V, S> {
     #[inline]
-    fn clone(&self) -> Self {
-        let mut map = Self::with_hasher(self.hash_builder.clone());
-        map.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
-        map
+    pub fn into_key(self) -> K {
+        self.key
     }
-}
 
-impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for LinkedHashMap<K, V, S> {
+    /// Inserts the key for this vacant entry paired with the given value as a new entry at the
+    /// *back* of the internal linked list.
     #[inline]
-    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
-        for (k, v) in iter {
-            self.insert(k, v);
+    pub fn insert(self, value: V) -> &'a mut V
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        self.raw_entry
+            .insert_hashed_nocheck(self.hash, self.key, value)
+            .1
+    }
+
+    /// Similar to `VacantEntry::insert`, but returns an `OccupiedEntry` for the new entry.
+    ///
+    /// The returned entry has no other key to replace the inserted one with, so its
+    /// `replace_entry`, `replace_key` and `insert_entry` methods return an error.
+    #[inline]
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S, A>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        OccupiedEntry {
+            key: None,
+            raw_entry: self
+                .raw_entry
+                .insert_node(self.hash, self.key, value, GUARD),
         }
     }
-}
 
-impl<'a, K, V, S> Extend<(&'a K, &'a V)> for LinkedHashMap<K, V, S>
-where
-    K: 'a + Hash + Eq + Copy,
-    V: 'a + Copy,
-    S: BuildHasher,
-{
+    /// Inserts the key for this vacant entry paired with the given value as a new entry at the
+    /// *front* of the internal linked list.
     #[inline]
-    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
-        for (&k, &v) in iter {
-            self.insert(k, v);
+    pub fn insert_front(self, value: V) -> &'a mut V
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let front = self
+            .raw_entry
+            .values
+            .first()
+            .map_or(GUARD, |g| g.links.next);
+        self.raw_entry
+            .insert_node(self.hash, self.key, value, front)
+            .into_mut()
+    }
+
+    /// Inserts the key for this vacant entry paired with the given value as a new entry
+    /// immediately before the entry for `anchor` in the internal linked list.
+    ///
+    /// If there is no entry for `anchor`, nothing is inserted, and this entry is returned along
+    /// with the given value.
+    #[inline]
+    pub fn insert_before<Q>(self, anchor: &Q, value: V) -> Result<&'a mut V, (Self, V)>
+    where
+        K: Hash,
+        Q: Hash + Equivalent<K> + ?Sized,
+        S: BuildHasher,
+    {
+        match self.raw_entry.find_anchor(self.hash, anchor) {
+            (raw_entry, Some(before)) => Ok(raw_entry
+                .insert_node(self.hash, self.key, value, before)
+                .into_mut()),
+            (raw_entry, None) => Err((
+                VacantEntry {
+                    hash: self.hash,
+                    key: self.key,
+                    raw_entry,
+                },
+                value,
+            )),
         }
     }
 }
 
-pub enum Entry<'a, K, V, S> {
-    Occupied(OccupiedEntry<'a, K, V, S>),
-    Vacant(VacantEntry<'a, K, V, S>),
+pub enum EntryRef<'a, 'b, K, Q: ?Sized, V, S, A: Allocator = Global> {
+    Occupied(OccupiedEntryRef<'a, 'b, K, Q, V, S, A>),
+    Vacant(VacantEntryRef<'a, 'b, K, Q, V, S, A>),
 }
 
-impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for Entry<'_, K, V, S> {
+impl<K, Q, V, S, A> fmt::Debug for EntryRef<'_, '_, K, Q, V, S, A>
+where
+    K: fmt::Debug,
+    Q: fmt::Debug + ?Sized,
+    V: fmt::Debug,
+    A: Allocator,
+{
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            Entry::Vacant(ref v) => f.debug_tuple("Entry").field(v).finish(),
-            Entry::Occupied(ref o) => f.debug_tuple("Entry").field(o).finish(),
+            EntryRef::Vacant(ref v) => f.debug_tuple("EntryRef").field(v).finish(),
+            EntryRef::Occupied(ref o) => f.debug_tuple("EntryRef").field(o).finish(),
         }
     }
 }
 
-impl<'a, K, V, S> Entry<'a, K, V, S> {
-    /// If this entry is vacant, inserts a new entry with the given value and returns a reference to
-    /// it.
+impl<'a, 'b, K, Q: ?Sized, V, S, A: Allocator> EntryRef<'a, 'b, K, Q, V, S, A> {
+    /// If this entry is vacant, inserts a new entry with an owned key and the given value and
+    /// returns a reference to it.
     ///
     /// If this entry is occupied, this method *moves the occupied entry to the back of the internal
     /// linked list* and returns a reference to the existing value.
     #[inline]
     pub fn or_insert(self, default: V) -> &'a mut V
     where
-        K: Hash,
+        K: Hash + From<&'b Q>,
         S: BuildHasher,
     {
         match self {
-            Entry::Occupied(mut entry) => {
+            EntryRef::Occupied(mut entry) => {
                 entry.to_back();
                 entry.into_mut()
             }
-            Entry::Vacant(entry) => entry.insert(default),
+            EntryRef::Vacant(entry) => entry.insert(default),
         }
     }
 
-    /// Similar to `Entry::or_insert`, but accepts a function to construct a new value if this entry
-    /// is vacant.
+    /// Similar to `EntryRef::or_insert`, but accepts a function to construct a new value if this
+    /// entry is vacant.
     #[inline]
     pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V
     where
-        K: Hash,
+        K: Hash + From<&'b Q>,
         S: BuildHasher,
     {
         match self {
-            Entry::Occupied(mut entry) => {
+            EntryRef::Occupied(mut entry) => {
                 entry.to_back();
                 entry.into_mut()
             }
-            Entry::Vacant(entry) => entry.insert(default()),
+            EntryRef::Vacant(entry) => entry.insert(default()),
         }
     }
 
+    /// Similar to `EntryRef::or_insert_with`, but the function is given a reference to the
+    /// borrowed key.
     #[inline]
-    pub fn key(&self) -> &K {
+    pub fn or_insert_with_key<F: FnOnce(&Q) -> V>(self, default: F) -> &'a mut V
+    where
+        K: Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        match self {
+            EntryRef::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            EntryRef::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Similar to `EntryRef::or_insert`, but inserts the default value if this entry is vacant.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        K: Hash + From<&'b Q>,
+        V: Default,
+        S: BuildHasher,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// If this entry is occupied, moves it to the back of the internal linked list.  Does nothing
+    /// if this entry is vacant.
+    #[inline]
+    pub fn to_back(&mut self) {
+        if let EntryRef::Occupied(entry) = self {
+            entry.to_back();
+        }
+    }
+
+    /// If this entry is occupied, moves it to the front of the internal linked list.  Does nothing
+    /// if this entry is vacant.
+    #[inline]
+    pub fn to_front(&mut self) {
+        if let EntryRef::Occupied(entry) = self {
+            entry.to_front();
+        }
+    }
+
+    /// Returns the borrowed key provided to `LinkedHashMap::entry_ref`.
+    #[inline]
+    pub fn key(&self) -> &'b Q {
         match *self {
-            Entry::Occupied(ref entry) => entry.key(),
-            Entry::Vacant(ref entry) => entry.key(),
+            EntryRef::Occupied(ref entry) => entry.lookup_key(),
+            EntryRef::Vacant(ref entry) => entry.key(),
         }
     }
 
@@ -781,36 +2270,50 @@ impl<'a, K, V, S> Entry<'a, K, V, S> {
         F: FnOnce(&mut V),
     {
         match self {
-            Entry::Occupied(mut entry) => {
+            EntryRef::Occupied(mut entry) => {
                 f(entry.get_mut());
-                Entry::Occupied(entry)
+                EntryRef::Occupied(entry)
             }
-            Entry::Vacant(entry) => Entry::Vacant(entry),
+            EntryRef::Vacant(entry) => EntryRef::Vacant(entry),
         }
     }
 }
 
-pub struct OccupiedEntry<'a, K, V, S> {
-    key: K,
-    raw_entry: RawOccupiedEntryMut<'a, K, V, S>,
+pub struct OccupiedEntryRef<'a, 'b, K, Q: ?Sized, V, S, A: Allocator = Global> {
+    key: &'b Q,
+    raw_entry: RawOccupiedEntryMut<'a, K, V, S, A>,
 }
 
-impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for OccupiedEntry<'_, K, V, S> {
+impl<K, Q, V, S, A> fmt::Debug for OccupiedEntryRef<'_, '_, K, Q, V, S, A>
+where
+    K: fmt::Debug,
+    Q: ?Sized,
+    V: fmt::Debug,
+    A: Allocator,
+{
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("OccupiedEntry")
+        f.debug_struct("OccupiedEntryRef")
             .field("key", self.key())
             .field("value", self.get())
             .finish()
     }
 }
 
-impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+impl<'a, 'b, K, Q: ?Sized, V, S, A: Allocator> OccupiedEntryRef<'a, 'b, K, Q, V, S, A> {
+    /// Returns the key stored in the map, as opposed to the borrowed key provided to
+    /// `LinkedHashMap::entry_ref`.
     #[inline]
     pub fn key(&self) -> &K {
         self.raw_entry.key()
     }
 
+    /// Returns the borrowed key provided to `LinkedHashMap::entry_ref`.
+    #[inline]
+    pub fn lookup_key(&self) -> &'b Q {
+        self.key
+    }
+
     #[inline]
     pub fn remove_entry(self) -> (K, V) {
         self.raw_entry.remove_entry()
@@ -856,17 +2359,20 @@ impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
         self.raw_entry.remove()
     }
 
-    /// Similar to `OccupiedEntry::replace_entry`, but *does* move the entry to the back of the
+    /// Similar to `OccupiedEntryRef::replace_entry`, but *does* move the entry to the back of the
     /// internal linked list.
     #[inline]
-    pub fn insert_entry(mut self, value: V) -> (K, V) {
+    pub fn insert_entry(mut self, value: V) -> (K, V)
+    where
+        K: From<&'b Q>,
+    {
         self.raw_entry.to_back();
         self.replace_entry(value)
     }
 
     /// Returns a `CursorMut` over the current entry.
     #[inline]
-    pub fn cursor_mut(self) -> CursorMut<'a, K, V, S>
+    pub fn cursor_mut(self) -> CursorMut<'a, K, V, S, A>
     where
         K: Eq + Hash,
         S: BuildHasher,
@@ -874,73 +2380,118 @@ impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
         self.raw_entry.cursor_mut()
     }
 
-    /// Replaces the entry's key with the key provided to `LinkedHashMap::entry`, and replaces the
-    /// entry's value with the given `value` parameter.
+    /// Converts the borrowed key provided to `LinkedHashMap::entry_ref` into an owned key, replaces
+    /// the entry's key with it, and replaces the entry's value with the given `value` parameter.
     ///
     /// Does *not* move the entry to the back of the internal linked list.
-    pub fn replace_entry(mut self, value: V) -> (K, V) {
-        let old_key = mem::replace(self.raw_entry.key_mut(), self.key);
+    pub fn replace_entry(mut self, value: V) -> (K, V)
+    where
+        K: From<&'b Q>,
+    {
+        let old_key = mem::replace(self.raw_entry.key_mut(), K::from(self.key));
         let old_value = mem::replace(self.raw_entry.get_mut(), value);
         (old_key, old_value)
     }
 
-    /// Replaces this entry's key with the key provided to `LinkedHashMap::entry`.
+    /// Converts the borrowed key provided to `LinkedHashMap::entry_ref` into an owned key and
+    /// replaces this entry's key with it.
     ///
     /// Does *not* move the entry to the back of the internal linked list.
     #[inline]
-    pub fn replace_key(mut self) -> K {
-        mem::replace(self.raw_entry.key_mut(), self.key)
+    pub fn replace_key(mut self) -> K
+    where
+        K: From<&'b Q>,
+    {
+        mem::replace(self.raw_entry.key_mut(), K::from(self.key))
     }
 }
 
-pub struct VacantEntry<'a, K, V, S> {
-    key: K,
-    raw_entry: RawVacantEntryMut<'a, K, V, S>,
+pub struct VacantEntryRef<'a, 'b, K, Q: ?Sized, V, S, A: Allocator = Global> {
+    // The hash of `key`, which `Equivalent` requires to match the hash of the owned key.
+    hash: u64,
+    key: &'b Q,
+    raw_entry: RawVacantEntryMut<'a, K, V, S, A>,
 }
 
-impl<K: fmt::Debug, V, S> fmt::Debug for VacantEntry<'_, K, V, S> {
+impl<K, Q, V, S, A> fmt::Debug for VacantEntryRef<'_, '_, K, Q, V, S, A>
+where
+    Q: fmt::Debug + ?Sized,
+    A: Allocator,
+{
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("VacantEntry").field(self.key()).finish()
+        f.debug_tuple("VacantEntryRef").field(&self.key).finish()
     }
 }
 
-impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+impl<'a, 'b, K, Q: ?Sized, V, S, A: Allocator> VacantEntryRef<'a, 'b, K, Q, V, S, A> {
     #[inline]
-    pub fn key(&self) -> &K {
-        &self.key
+    pub fn key(&self) -> &'b Q {
+        self.key
     }
 
+    /// Converts the borrowed key into an owned key, and inserts it paired with the given value as
+    /// a new entry at the *back* of the internal linked list.
     #[inline]
-    pub fn into_key(self) -> K {
-        self.key
+    pub fn insert(self, value: V) -> &'a mut V
+    where
+        K: Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        self.raw_entry
+            .insert_hashed_nocheck(self.hash, K::from(self.key), value)
+            .1
     }
 
-    /// Insert's the key for this vacant entry paired with the given value as a new entry at the
-    /// *back* of the internal linked list.
+    /// Similar to `VacantEntryRef::insert`, but returns an `OccupiedEntry` for the new entry.
+    ///
+    /// As with `VacantEntry::insert_entry`, the returned entry has no other key to replace the
+    /// inserted one with.
     #[inline]
-    pub fn insert(self, value: V) -> &'a mut V
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V, S, A>
     where
-        K: Hash,
+        K: Hash + From<&'b Q>,
         S: BuildHasher,
     {
-        self.raw_entry.insert(self.key, value).1
+        OccupiedEntry {
+            key: None,
+            raw_entry: self
+                .raw_entry
+                .insert_node(self.hash, K::from(self.key), value, GUARD),
+        }
+    }
+
+    /// Converts the borrowed key into an owned key, and inserts it paired with the given value as
+    /// a new entry at the *front* of the internal linked list.
+    #[inline]
+    pub fn insert_front(self, value: V) -> &'a mut V
+    where
+        K: Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        let front = self
+            .raw_entry
+            .values
+            .first()
+            .map_or(GUARD, |g| g.links.next);
+        self.raw_entry
+            .insert_node(self.hash, K::from(self.key), value, front)
+            .into_mut()
     }
 }
 
-pub struct RawEntryBuilder<'a, K, V, S> {
-    map: &'a LinkedHashMap<K, V, S>,
+pub struct RawEntryBuilder<'a, K, V, S, A: Allocator = Global> {
+    map: &'a LinkedHashMap<K, V, S, A>,
 }
 
-impl<'a, K, V, S> RawEntryBuilder<'a, K, V, S>
+impl<'a, K, V, S, A: Allocator> RawEntryBuilder<'a, K, V, S, A>
 where
     S: BuildHasher,
 {
     #[inline]
     pub fn from_key<Q>(self, k: &Q) -> Option<(&'a K, &'a V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         let hash = hash_key(&self.map.hash_builder, k);
         self.from_key_hashed_nocheck(hash, k)
@@ -949,10 +2500,9 @@ where
     #[inline]
     pub fn from_key_hashed_nocheck<Q>(self, hash: u64, k: &Q) -> Option<(&'a K, &'a V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.from_hash(hash, move |o| k.eq(o.borrow()))
+        self.from_hash(hash, move |o| k.equivalent(o))
     }
 
     #[inline]
@@ -962,42 +2512,41 @@ where
         mut is_match: impl FnMut(&K) -> bool,
     ) -> Option<(&'a K, &'a V)> {
         unsafe {
-            let node = self
+            let values = self.map.values.as_ptr();
+            let node = *self
                 .map
                 .table
-                .find(hash, move |k| is_match((*k).as_ref().key_ref()))?;
+                .find(hash, move |&k| is_match((*node_at(values, k)).key_ref()))?;
 
-            let (key, value) = (*node.as_ptr()).entry_ref();
+            let (key, value) = (*node_at(values, node)).entry_ref();
             Some((key, value))
         }
     }
 }
 
-pub struct RawEntryBuilderMut<'a, K, V, S> {
-    map: &'a mut LinkedHashMap<K, V, S>,
+pub struct RawEntryBuilderMut<'a, K, V, S, A: Allocator = Global> {
+    map: &'a mut LinkedHashMap<K, V, S, A>,
 }
 
-impl<'a, K, V, S> RawEntryBuilderMut<'a, K, V, S>
+impl<'a, K, V, S, A: Allocator> RawEntryBuilderMut<'a, K, V, S, A>
 where
     S: BuildHasher,
 {
     #[inline]
-    pub fn from_key<Q>(self, k: &Q) -> RawEntryMut<'a, K, V, S>
+    pub fn from_key<Q>(self, k: &Q) -> RawEntryMut<'a, K, V, S, A>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         let hash = hash_key(&self.map.hash_builder, k);
         self.from_key_hashed_nocheck(hash, k)
     }
 
     #[inline]
-    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, k: &Q) -> RawEntryMut<'a, K, V, S>
+    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, k: &Q) -> RawEntryMut<'a, K, V, S, A>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.from_hash(hash, move |o| k.eq(o.borrow()))
+        self.from_hash(hash, move |o| k.equivalent(o))
     }
 
     #[inline]
@@ -1005,11 +2554,11 @@ where
         self,
         hash: u64,
         mut is_match: impl FnMut(&K) -> bool,
-    ) -> RawEntryMut<'a, K, V, S> {
-        let entry = self
-            .map
-            .table
-            .find_entry(hash, move |k| is_match(unsafe { (*k).as_ref().key_ref() }));
+    ) -> RawEntryMut<'a, K, V, S, A> {
+        let values = self.map.values.as_ptr();
+        let entry = self.map.table.find_entry(hash, move |&k| {
+            is_match(unsafe { (*node_at(values, k)).key_ref() })
+        });
 
         match entry {
             Ok(occupied) => RawEntryMut::Occupied(RawOccupiedEntryMut {
@@ -1028,12 +2577,12 @@ where
     }
 }
 
-pub enum RawEntryMut<'a, K, V, S> {
-    Occupied(RawOccupiedEntryMut<'a, K, V, S>),
-    Vacant(RawVacantEntryMut<'a, K, V, S>),
+pub enum RawEntryMut<'a, K, V, S, A: Allocator = Global> {
+    Occupied(RawOccupiedEntryMut<'a, K, V, S, A>),
+    Vacant(RawVacantEntryMut<'a, K, V, S, A>),
 }
 
-impl<'a, K, V, S> RawEntryMut<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> RawEntryMut<'a, K, V, S, A> {
     /// Similarly to `Entry::or_insert`, if this entry is occupied, it will move the existing entry
     /// to the back of the internal linked list.
     #[inline]
@@ -1090,14 +2639,14 @@ impl<'a, K, V, S> RawEntryMut<'a, K, V, S> {
     }
 }
 
-pub struct RawOccupiedEntryMut<'a, K, V, S> {
+pub struct RawOccupiedEntryMut<'a, K, V, S, A: Allocator = Global> {
     hash_builder: &'a S,
-    free: &'a mut Option<NonNull<Node<K, V>>>,
-    values: &'a mut Option<NonNull<Node<K, V>>>,
-    entry: hash_table::OccupiedEntry<'a, NonNull<Node<K, V>>>,
+    free: &'a mut u32,
+    values: &'a mut Vec<Node<K, V>, A>,
+    entry: hash_table::OccupiedEntry<'a, u32, A>,
 }
 
-impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> RawOccupiedEntryMut<'a, K, V, S, A> {
     #[inline]
     pub fn key(&self) -> &K {
         self.get_key_value().0
@@ -1132,7 +2681,7 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
     pub fn get_key_value(&self) -> (&K, &V) {
         unsafe {
             let node = *self.entry.get();
-            let (key, value) = (*node.as_ptr()).entry_ref();
+            let (key, value) = (*node_at(self.values.as_ptr(), node)).entry_ref();
             (key, value)
         }
     }
@@ -1141,7 +2690,7 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
     pub fn get_key_value_mut(&mut self) -> (&mut K, &mut V) {
         unsafe {
             let node = *self.entry.get_mut();
-            let (key, value) = (*node.as_ptr()).entry_mut();
+            let (key, value) = (*node_at_mut(self.values.as_mut_ptr(), node)).entry_mut();
             (key, value)
         }
     }
@@ -1150,7 +2699,7 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
     pub fn into_key_value(self) -> (&'a mut K, &'a mut V) {
         unsafe {
             let node = *self.entry.into_mut();
-            let (key, value) = (*node.as_ptr()).entry_mut();
+            let (key, value) = (*node_at_mut(self.values.as_mut_ptr(), node)).entry_mut();
             (key, value)
         }
     }
@@ -1158,35 +2707,31 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
     #[inline]
     pub fn to_back(&mut self) {
         unsafe {
+            let values = self.values.as_mut_ptr();
             let node = *self.entry.get_mut();
-            detach_node(node);
-            attach_before(node, NonNull::new_unchecked(self.values.as_ptr()));
+            detach_node(values, node);
+            attach_before(values, node, GUARD);
         }
     }
 
     #[inline]
     pub fn to_front(&mut self) {
         unsafe {
+            let values = self.values.as_mut_ptr();
             let node = *self.entry.get_mut();
-            detach_node(node);
-            attach_before(node, (*self.values.as_ptr()).links.value.next);
+            detach_node(values, node);
+            attach_before(values, node, (*values).links.next);
         }
     }
 
     #[inline]
     pub fn replace_value(&mut self, value: V) -> V {
-        unsafe {
-            let mut node = *self.entry.get_mut();
-            mem::replace(&mut node.as_mut().entry_mut().1, value)
-        }
+        mem::replace(self.get_mut(), value)
     }
 
     #[inline]
     pub fn replace_key(&mut self, key: K) -> K {
-        unsafe {
-            let mut node = *self.entry.get_mut();
-            mem::replace(&mut node.as_mut().entry_mut().0, key)
-        }
+        mem::replace(self.key_mut(), key)
     }
 
     #[inline]
@@ -1197,18 +2742,18 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
     #[inline]
     pub fn remove_entry(self) -> (K, V) {
         let node = self.entry.remove().0;
-        unsafe { remove_node(self.free, node) }
+        unsafe { remove_node(self.values.as_mut_ptr(), self.free, node) }
     }
 
     /// Returns a `CursorMut` over the current entry.
     #[inline]
-    pub fn cursor_mut(self) -> CursorMut<'a, K, V, S>
+    pub fn cursor_mut(self) -> CursorMut<'a, K, V, S, A>
     where
         K: Eq + Hash,
         S: BuildHasher,
     {
         CursorMut {
-            cur: self.entry.get().as_ptr(),
+            cur: *self.entry.get(),
             hash_builder: self.hash_builder,
             free: self.free,
             values: self.values,
@@ -1217,14 +2762,14 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
     }
 }
 
-pub struct RawVacantEntryMut<'a, K, V, S> {
+pub struct RawVacantEntryMut<'a, K, V, S, A: Allocator = Global> {
     hash_builder: &'a S,
-    values: &'a mut Option<NonNull<Node<K, V>>>,
-    free: &'a mut Option<NonNull<Node<K, V>>>,
-    entry: hash_table::AbsentEntry<'a, NonNull<Node<K, V>>>,
+    values: &'a mut Vec<Node<K, V>, A>,
+    free: &'a mut u32,
+    entry: hash_table::AbsentEntry<'a, u32, A>,
 }
 
-impl<'a, K, V, S> RawVacantEntryMut<'a, K, V, S> {
+impl<'a, K, V, S, A: Allocator> RawVacantEntryMut<'a, K, V, S, A> {
     #[inline]
     pub fn insert(self, key: K, value: V) -> (&'a mut K, &'a mut V)
     where
@@ -1253,35 +2798,106 @@ impl<'a, K, V, S> RawVacantEntryMut<'a, K, V, S> {
         value: V,
         hasher: impl Fn(&K) -> u64,
     ) -> (&'a mut K, &'a mut V)
+    where
+        S: BuildHasher,
+    {
+        self.insert_node_with_hasher(hash, key, value, GUARD, hasher)
+            .into_key_value()
+    }
+
+    // Inserts a new entry immediately before `before`, which must be the guard node or a live
+    // node.
+    #[inline]
+    fn insert_node(
+        self,
+        hash: u64,
+        key: K,
+        value: V,
+        before: u32,
+    ) -> RawOccupiedEntryMut<'a, K, V, S, A>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let hash_builder = self.hash_builder;
+        self.insert_node_with_hasher(hash, key, value, before, |k| hash_key(hash_builder, k))
+    }
+
+    #[inline]
+    fn insert_node_with_hasher(
+        self,
+        hash: u64,
+        key: K,
+        value: V,
+        before: u32,
+        hasher: impl Fn(&K) -> u64,
+    ) -> RawOccupiedEntryMut<'a, K, V, S, A>
     where
         S: BuildHasher,
     {
         unsafe {
             ensure_guard_node(self.values);
-            let mut new_node = allocate_node(self.free);
-            new_node.as_mut().put_entry((key, value));
-            attach_before(new_node, NonNull::new_unchecked(self.values.as_ptr()));
+            let new_node = allocate_node(self.values, self.free);
+            let values = self.values.as_mut_ptr();
+            seed_order(values, self.hash_builder);
+            (*node_at_mut(values, new_node)).put_entry(hash, (key, value));
+            attach_before(values, new_node, before);
 
-            let node = self
+            let entry = self
                 .entry
                 .into_table()
-                .insert_unique(hash, new_node, move |k| hasher((*k).as_ref().key_ref()))
-                .into_mut();
+                .insert_unique(hash, new_node, move |&k| cached_hash_or(values, k, &hasher));
 
-            let (key, value) = (*node.as_ptr()).entry_mut();
-            (key, value)
+            RawOccupiedEntryMut {
+                hash_builder: self.hash_builder,
+                free: self.free,
+                values: self.values,
+                entry,
+            }
         }
     }
+
+    // Looks up the node for `anchor`, returning it along with a new vacant entry for the key with
+    // the given `hash`.
+    #[inline]
+    fn find_anchor<Q>(self, hash: u64, anchor: &Q) -> (Self, Option<u32>)
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        S: BuildHasher,
+    {
+        let anchor_hash = hash_key(self.hash_builder, anchor);
+        let values = self.values.as_ptr();
+        let table = self.entry.into_table();
+        let node = table
+            .find(anchor_hash, |&o| unsafe {
+                anchor.equivalent((*node_at(values, o)).key_ref())
+            })
+            .copied();
+        let entry = match table.find_entry(hash, |_| false) {
+            Ok(_) => unreachable!(),
+            Err(entry) => entry,
+        };
+
+        (
+            RawVacantEntryMut {
+                hash_builder: self.hash_builder,
+                values: self.values,
+                free: self.free,
+                entry,
+            },
+            node,
+        )
+    }
 }
 
-impl<K, V, S> fmt::Debug for RawEntryBuilderMut<'_, K, V, S> {
+impl<K, V, S, A: Allocator> fmt::Debug for RawEntryBuilderMut<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawEntryBuilder").finish()
     }
 }
 
-impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawEntryMut<'_, K, V, S> {
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug for RawEntryMut<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -1291,7 +2907,9 @@ impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawEntryMut<'_, K, V, S> {
     }
 }
 
-impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawOccupiedEntryMut<'_, K, V, S> {
+impl<K: fmt::Debug, V: fmt::Debug, S, A: Allocator> fmt::Debug
+    for RawOccupiedEntryMut<'_, K, V, S, A>
+{
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawOccupiedEntryMut")
@@ -1301,77 +2919,84 @@ impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawOccupiedEntryMut<'_, K,
     }
 }
 
-impl<K, V, S> fmt::Debug for RawVacantEntryMut<'_, K, V, S> {
+impl<K, V, S, A: Allocator> fmt::Debug for RawVacantEntryMut<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawVacantEntryMut").finish()
     }
 }
 
-impl<K, V, S> fmt::Debug for RawEntryBuilder<'_, K, V, S> {
+impl<K, V, S, A: Allocator> fmt::Debug for RawEntryBuilder<'_, K, V, S, A> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RawEntryBuilder").finish()
     }
 }
 
-unsafe impl<K, V, S> Send for RawOccupiedEntryMut<'_, K, V, S>
+unsafe impl<K, V, S, A> Send for RawOccupiedEntryMut<'_, K, V, S, A>
 where
     K: Send,
     V: Send,
     S: Send,
+    A: Allocator + Send,
 {
 }
 
-unsafe impl<K, V, S> Sync for RawOccupiedEntryMut<'_, K, V, S>
+unsafe impl<K, V, S, A> Sync for RawOccupiedEntryMut<'_, K, V, S, A>
 where
     K: Sync,
     V: Sync,
     S: Sync,
+    A: Allocator + Sync,
 {
 }
 
-unsafe impl<K, V, S> Send for RawVacantEntryMut<'_, K, V, S>
+unsafe impl<K, V, S, A> Send for RawVacantEntryMut<'_, K, V, S, A>
 where
     K: Send,
     V: Send,
     S: Send,
+    A: Allocator + Send,
 {
 }
 
-unsafe impl<K, V, S> Sync for RawVacantEntryMut<'_, K, V, S>
+unsafe impl<K, V, S, A> Sync for RawVacantEntryMut<'_, K, V, S, A>
 where
     K: Sync,
     V: Sync,
     S: Sync,
+    A: Allocator + Sync,
 {
 }
 
 pub struct Iter<'a, K, V> {
-    head: *const Node<K, V>,
-    tail: *const Node<K, V>,
+    values: *const Node<K, V>,
+    head: u32,
+    tail: u32,
     remaining: usize,
     marker: PhantomData<(&'a K, &'a V)>,
 }
 
 pub struct IterMut<'a, K, V> {
-    head: Option<NonNull<Node<K, V>>>,
-    tail: Option<NonNull<Node<K, V>>>,
+    values: *mut Node<K, V>,
+    head: u32,
+    tail: u32,
     remaining: usize,
     marker: PhantomData<(&'a K, &'a mut V)>,
 }
 
-pub struct IntoIter<K, V> {
-    head: Option<NonNull<Node<K, V>>>,
-    tail: Option<NonNull<Node<K, V>>>,
+pub struct IntoIter<K, V, A: Allocator = Global> {
+    values: Vec<Node<K, V>, A>,
+    head: u32,
+    tail: u32,
     remaining: usize,
-    marker: PhantomData<(K, V)>,
 }
 
 pub struct Drain<'a, K, V> {
-    free: NonNull<Option<NonNull<Node<K, V>>>>,
-    head: Option<NonNull<Node<K, V>>>,
-    tail: Option<NonNull<Node<K, V>>>,
+    values: NonNull<Node<K, V>>,
+    free: NonNull<u32>,
+    head: u32,
+    tail: u32,
     remaining: usize,
     // We want `Drain` to be covariant
     marker: PhantomData<(K, V, &'a LinkedHashMap<K, V>)>,
@@ -1381,20 +3006,22 @@ impl<K, V> IterMut<'_, K, V> {
     #[inline]
     pub(crate) fn iter(&self) -> Iter<'_, K, V> {
         Iter {
-            head: self.head.as_ptr(),
-            tail: self.tail.as_ptr(),
+            values: self.values,
+            head: self.head,
+            tail: self.tail,
             remaining: self.remaining,
             marker: PhantomData,
         }
     }
 }
 
-impl<K, V> IntoIter<K, V> {
+impl<K, V, A: Allocator> IntoIter<K, V, A> {
     #[inline]
     pub(crate) fn iter(&self) -> Iter<'_, K, V> {
         Iter {
-            head: self.head.as_ptr(),
-            tail: self.tail.as_ptr(),
+            values: self.values.as_ptr(),
+            head: self.head,
+            tail: self.tail,
             remaining: self.remaining,
             marker: PhantomData,
         }
@@ -1405,8 +3032,9 @@ impl<K, V> Drain<'_, K, V> {
     #[inline]
     pub(crate) fn iter(&self) -> Iter<'_, K, V> {
         Iter {
-            head: self.head.as_ptr(),
-            tail: self.tail.as_ptr(),
+            values: self.values.as_ptr(),
+            head: self.head,
+            tail: self.tail,
             remaining: self.remaining,
             marker: PhantomData,
         }
@@ -1427,10 +3055,11 @@ where
 {
 }
 
-unsafe impl<K, V> Send for IntoIter<K, V>
+unsafe impl<K, V, A> Send for IntoIter<K, V, A>
 where
     K: Send,
     V: Send,
+    A: Allocator + Send,
 {
 }
 
@@ -1455,10 +3084,11 @@ where
 {
 }
 
-unsafe impl<K, V> Sync for IntoIter<K, V>
+unsafe impl<K, V, A> Sync for IntoIter<K, V, A>
 where
     K: Sync,
     V: Sync,
+    A: Allocator + Sync,
 {
 }
 
@@ -1494,7 +3124,7 @@ where
     }
 }
 
-impl<K, V> fmt::Debug for IntoIter<K, V>
+impl<K, V, A: Allocator> fmt::Debug for IntoIter<K, V, A>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -1526,8 +3156,9 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
         } else {
             self.remaining -= 1;
             unsafe {
-                let (key, value) = (*self.head).entry_ref();
-                self.head = (*self.head).links.value.next.as_ptr();
+                let head = node_at(self.values, self.head);
+                let (key, value) = (*head).entry_ref();
+                self.head = (*head).links.next;
                 Some((key, value))
             }
         }
@@ -1549,9 +3180,9 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
         } else {
             self.remaining -= 1;
             unsafe {
-                let head = self.head.as_ptr();
+                let head = node_at_mut(self.values, self.head);
                 let (key, value) = (*head).entry_mut();
-                self.head = Some((*head).links.value.next);
+                self.head = (*head).links.next;
                 Some((key, value))
             }
         }
@@ -1563,7 +3194,7 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
     }
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
+impl<K, V, A: Allocator> Iterator for IntoIter<K, V, A> {
     type Item = (K, V);
 
     #[inline]
@@ -1573,10 +3204,9 @@ impl<K, V> Iterator for IntoIter<K, V> {
         }
         self.remaining -= 1;
         unsafe {
-            let head = self.head.as_ptr();
-            self.head = Some((*head).links.value.next);
-            let mut e = Box::from_raw(head);
-            Some(e.take_entry())
+            let head = node_at_mut(self.values.as_mut_ptr(), self.head);
+            self.head = (*head).links.next;
+            Some((*head).take_entry())
         }
     }
 
@@ -1596,10 +3226,11 @@ impl<K, V> Iterator for Drain<'_, K, V> {
         }
         self.remaining -= 1;
         unsafe {
-            let mut head = NonNull::new_unchecked(self.head.as_ptr());
-            self.head = Some(head.as_ref().links.value.next);
-            let entry = head.as_mut().take_entry();
-            push_free(self.free.as_mut(), head);
+            let values = self.values.as_ptr();
+            let head = self.head;
+            self.head = (*node_at(values, head)).links.next;
+            let entry = (*node_at_mut(values, head)).take_entry();
+            push_free(values, self.free.as_mut(), head);
             Some(entry)
         }
     }
@@ -1618,8 +3249,8 @@ impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
         } else {
             self.remaining -= 1;
             unsafe {
-                let tail = self.tail;
-                self.tail = (*tail).links.value.prev.as_ptr();
+                let tail = node_at(self.values, self.tail);
+                self.tail = (*tail).links.prev;
                 let (key, value) = (*tail).entry_ref();
                 Some((key, value))
             }
@@ -1635,8 +3266,8 @@ impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
         } else {
             self.remaining -= 1;
             unsafe {
-                let tail = self.tail.as_ptr();
-                self.tail = Some((*tail).links.value.prev);
+                let tail = node_at_mut(self.values, self.tail);
+                self.tail = (*tail).links.prev;
                 let (key, value) = (*tail).entry_mut();
                 Some((key, value))
             }
@@ -1644,7 +3275,7 @@ impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
     }
 }
 
-impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+impl<K, V, A: Allocator> DoubleEndedIterator for IntoIter<K, V, A> {
     #[inline]
     fn next_back(&mut self) -> Option<(K, V)> {
         if self.remaining == 0 {
@@ -1652,9 +3283,9 @@ impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
         }
         self.remaining -= 1;
         unsafe {
-            let mut e = *Box::from_raw(self.tail.as_ptr());
-            self.tail = Some(e.links.value.prev);
-            Some(e.take_entry())
+            let tail = node_at_mut(self.values.as_mut_ptr(), self.tail);
+            self.tail = (*tail).links.prev;
+            Some((*tail).take_entry())
         }
     }
 }
@@ -1667,10 +3298,11 @@ impl<K, V> DoubleEndedIterator for Drain<'_, K, V> {
         }
         self.remaining -= 1;
         unsafe {
-            let mut tail = NonNull::new_unchecked(self.tail.as_ptr());
-            self.tail = Some(tail.as_ref().links.value.prev);
-            let entry = tail.as_mut().take_entry();
-            push_free(&mut *self.free.as_ptr(), tail);
+            let values = self.values.as_ptr();
+            let tail = self.tail;
+            self.tail = (*node_at(values, tail)).links.prev;
+            let entry = (*node_at_mut(values, tail)).take_entry();
+            push_free(values, self.free.as_mut(), tail);
             Some(entry)
         }
     }
@@ -1680,17 +3312,17 @@ impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
 
 impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
 
-impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V, A: Allocator> ExactSizeIterator for IntoIter<K, V, A> {}
 
-impl<K, V> Drop for IntoIter<K, V> {
+impl<K, V, A: Allocator> Drop for IntoIter<K, V, A> {
     #[inline]
     fn drop(&mut self) {
+        let values = self.values.as_mut_ptr();
         for _ in 0..self.remaining {
             unsafe {
-                let tail = self.tail.as_ptr();
-                self.tail = Some((*tail).links.value.prev);
+                let tail = node_at_mut(values, self.tail);
+                self.tail = (*tail).links.prev;
                 (*tail).take_entry();
-                let _ = Box::from_raw(tail);
             }
         }
     }
@@ -1699,17 +3331,319 @@ impl<K, V> Drop for IntoIter<K, V> {
 impl<K, V> Drop for Drain<'_, K, V> {
     #[inline]
     fn drop(&mut self) {
+        let values = self.values.as_ptr();
         for _ in 0..self.remaining {
             unsafe {
-                let mut tail = NonNull::new_unchecked(self.tail.as_ptr());
-                self.tail = Some(tail.as_ref().links.value.prev);
-                tail.as_mut().take_entry();
-                push_free(&mut *self.free.as_ptr(), tail);
+                let tail = self.tail;
+                self.tail = (*node_at(values, tail)).links.prev;
+                (*node_at_mut(values, tail)).take_entry();
+                push_free(values, self.free.as_mut(), tail);
             }
         }
     }
 }
 
+/// An iterator over a range of entries of a `LinkedHashMap` in list order, see
+/// `LinkedHashMap::range`.
+pub struct Range<'a, K, V> {
+    values: *const Node<K, V>,
+    // The next nodes to yield from the front and the back, inclusive, or both `GUARD` once the
+    // range is exhausted.
+    head: u32,
+    tail: u32,
+    marker: PhantomData<(&'a K, &'a V)>,
+}
+
+/// A mutable iterator over a range of entries of a `LinkedHashMap` in list order, see
+/// `LinkedHashMap::range_mut`.
+pub struct RangeMut<'a, K, V> {
+    values: *mut Node<K, V>,
+    head: u32,
+    tail: u32,
+    marker: PhantomData<(&'a K, &'a mut V)>,
+}
+
+// Returns the inclusive `(head, tail)` bounds of the range from `start` up to but not including
+// `end`, which are both `GUARD` if the range is empty.
+#[inline]
+unsafe fn range_bounds<K, V>(values: *const Node<K, V>, start: u32, end: u32) -> (u32, u32) {
+    let tail = (*node_at(values, end)).links.prev;
+    if start == end || tail == GUARD {
+        (GUARD, GUARD)
+    } else {
+        (start, tail)
+    }
+}
+
+// Returns whether node `a` comes strictly before node `b` in the list.
+#[cfg(feature = "indexed")]
+#[inline]
+unsafe fn comes_before<K, V>(values: *const Node<K, V>, a: u32, b: u32) -> bool {
+    order::position(values, a) < order::position(values, b)
+}
+
+// Returns whether node `a` comes strictly before node `b` in the list, by walking outwards from
+// `b` in both directions until one walk finds `a` or runs off its end of the list.  This takes time
+// proportional to the smaller of the distance between the nodes and the distance from `b` to the
+// front of the list.
+#[cfg(not(feature = "indexed"))]
+#[inline]
+unsafe fn comes_before<K, V>(values: *const Node<K, V>, a: u32, b: u32) -> bool {
+    let (mut back, mut fwd) = (b, b);
+    loop {
+        back = (*node_at(values, back)).links.prev;
+        if back == a {
+            return true;
+        } else if back == GUARD {
+            return false;
+        }
+        fwd = (*node_at(values, fwd)).links.next;
+        if fwd == a {
+            return false;
+        } else if fwd == GUARD {
+            return true;
+        }
+    }
+}
+
+// Steps the range bounds past `node`, which was just yielded from the front.  If we run off the end
+// of the list, the whole range is exhausted so that no entry can be yielded twice.
+#[inline]
+unsafe fn range_advance<K, V>(values: *const Node<K, V>, head: &mut u32, tail: &mut u32) -> u32 {
+    let node = *head;
+    let next = (*node_at(values, node)).links.next;
+    if node == *tail || next == GUARD {
+        *head = GUARD;
+        *tail = GUARD;
+    } else {
+        *head = next;
+    }
+    node
+}
+
+#[inline]
+unsafe fn range_advance_back<K, V>(
+    values: *const Node<K, V>,
+    head: &mut u32,
+    tail: &mut u32,
+) -> u32 {
+    let node = *tail;
+    let prev = (*node_at(values, node)).links.prev;
+    if node == *head || prev == GUARD {
+        *head = GUARD;
+        *tail = GUARD;
+    } else {
+        *tail = prev;
+    }
+    node
+}
+
+impl<K, V> Range<'_, K, V> {
+    #[inline]
+    fn new(values: *const Node<K, V>, start: u32, end: u32) -> Self {
+        let (head, tail) = unsafe { range_bounds(values, start, end) };
+        Range {
+            values,
+            head,
+            tail,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> RangeMut<'_, K, V> {
+    #[inline]
+    fn new(values: *mut Node<K, V>, start: u32, end: u32) -> Self {
+        let (head, tail) = unsafe { range_bounds(values, start, end) };
+        RangeMut {
+            values,
+            head,
+            tail,
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn iter(&self) -> Range<'_, K, V> {
+        Range {
+            values: self.values,
+            head: self.head,
+            tail: self.tail,
+            marker: PhantomData,
+        }
+    }
+}
+
+unsafe impl<K: Sync, V: Sync> Send for Range<'_, K, V> {}
+
+unsafe impl<K: Sync, V: Sync> Sync for Range<'_, K, V> {}
+
+unsafe impl<K: Send, V: Send> Send for RangeMut<'_, K, V> {}
+
+unsafe impl<K: Sync, V: Sync> Sync for RangeMut<'_, K, V> {}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Range { ..*self }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Range<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RangeMut<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.head == GUARD {
+            return None;
+        }
+        unsafe {
+            let node = range_advance(self.values, &mut self.head, &mut self.tail);
+            let (key, value) = (*node_at(self.values, node)).entry_ref();
+            Some((key, value))
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.tail == GUARD {
+            return None;
+        }
+        unsafe {
+            let node = range_advance_back(self.values, &mut self.head, &mut self.tail);
+            let (key, value) = (*node_at(self.values, node)).entry_ref();
+            Some((key, value))
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        if self.head == GUARD {
+            return None;
+        }
+        unsafe {
+            let node = range_advance(self.values, &mut self.head, &mut self.tail);
+            let (key, value) = (*node_at_mut(self.values, node)).entry_mut();
+            Some((key, value))
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for RangeMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a mut V)> {
+        if self.tail == GUARD {
+            return None;
+        }
+        unsafe {
+            let node = range_advance_back(self.values, &mut self.head, &mut self.tail);
+            let (key, value) = (*node_at_mut(self.values, node)).entry_mut();
+            Some((key, value))
+        }
+    }
+}
+
+/// A read-only cursor over the entries of a `LinkedHashMap`, which can move freely in either
+/// direction.
+///
+/// Like `CursorMut`, the cursor may also point at the _guard_ node between the back and the front
+/// of the list, in which case it has no current element.  A `Cursor` is cheap to copy, so a cursor
+/// can be saved and restored while walking back and forth over a shared map.
+pub struct Cursor<'a, K, V> {
+    cur: u32,
+    values: &'a [Node<K, V>],
+}
+
+impl<'a, K, V> Cursor<'a, K, V> {
+    /// Returns the current element in the list, or `None` if the cursor is over the _guard_
+    /// node.
+    #[inline]
+    pub fn current(&self) -> Option<(&'a K, &'a V)> {
+        self.peek(self.cur)
+    }
+
+    /// Retrieves the next element in the list (moving towards the end).
+    #[inline]
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.peek(self.next(self.cur))
+    }
+
+    /// Retrieves the previous element in the list (moving towards the front).
+    #[inline]
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.peek(self.prev(self.cur))
+    }
+
+    /// Moves the cursor to the next element in the list (that is, moving towards the end).
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.cur = self.next(self.cur);
+    }
+
+    /// Moves the cursor to the previous element in the list (that is, moving towards the front).
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.cur = self.prev(self.cur);
+    }
+
+    #[inline]
+    fn peek(&self, at: u32) -> Option<(&'a K, &'a V)> {
+        if at == GUARD {
+            None
+        } else {
+            let (key, value) = unsafe { self.values[at as usize].entry_ref() };
+            Some((key, value))
+        }
+    }
+
+    // An empty map may not have a guard node at all, in which case we stay on the (missing) guard.
+    #[inline]
+    fn next(&self, at: u32) -> u32 {
+        self.values.get(at as usize).map_or(GUARD, |n| n.links.next)
+    }
+
+    #[inline]
+    fn prev(&self, at: u32) -> u32 {
+        self.values.get(at as usize).map_or(GUARD, |n| n.links.prev)
+    }
+}
+
+impl<K, V> Clone for Cursor<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Cursor<'_, K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Cursor<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Cursor").field(&self.current()).finish()
+    }
+}
+
 /// The `CursorMut` struct and its implementation provide the basic mutable Cursor API for Linked
 /// lists as proposed in
 /// [here](https://github.com/rust-lang/rfcs/blob/master/text/2570-linked-list-cursors.md), with
@@ -1723,58 +3657,47 @@ impl<K, V> Drop for Drain<'_, K, V> {
 /// - The current implementation does not include an `index` method, as it does not track the index
 ///   of its elements. It provides access to each map entry as a tuple of `(&K, &mut V)`.
 ///
-pub struct CursorMut<'a, K, V, S> {
-    cur: *mut Node<K, V>,
+pub struct CursorMut<'a, K, V, S, A: Allocator = Global> {
+    cur: u32,
     hash_builder: &'a S,
-    free: &'a mut Option<NonNull<Node<K, V>>>,
-    values: &'a mut Option<NonNull<Node<K, V>>>,
-    table: &'a mut hashbrown::HashTable<NonNull<Node<K, V>>>,
+    free: &'a mut u32,
+    values: &'a mut Vec<Node<K, V>, A>,
+    table: &'a mut HashTable<u32, A>,
 }
 
-impl<K, V, S> CursorMut<'_, K, V, S> {
+impl<K, V, S, A: Allocator> CursorMut<'_, K, V, S, A> {
     /// Returns an `Option` of the current element in the list, provided it is not the
     /// _guard_ node, and `None` overwise.
     #[inline]
     pub fn current(&mut self) -> Option<(&K, &mut V)> {
-        unsafe {
-            let at = NonNull::new_unchecked(self.cur);
-            self.peek(at)
-        }
+        let at = self.cur;
+        self.peek(at)
     }
 
     /// Retrieves the next element in the list (moving towards the end).
     #[inline]
     pub fn peek_next(&mut self) -> Option<(&K, &mut V)> {
-        unsafe {
-            let at = (*self.cur).links.value.next;
-            self.peek(at)
-        }
+        let at = self.values[self.cur as usize].links.next;
+        self.peek(at)
     }
 
     /// Retrieves the previous element in the list (moving towards the front).
     #[inline]
     pub fn peek_prev(&mut self) -> Option<(&K, &mut V)> {
-        unsafe {
-            let at = (*self.cur).links.value.prev;
-            self.peek(at)
-        }
+        let at = self.values[self.cur as usize].links.prev;
+        self.peek(at)
     }
 
     // Retrieves the element without advancing current position to it.
     #[inline]
-    fn peek(&mut self, at: NonNull<Node<K, V>>) -> Option<(&K, &mut V)> {
-        if let Some(values) = self.values {
+    fn peek(&mut self, at: u32) -> Option<(&K, &mut V)> {
+        if at == GUARD {
+            None
+        } else {
             unsafe {
-                let node = at.as_ptr();
-                if node == values.as_ptr() {
-                    None
-                } else {
-                    let entry = (*node).entry_mut();
-                    Some((&entry.0, &mut entry.1))
-                }
+                let entry = (*node_at_mut(self.values.as_mut_ptr(), at)).entry_mut();
+                Some((&entry.0, &mut entry.1))
             }
-        } else {
-            None
         }
     }
 
@@ -1782,7 +3705,7 @@ impl<K, V, S> CursorMut<'_, K, V, S> {
     /// list (that is, moving towards the end).
     #[inline]
     pub fn move_next(&mut self) {
-        let at = unsafe { (*self.cur).links.value.next };
+        let at = self.values[self.cur as usize].links.next;
         self.muv(at);
     }
 
@@ -1790,14 +3713,14 @@ impl<K, V, S> CursorMut<'_, K, V, S> {
     /// list (that is, moving towards the front).
     #[inline]
     pub fn move_prev(&mut self) {
-        let at = unsafe { (*self.cur).links.value.prev };
+        let at = self.values[self.cur as usize].links.prev;
         self.muv(at);
     }
 
     // Updates the pointer to the current element to the one returned by the at closure function.
     #[inline]
-    fn muv(&mut self, at: NonNull<Node<K, V>>) {
-        self.cur = at.as_ptr();
+    fn muv(&mut self, at: u32) {
+        self.cur = at;
     }
 
     /// Inserts the provided key and value before the current element. It checks if an entry
@@ -1813,7 +3736,7 @@ impl<K, V, S> CursorMut<'_, K, V, S> {
         K: Eq + Hash,
         S: BuildHasher,
     {
-        let before = unsafe { NonNull::new_unchecked(self.cur) };
+        let before = self.cur;
         self.insert(key, value, before)
     }
 
@@ -1830,45 +3753,317 @@ impl<K, V, S> CursorMut<'_, K, V, S> {
         K: Eq + Hash,
         S: BuildHasher,
     {
-        let before = unsafe { (*self.cur).links.value.next };
+        let before = self.values[self.cur as usize].links.next;
         self.insert(key, value, before)
     }
 
+    /// Removes the current entry and returns it, moving the cursor to the next entry.
+    ///
+    /// Returns `None` and does nothing if the cursor is over the _guard_ node.
+    #[inline]
+    pub fn remove_current(&mut self) -> Option<(K, V)>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let next = self.values[self.cur as usize].links.next;
+        self.remove_current_and_move(next)
+    }
+
+    /// Removes the current entry and returns it, moving the cursor to the previous entry.
+    ///
+    /// Returns `None` and does nothing if the cursor is over the _guard_ node.
+    #[inline]
+    pub fn remove_current_and_move_prev(&mut self) -> Option<(K, V)>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let prev = self.values[self.cur as usize].links.prev;
+        self.remove_current_and_move(prev)
+    }
+
+    #[inline]
+    fn remove_current_and_move(&mut self, to: u32) -> Option<(K, V)>
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        if self.cur == GUARD {
+            return None;
+        }
+        let node = self.cur;
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            let hash = hash_node(self.hash_builder, values, node);
+            self.table
+                .find_entry(hash, |&o| o == node)
+                .unwrap()
+                .remove();
+            self.cur = to;
+            Some(remove_node(values, self.free, node))
+        }
+    }
+
+    /// Replaces the key of the current entry, returning the old key.  The entry keeps its position
+    /// in the list and its value.
+    ///
+    /// If the cursor is over the _guard_ node, or if a *different* entry already has the given
+    /// key, nothing is changed and the given key is returned as an error.
+    pub fn replace_key(&mut self, key: K) -> Result<K, K>
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        if self.cur == GUARD {
+            return Err(key);
+        }
+        let node = self.cur;
+        unsafe {
+            let hash_builder = self.hash_builder;
+            let values = self.values.as_mut_ptr();
+            let hash = hash_key(hash_builder, &key);
+            if let Some(&other) = self
+                .table
+                .find(hash, |&o| (*node_at(values, o)).key_ref().eq(&key))
+            {
+                if other != node {
+                    return Err(key);
+                }
+            }
+
+            // Make sure re-inserting the node cannot need to rehash, so that no user code can run
+            // while the node is missing from the table.
+            self.table
+                .reserve(1, move |&n| hash_node(hash_builder, values, n));
+            let old_hash = hash_node(hash_builder, values, node);
+            self.table
+                .find_entry(old_hash, |&o| o == node)
+                .unwrap()
+                .remove();
+            let old_key = mem::replace(&mut (*node_at_mut(values, node)).entry_mut().0, key);
+            (*node_at_mut(values, node)).set_hash(hash);
+            self.table
+                .insert_unique(hash, node, move |&n| hash_node(hash_builder, values, n));
+            Ok(old_key)
+        }
+    }
+
+    /// Swaps the current entry with the next one in the list.  The cursor stays on the same entry,
+    /// which moves one place towards the end.
+    ///
+    /// Returns `false` and does nothing if the cursor is over the _guard_ node or the back entry.
+    #[inline]
+    pub fn swap_with_next(&mut self) -> bool {
+        let next = self.values[self.cur as usize].links.next;
+        if self.cur == GUARD || next == GUARD {
+            return false;
+        }
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            detach_node(values, next);
+            attach_before(values, next, self.cur);
+        }
+        true
+    }
+
+    /// Swaps the current entry with the previous one in the list.  The cursor stays on the same
+    /// entry, which moves one place towards the front.
+    ///
+    /// Returns `false` and does nothing if the cursor is over the _guard_ node or the front entry.
+    #[inline]
+    pub fn swap_with_prev(&mut self) -> bool {
+        let prev = self.values[self.cur as usize].links.prev;
+        if self.cur == GUARD || prev == GUARD {
+            return false;
+        }
+        unsafe {
+            let values = self.values.as_mut_ptr();
+            let next = (*node_at(values, self.cur)).links.next;
+            detach_node(values, prev);
+            attach_before(values, prev, next);
+        }
+        true
+    }
+
+    /// Moves every entry of `other` to just after the current entry, keeping their order.  If the
+    /// cursor is over the _guard_ node, the entries are moved to the front of the list.
+    ///
+    /// Entries whose keys are already present behave as with `CursorMut::insert_after`: the
+    /// existing entry has its value replaced and is moved into place, but keeps its key.
+    pub fn splice_after(&mut self, other: LinkedHashMap<K, V, S, A>)
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        let hash_builder = self.hash_builder;
+        let values = self.values.as_ptr();
+        self.table.reserve(other.len(), move |&n| unsafe {
+            hash_node(hash_builder, values, n)
+        });
+        reserve_values(self.values, self.table.len(), other.len());
+
+        let mut last = self.cur;
+        for (k, v) in other {
+            let before = self.values[last as usize].links.next;
+            last = self.insert_node(k, v, before).0;
+        }
+    }
+
+    /// Splits the list after the current entry, returning a new map containing every entry after
+    /// it.  If the cursor is over the _guard_ node, every entry is moved to the new map.
+    ///
+    /// This takes time proportional to the number of entries moved into the returned map.
+    pub fn split_after(&mut self) -> LinkedHashMap<K, V, S, A>
+    where
+        K: Eq + Hash,
+        S: BuildHasher + Clone,
+        A: Clone,
+    {
+        let first = self.values[self.cur as usize].links.next;
+        let mut count = 0;
+        let mut cur = first;
+        while cur != GUARD {
+            count += 1;
+            cur = self.values[cur as usize].links.next;
+        }
+        unsafe {
+            split_nodes(
+                self.hash_builder,
+                self.values,
+                self.free,
+                self.table,
+                first,
+                count,
+            )
+        }
+    }
+
     // Inserts an element immediately before the given `before` node.
     #[inline]
-    fn insert(&mut self, key: K, value: V, before: NonNull<Node<K, V>>) -> Option<V>
+    fn insert(&mut self, key: K, value: V, before: u32) -> Option<V>
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        self.insert_node(key, value, before).1
+    }
+
+    // Inserts an element immediately before the given `before` node, returning the node which now
+    // holds the entry.
+    #[inline]
+    fn insert_node(&mut self, key: K, value: V, before: u32) -> (u32, Option<V>)
     where
         K: Eq + Hash,
         S: BuildHasher,
     {
         unsafe {
             let hash = hash_key(self.hash_builder, &key);
+            let values = self.values.as_mut_ptr();
             let i_entry = self
                 .table
-                .find_entry(hash, |o| (*o).as_ref().key_ref().eq(&key));
+                .find_entry(hash, |&o| (*node_at(values, o)).key_ref().eq(&key));
 
             match i_entry {
                 Ok(occupied) => {
-                    let mut node = *occupied.into_mut();
-                    let pv = mem::replace(&mut node.as_mut().entry_mut().1, value);
+                    let node = *occupied.into_mut();
+                    let pv = mem::replace(&mut (*node_at_mut(values, node)).entry_mut().1, value);
                     if node != before {
-                        detach_node(node);
-                        attach_before(node, before);
+                        detach_node(values, node);
+                        attach_before(values, node, before);
                     }
-                    Some(pv)
+                    (node, Some(pv))
                 }
                 Err(_) => {
-                    let mut new_node = allocate_node(self.free);
-                    new_node.as_mut().put_entry((key, value));
-                    attach_before(new_node, before);
+                    let new_node = allocate_node(self.values, self.free);
+                    let values = self.values.as_mut_ptr();
+                    seed_order(values, self.hash_builder);
+                    (*node_at_mut(values, new_node)).put_entry(hash, (key, value));
+                    attach_before(values, new_node, before);
                     let hash_builder = self.hash_builder;
-                    self.table.insert_unique(hash, new_node, move |k| {
-                        hash_key(hash_builder, (*k).as_ref().key_ref())
+                    self.table.insert_unique(hash, new_node, move |&k| {
+                        hash_node(hash_builder, values, k)
                     });
-                    None
+                    (new_node, None)
+                }
+            }
+        }
+    }
+}
+
+/// An iterator which removes and yields the entries of a `LinkedHashMap` matching a predicate,
+/// see `LinkedHashMap::extract_if`.
+pub struct ExtractIf<'a, K, V, F, S = DefaultHashBuilder, A: Allocator = Global> {
+    state: ExtractIfState<'a, K, V, S, A>,
+    pred: F,
+}
+
+// The predicate independent part of `ExtractIf`, shared with `LinkedHashSet`.
+pub(crate) struct ExtractIfState<'a, K, V, S, A: Allocator> {
+    map: &'a mut LinkedHashMap<K, V, S, A>,
+    // The next node to visit, or `GUARD` once the walk has reached the end of the list.
+    next: u32,
+}
+
+impl<'a, K, V, S, A: Allocator> ExtractIfState<'a, K, V, S, A> {
+    #[inline]
+    pub(crate) fn new(map: &'a mut LinkedHashMap<K, V, S, A>) -> Self {
+        let (next, _) = guard_links(&map.values);
+        ExtractIfState { map, next }
+    }
+
+    #[inline]
+    pub(crate) fn next<F>(&mut self, mut pred: F) -> Option<(K, V)>
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        while self.next != GUARD {
+            let node = self.next;
+            unsafe {
+                let values = self.map.values.as_mut_ptr();
+                // Advance before calling `pred`, so that a panic leaves us somewhere sensible.
+                self.next = (*node_at(values, node)).links.next;
+                let (k, v) = (*node_at_mut(values, node)).entry_mut();
+                if pred(k, v) {
+                    return Some(self.map.take_node(node));
                 }
             }
         }
+        None
+    }
+
+    #[inline]
+    pub(crate) fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.len()))
+    }
+}
+
+impl<K, V, F, S, A> Iterator for ExtractIf<'_, K, V, F, S, A>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+    A: Allocator,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.state.next(&mut self.pred)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.state.size_hint()
+    }
+}
+
+impl<K, V, F, S, A: Allocator> fmt::Debug for ExtractIf<'_, K, V, F, S, A> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractIf").finish_non_exhaustive()
     }
 }
 
@@ -2011,7 +4206,7 @@ impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {
     }
 }
 
-impl<'a, K, V, S> IntoIterator for &'a LinkedHashMap<K, V, S> {
+impl<'a, K, V, S, A: Allocator> IntoIterator for &'a LinkedHashMap<K, V, S, A> {
     type Item = (&'a K, &'a V);
     type IntoIter = Iter<'a, K, V>;
 
@@ -2021,7 +4216,7 @@ impl<'a, K, V, S> IntoIterator for &'a LinkedHashMap<K, V, S> {
     }
 }
 
-impl<'a, K, V, S> IntoIterator for &'a mut LinkedHashMap<K, V, S> {
+impl<'a, K, V, S, A: Allocator> IntoIterator for &'a mut LinkedHashMap<K, V, S, A> {
     type Item = (&'a K, &'a mut V);
     type IntoIter = IterMut<'a, K, V>;
 
@@ -2031,85 +4226,99 @@ impl<'a, K, V, S> IntoIterator for &'a mut LinkedHashMap<K, V, S> {
     }
 }
 
-impl<K, V, S> IntoIterator for LinkedHashMap<K, V, S> {
+impl<K, V, S, A: Allocator> IntoIterator for LinkedHashMap<K, V, S, A> {
     type Item = (K, V);
-    type IntoIter = IntoIter<K, V>;
+    type IntoIter = IntoIter<K, V, A>;
 
     #[inline]
-    fn into_iter(mut self) -> IntoIter<K, V> {
-        unsafe {
-            let (head, tail) = if let Some(values) = self.values {
-                let ValueLinks {
-                    next: head,
-                    prev: tail,
-                } = values.as_ref().links.value;
+    fn into_iter(self) -> IntoIter<K, V, A> {
+        let (head, tail) = guard_links(&self.values);
+        let len = self.len();
 
-                let _ = Box::from_raw(self.values.as_ptr());
-                self.values = None;
-
-                (Some(head), Some(tail))
-            } else {
-                (None, None)
-            };
-            let len = self.len();
-
-            drop_free_nodes(self.free.take());
-
-            self.table.clear();
+        // Move the node slab out of the map without running its `Drop` impl, which would drop
+        // every key and value.
+        let mut map = mem::ManuallyDrop::new(self);
+        unsafe {
+            let values = ptr::read(&map.values);
+            ptr::drop_in_place(&mut map.table);
+            ptr::drop_in_place(&mut map.hash_builder);
 
             IntoIter {
+                values,
                 head,
                 tail,
                 remaining: len,
-                marker: PhantomData,
             }
         }
     }
 }
 
-struct ValueLinks<K, V> {
-    next: NonNull<Node<K, V>>,
-    prev: NonNull<Node<K, V>>,
-}
-
-impl<K, V> Clone for ValueLinks<K, V> {
-    #[inline]
-    fn clone(&self) -> Self {
-        *self
-    }
-}
-
-impl<K, V> Copy for ValueLinks<K, V> {}
-
-struct FreeLink<K, V> {
-    next: Option<NonNull<Node<K, V>>>,
-}
-
-impl<K, V> Clone for FreeLink<K, V> {
-    #[inline]
-    fn clone(&self) -> Self {
-        *self
-    }
-}
+// Index of the guard node in a non-empty node slab.
+const GUARD: u32 = 0;
 
-impl<K, V> Copy for FreeLink<K, V> {}
+// Marks the end of the free list, `u32::MAX` is never a valid index into the node slab.
+const NIL: u32 = u32::MAX;
 
-union Links<K, V> {
-    value: ValueLinks<K, V>,
-    free: FreeLink<K, V>,
+#[derive(Clone, Copy)]
+struct Links {
+    next: u32,
+    prev: u32,
 }
 
 struct Node<K, V> {
     entry: MaybeUninit<(K, V)>,
-    links: Links<K, V>,
+    links: Links,
+    #[cfg(feature = "indexed")]
+    order: order::OrderLinks,
+    // The hash of the key, so that the table never needs to rehash keys when it is resized or
+    // when removing known nodes.  Only meaningful while the node holds an entry.
+    #[cfg(feature = "cached-hashes")]
+    hash: u64,
 }
 
 impl<K, V> Node<K, V> {
     #[inline]
-    unsafe fn put_entry(&mut self, entry: (K, V)) {
+    fn new(links: Links) -> Self {
+        Node {
+            entry: MaybeUninit::uninit(),
+            links,
+            #[cfg(feature = "indexed")]
+            order: order::OrderLinks::EMPTY,
+            #[cfg(feature = "cached-hashes")]
+            hash: 0,
+        }
+    }
+
+    // Resets the guard node to represent an empty list.
+    #[inline]
+    fn unlink_all(&mut self) {
+        self.links = Links {
+            next: GUARD,
+            prev: GUARD,
+        };
+        #[cfg(feature = "indexed")]
+        {
+            self.order = order::OrderLinks::EMPTY;
+        }
+    }
+
+    #[inline]
+    unsafe fn put_entry(&mut self, hash: u64, entry: (K, V)) {
+        self.set_hash(hash);
         self.entry.as_mut_ptr().write(entry)
     }
 
+    // Records the hash of this node's key, if hashes are cached.
+    #[inline]
+    fn set_hash(&mut self, hash: u64) {
+        #[cfg(feature = "cached-hashes")]
+        {
+            self.hash = hash;
+        }
+        #[cfg(not(feature = "cached-hashes"))]
+        let _ = hash;
+    }
+
     #[inline]
     unsafe fn entry_ref(&self) -> &(K, V) {
         &*self.entry.as_ptr()
@@ -2131,145 +4340,363 @@ impl<K, V> Node<K, V> {
     }
 }
 
-trait OptNonNullExt<T> {
-    #[allow(clippy::wrong_self_convention)]
-    fn as_ptr(self) -> *mut T;
+#[inline]
+unsafe fn node_at<K, V>(values: *const Node<K, V>, index: u32) -> *const Node<K, V> {
+    values.add(index as usize)
 }
 
-impl<T> OptNonNullExt<T> for Option<NonNull<T>> {
-    #[inline]
-    fn as_ptr(self) -> *mut T {
-        match self {
-            Some(ptr) => ptr.as_ptr(),
-            None => ptr::null_mut(),
-        }
+#[inline]
+unsafe fn node_at_mut<K, V>(values: *mut Node<K, V>, index: u32) -> *mut Node<K, V> {
+    values.add(index as usize)
+}
+
+// Returns the first and last node in the list, or the guard node twice if the list is empty.
+#[inline]
+fn guard_links<K, V>(values: &[Node<K, V>]) -> (u32, u32) {
+    match values.first() {
+        Some(guard) => (guard.links.next, guard.links.prev),
+        None => (GUARD, GUARD),
+    }
+}
+
+#[inline]
+fn values_with_capacity_in<K, V, A: Allocator>(capacity: usize, alloc: A) -> Vec<Node<K, V>, A> {
+    if capacity == 0 {
+        Vec::new_in(alloc)
+    } else {
+        Vec::with_capacity_in(capacity.saturating_add(1), alloc)
     }
 }
 
+// Make sure that the node slab has room for `len + additional` entries plus the guard node,
+// without counting any slots in the free list.
+#[inline]
+fn reserve_values<K, V, A: Allocator>(
+    values: &mut Vec<Node<K, V>, A>,
+    len: usize,
+    additional: usize,
+) {
+    let needed = len
+        .checked_add(additional)
+        .and_then(|n| n.checked_add(1))
+        .expect("capacity overflow");
+    if needed > NIL as usize {
+        panic!("capacity overflow");
+    }
+    let extra = needed.saturating_sub(values.len());
+    values.reserve(extra);
+}
+
+#[inline]
+fn try_reserve_values<K, V, A: Allocator>(
+    values: &mut Vec<Node<K, V>, A>,
+    len: usize,
+    additional: usize,
+) -> Result<(), TryReserveError> {
+    let needed = len
+        .checked_add(additional)
+        .and_then(|n| n.checked_add(1))
+        .filter(|&n| n <= NIL as usize)
+        .ok_or(TryReserveError::CapacityOverflow)?;
+    let extra = needed.saturating_sub(values.len());
+    values.try_reserve(extra).map_err(
+        |_| match Layout::array::<Node<K, V>>(values.len() + extra) {
+            Ok(layout) => TryReserveError::AllocError { layout },
+            Err(_) => TryReserveError::CapacityOverflow,
+        },
+    )
+}
+
+// Reserves room for `additional` more elements in a scratch buffer.
+fn try_reserve_buffer<T, A: Allocator>(
+    buffer: &mut Vec<T, A>,
+    additional: usize,
+) -> Result<(), TryReserveError> {
+    buffer.try_reserve(additional).map_err(|_| {
+        match buffer.len().checked_add(additional).map(Layout::array::<T>) {
+            Some(Ok(layout)) => TryReserveError::AllocError { layout },
+            _ => TryReserveError::CapacityOverflow,
+        }
+    })
+}
+
 // Allocate a circular list guard node if not present.
 #[inline]
-unsafe fn ensure_guard_node<K, V>(head: &mut Option<NonNull<Node<K, V>>>) {
-    if head.is_none() {
-        let mut p = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-            entry: MaybeUninit::uninit(),
-            links: Links {
-                value: ValueLinks {
-                    next: NonNull::dangling(),
-                    prev: NonNull::dangling(),
-                },
-            },
-        })));
-        p.as_mut().links.value = ValueLinks { next: p, prev: p };
-        *head = Some(p);
+fn ensure_guard_node<K, V, A: Allocator>(values: &mut Vec<Node<K, V>, A>) {
+    if values.is_empty() {
+        values.push(Node::new(Links {
+            next: GUARD,
+            prev: GUARD,
+        }));
     }
 }
 
+// Draws the seed of the order tree from the hash builder, if it has not been drawn yet.  Must be
+// called before attaching a newly allocated node.
+#[inline]
+unsafe fn seed_order<K, V, S: BuildHasher>(values: *mut Node<K, V>, hash_builder: &S) {
+    #[cfg(feature = "indexed")]
+    order::seed(values, || {
+        let hash = hash_key(hash_builder, &GUARD);
+        (hash ^ (hash >> 32)) as u32
+    });
+    #[cfg(not(feature = "indexed"))]
+    let _ = (values, hash_builder);
+}
+
 // Attach the `to_attach` node to the existing circular list *before* `node`.
 #[inline]
-unsafe fn attach_before<K, V>(mut to_attach: NonNull<Node<K, V>>, mut node: NonNull<Node<K, V>>) {
-    to_attach.as_mut().links.value = ValueLinks {
-        prev: node.as_ref().links.value.prev,
+unsafe fn attach_before<K, V>(values: *mut Node<K, V>, to_attach: u32, node: u32) {
+    let prev = (*node_at(values, node)).links.prev;
+    (*node_at_mut(values, to_attach)).links = Links { prev, next: node };
+    (*node_at_mut(values, node)).links.prev = to_attach;
+    (*node_at_mut(values, prev)).links.next = to_attach;
+    #[cfg(feature = "indexed")]
+    order::attach_before(values, to_attach, node);
+}
+
+#[inline]
+unsafe fn detach_node<K, V>(values: *mut Node<K, V>, node: u32) {
+    let Links { next, prev } = (*node_at(values, node)).links;
+    (*node_at_mut(values, prev)).links.next = next;
+    (*node_at_mut(values, next)).links.prev = prev;
+    #[cfg(feature = "indexed")]
+    order::detach(values, node);
+}
+
+// Moves `count` entries, starting at `node` and following the list order, into a new map with a
+// clone of the given hash builder.  Since the hash builders are equivalent, each key only needs to
+// be hashed once.
+unsafe fn split_nodes<K, V, S, A>(
+    hash_builder: &S,
+    values: &mut Vec<Node<K, V>, A>,
+    free: &mut u32,
+    table: &mut HashTable<u32, A>,
+    mut node: u32,
+    count: usize,
+) -> LinkedHashMap<K, V, S, A>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Clone,
+    A: Allocator + Clone,
+{
+    let mut other = LinkedHashMap::with_capacity_and_hasher_in(
+        count,
+        hash_builder.clone(),
+        values.allocator().clone(),
+    );
+    for _ in 0..count {
+        let ptr = values.as_mut_ptr();
+        let next = (*node_at(ptr, node)).links.next;
+        let hash = hash_node(hash_builder, ptr, node);
+        table.find_entry(hash, |&o| o == node).unwrap().remove();
+        let (k, v) = remove_node(ptr, free, node);
+        other.push_back_unique(hash, k, v);
+        node = next;
+    }
+    other
+}
+
+// Moves the guard node so that `node` becomes the front of the circular list, which rotates the
+// order of the entries.
+unsafe fn move_guard_before<K, V>(values: *mut Node<K, V>, node: u32) {
+    let Links {
+        next: front,
+        prev: back,
+    } = (*node_at(values, GUARD)).links;
+    let new_back = (*node_at(values, node)).links.prev;
+
+    (*node_at_mut(values, back)).links.next = front;
+    (*node_at_mut(values, front)).links.prev = back;
+    (*node_at_mut(values, new_back)).links.next = GUARD;
+    (*node_at_mut(values, node)).links.prev = GUARD;
+    (*node_at_mut(values, GUARD)).links = Links {
         next: node,
+        prev: new_back,
     };
-    node.as_mut().links.value.prev = to_attach;
-    (*to_attach.as_mut().links.value.prev.as_ptr())
-        .links
-        .value
-        .next = to_attach;
+    #[cfg(feature = "indexed")]
+    order::rebuild(values);
 }
 
-#[inline]
-unsafe fn detach_node<K, V>(mut node: NonNull<Node<K, V>>) {
-    node.as_mut().links.value.prev.as_mut().links.value.next = node.as_ref().links.value.next;
-    node.as_mut().links.value.next.as_mut().links.value.prev = node.as_ref().links.value.prev;
+// Relinks the whole list so that it contains exactly `nodes`, in order.
+unsafe fn relink_nodes<K, V>(values: *mut Node<K, V>, nodes: impl IntoIterator<Item = u32>) {
+    let mut prev = GUARD;
+    for node in nodes {
+        (*node_at_mut(values, prev)).links.next = node;
+        (*node_at_mut(values, node)).links.prev = prev;
+        prev = node;
+    }
+    (*node_at_mut(values, prev)).links.next = GUARD;
+    (*node_at_mut(values, GUARD)).links.prev = prev;
+    #[cfg(feature = "indexed")]
+    order::rebuild(values);
 }
 
 #[inline]
-unsafe fn push_free<K, V>(
-    free_list: &mut Option<NonNull<Node<K, V>>>,
-    mut node: NonNull<Node<K, V>>,
-) {
-    node.as_mut().links.free.next = *free_list;
-    *free_list = Some(node);
+unsafe fn push_free<K, V>(values: *mut Node<K, V>, free_list: &mut u32, node: u32) {
+    (*node_at_mut(values, node)).links.next = *free_list;
+    *free_list = node;
 }
 
 #[inline]
-unsafe fn pop_free<K, V>(
-    free_list: &mut Option<NonNull<Node<K, V>>>,
-) -> Option<NonNull<Node<K, V>>> {
-    if let Some(free) = *free_list {
-        *free_list = free.as_ref().links.free.next;
+unsafe fn pop_free<K, V>(values: *const Node<K, V>, free_list: &mut u32) -> Option<u32> {
+    if *free_list != NIL {
+        let free = *free_list;
+        *free_list = (*node_at(values, free)).links.next;
         Some(free)
     } else {
         None
     }
 }
 
+// Returns an unlinked node from the free list, or appends a new one to the slab.  The slab must
+// already contain the guard node.
 #[inline]
-unsafe fn allocate_node<K, V>(free_list: &mut Option<NonNull<Node<K, V>>>) -> NonNull<Node<K, V>> {
-    if let Some(mut free) = pop_free(free_list) {
-        free.as_mut().links.value = ValueLinks {
-            next: NonNull::dangling(),
-            prev: NonNull::dangling(),
-        };
+unsafe fn allocate_node<K, V, A: Allocator>(
+    values: &mut Vec<Node<K, V>, A>,
+    free_list: &mut u32,
+) -> u32 {
+    if let Some(free) = pop_free(values.as_ptr(), free_list) {
         free
     } else {
-        NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-            entry: MaybeUninit::uninit(),
-            links: Links {
-                value: ValueLinks {
-                    next: NonNull::dangling(),
-                    prev: NonNull::dangling(),
-                },
-            },
-        })))
+        let index = values.len();
+        if index >= NIL as usize {
+            panic!("capacity overflow");
+        }
+        values.push(Node::new(Links {
+            next: NIL,
+            prev: NIL,
+        }));
+        index as u32
     }
 }
 
-// Given node is assumed to be the guard node and is *not* dropped.
+// Drops the keys and values of every node from `tail` backwards until reaching the guard node.
 #[inline]
-unsafe fn drop_value_nodes<K, V>(guard: NonNull<Node<K, V>>) {
-    let mut cur = guard.as_ref().links.value.prev;
-    while cur != guard {
-        let prev = cur.as_ref().links.value.prev;
-        cur.as_mut().take_entry();
-        let _ = Box::from_raw(cur.as_ptr());
-        cur = prev;
+unsafe fn drop_value_nodes<K, V>(values: *mut Node<K, V>, tail: u32) {
+    let mut cur = tail;
+    while cur != GUARD {
+        let node = node_at_mut(values, cur);
+        cur = (*node).links.prev;
+        (*node).take_entry();
     }
 }
 
-// Drops all linked free nodes starting with the given node.  Free nodes are only non-circular
-// singly linked, and should have uninitialized keys / values.
 #[inline]
-unsafe fn drop_free_nodes<K, V>(mut free: Option<NonNull<Node<K, V>>>) {
-    while let Some(some_free) = free {
-        let next_free = some_free.as_ref().links.free.next;
-        let _ = Box::from_raw(some_free.as_ptr());
-        free = next_free;
-    }
+unsafe fn remove_node<K, V>(values: *mut Node<K, V>, free_list: &mut u32, node: u32) -> (K, V) {
+    detach_node(values, node);
+    push_free(values, free_list, node);
+    (*node_at_mut(values, node)).take_entry()
 }
 
-#[inline]
-unsafe fn remove_node<K, V>(
-    free_list: &mut Option<NonNull<Node<K, V>>>,
-    mut node: NonNull<Node<K, V>>,
-) -> (K, V) {
-    detach_node(node);
-    push_free(free_list, node);
-    node.as_mut().take_entry()
+// Rebuilds the node slab with room for the guard node and `capacity` other nodes, laying out the
+// live nodes contiguously in list order followed by `spare` nodes on the free list.  `capacity`
+// must be at least the number of entries plus `spare`.
+unsafe fn compact_values<K, V, A: Allocator + Clone>(
+    values: &mut Vec<Node<K, V>, A>,
+    free: &mut u32,
+    table: &mut HashTable<u32, A>,
+    capacity: usize,
+    spare: usize,
+) {
+    debug_assert!(capacity >= table.len() + spare);
+    *free = NIL;
+    if capacity == 0 {
+        *values = Vec::new_in(values.allocator().clone());
+        return;
+    }
+
+    let mut compacted: Vec<Node<K, V>, A> =
+        Vec::with_capacity_in(capacity + 1, values.allocator().clone());
+    compacted.push(Node::new(Links {
+        next: GUARD,
+        prev: GUARD,
+    }));
+
+    if !table.is_empty() {
+        // No user code runs from here on, so there is no need to worry about panics.  We overwrite
+        // the `next` link of each moved node with its new index, so that we can fix up the table
+        // afterwards.
+        let old = values.as_mut_ptr();
+        let mut cur = (*old).links.next;
+        while cur != GUARD {
+            let node = node_at_mut(old, cur);
+            let new_index = compacted.len() as u32;
+            cur = (*node).links.next;
+            compacted.push(Node {
+                entry: ptr::read(&(*node).entry),
+                links: Links {
+                    next: new_index + 1,
+                    prev: new_index - 1,
+                },
+                #[cfg(feature = "indexed")]
+                order: order::OrderLinks::EMPTY,
+                #[cfg(feature = "cached-hashes")]
+                hash: (*node).hash,
+            });
+            (*node).links.next = new_index;
+        }
+
+        let last = (compacted.len() - 1) as u32;
+        compacted[last as usize].links.next = GUARD;
+        compacted[GUARD as usize].links = Links {
+            next: 1,
+            prev: last,
+        };
+
+        for index in table.iter_mut() {
+            *index = (*node_at(old, *index)).links.next;
+        }
+    }
+
+    for _ in 0..spare {
+        let index = compacted.len() as u32;
+        compacted.push(Node::new(Links {
+            next: NIL,
+            prev: NIL,
+        }));
+        push_free(compacted.as_mut_ptr(), free, index);
+    }
+
+    #[cfg(feature = "indexed")]
+    {
+        if !values.is_empty() {
+            order::copy_seed(values.as_ptr(), compacted.as_mut_ptr());
+        }
+        order::rebuild(compacted.as_mut_ptr());
+    }
+
+    *values = compacted;
 }
 
 #[inline]
-unsafe fn hash_node<S, K, V>(s: &S, node: NonNull<Node<K, V>>) -> u64
+unsafe fn hash_node<S, K, V>(s: &S, values: *const Node<K, V>, node: u32) -> u64
 where
     S: BuildHasher,
     K: Hash,
 {
-    hash_key(s, node.as_ref().key_ref())
+    cached_hash_or(values, node, |k| hash_key(s, k))
+}
+
+// Returns the hash of the key of the given live node, which is either cached in the node or
+// computed with `hasher`.
+#[inline]
+unsafe fn cached_hash_or<K, V>(
+    values: *const Node<K, V>,
+    node: u32,
+    hasher: impl Fn(&K) -> u64,
+) -> u64 {
+    #[cfg(feature = "cached-hashes")]
+    {
+        let _ = hasher;
+        (*node_at(values, node)).hash
+    }
+    #[cfg(not(feature = "cached-hashes"))]
+    hasher((*node_at(values, node)).key_ref())
 }
 
 #[inline]
-fn hash_key<S, Q>(s: &S, k: &Q) -> u64
+pub(crate) fn hash_key<S, Q>(s: &S, k: &Q) -> u64
 where
     S: BuildHasher,
     Q: Hash + ?Sized,
@@ -2281,22 +4708,40 @@ where
 
 // We do not drop the key and value when a value is filtered from the map during the call to
 // `retain`.  We need to be very careful not to have a live `HashMap` entry pointing to
-// either a dangling `Node` or a `Node` with dropped keys / values.  Since the key and value
+// either a free slot or a `Node` with dropped keys / values.  Since the key and value
 // types may panic on drop, they may short-circuit the entry in the map actually being
 // removed.  Instead, we push the removed nodes onto the free list eagerly, then try and
 // drop the keys and values for any newly freed nodes *after* `HashMap::retain` has
 // completely finished.
 struct DropFilteredValues<'a, K, V> {
-    free: &'a mut Option<NonNull<Node<K, V>>>,
-    cur_free: Option<NonNull<Node<K, V>>>,
+    values: *mut Node<K, V>,
+    free: &'a mut u32,
+    cur_free: u32,
+    // The head of the free list once every filtered node is on it, if already known.
+    end_free: Option<u32>,
 }
 
 impl<K, V> DropFilteredValues<'_, K, V> {
     #[inline]
-    fn drop_later(&mut self, node: NonNull<Node<K, V>>) {
+    fn drop_later(&mut self, node: u32) {
+        unsafe {
+            detach_node(self.values, node);
+            push_free(self.values, &mut self.cur_free, node);
+        }
+    }
+
+    // Moves every filtered entry out and passes it to `f`.  If `f` panics, the remaining entries
+    // are dropped as usual.
+    #[inline]
+    fn take_each<F: FnMut(K, V)>(mut self, mut f: F) {
         unsafe {
-            detach_node(node);
-            push_free(&mut self.cur_free, node);
+            self.end_free = Some(self.cur_free);
+            while self.cur_free != *self.free {
+                let cur_free = node_at_mut(self.values, self.cur_free);
+                let (k, v) = (*cur_free).take_entry();
+                self.cur_free = (*cur_free).links.next;
+                f(k, v);
+            }
         }
     }
 }
@@ -2304,11 +4749,11 @@ impl<K, V> DropFilteredValues<'_, K, V> {
 impl<K, V> Drop for DropFilteredValues<'_, K, V> {
     fn drop(&mut self) {
         unsafe {
-            let end_free = self.cur_free;
+            let end_free = self.end_free.unwrap_or(self.cur_free);
             while self.cur_free != *self.free {
-                let cur_free = self.cur_free.as_ptr();
+                let cur_free = node_at_mut(self.values, self.cur_free);
                 (*cur_free).take_entry();
-                self.cur_free = (*cur_free).links.free.next;
+                self.cur_free = (*cur_free).links.next;
             }
             *self.free = end_free;
         }
@@ -0,0 +1,304 @@
+// An order statistics tree layered over the linked list of a `LinkedHashMap`, which lets us find
+// the position of a node and the node at a position in `O(log n)`.
+//
+// The tree is an implicit treap: it is keyed by list position only, so its in-order traversal is
+// always exactly the list order, and it must be kept in sync every time a node is attached to or
+// detached from the list.  Each node stores its tree links alongside its list links, and the root
+// of the tree is stored in the `left` link of the guard node, which makes the guard node the
+// "parent" of the root.  Rather than storing a priority per node, we derive it from the node's
+// slab index with an integer hash, mixed with a per-map seed drawn from the map's hash builder so
+// that the shape of the tree can't be predicted from the insertion pattern alone.  The seed lives
+// in the otherwise unused `parent` link of the guard node, where `NIL` means no seed has been
+// drawn yet.
+
+use super::{node_at, node_at_mut, Node, GUARD, NIL};
+
+#[derive(Clone, Copy)]
+pub(super) struct OrderLinks {
+    parent: u32,
+    left: u32,
+    right: u32,
+    // The number of nodes in the subtree rooted at this node, including itself.
+    size: u32,
+}
+
+impl OrderLinks {
+    pub(super) const EMPTY: OrderLinks = OrderLinks {
+        parent: NIL,
+        left: NIL,
+        right: NIL,
+        size: 0,
+    };
+}
+
+// Seeds the priorities with the result of `seed` if no seed has been drawn yet, which must happen
+// before the first node is attached.
+#[inline]
+pub(super) unsafe fn seed<K, V>(values: *mut Node<K, V>, seed: impl FnOnce() -> u32) {
+    let guard = links_mut(values, GUARD);
+    if guard.parent == NIL {
+        debug_assert_eq!(guard.left, NIL);
+        guard.parent = match seed() {
+            NIL => 0,
+            seed => seed,
+        };
+    }
+}
+
+// Carries the seed of the tree in `from` over to the tree in `to`, which must not have any nodes
+// attached yet.
+#[inline]
+pub(super) unsafe fn copy_seed<K, V>(from: *const Node<K, V>, to: *mut Node<K, V>) {
+    debug_assert_eq!(links(to, GUARD).left, NIL);
+    links_mut(to, GUARD).parent = links(from, GUARD).parent;
+}
+
+// Inserts `node` into the tree immediately before `before` in list order, or as the last node if
+// `before` is the guard node.
+pub(super) unsafe fn attach_before<K, V>(values: *mut Node<K, V>, node: u32, before: u32) {
+    let parent = if before == GUARD {
+        match root(values) {
+            NIL => GUARD,
+            root => rightmost(values, root),
+        }
+    } else {
+        match links(values, before).left {
+            NIL => before,
+            left => rightmost(values, left),
+        }
+    };
+
+    *links_mut(values, node) = OrderLinks {
+        parent,
+        left: NIL,
+        right: NIL,
+        size: 1,
+    };
+    // The new node always becomes the left child of `before` or the right child of its in-order
+    // predecessor, and the root always hangs from the guard's left link.
+    if parent == GUARD || parent == before {
+        links_mut(values, parent).left = node;
+    } else {
+        links_mut(values, parent).right = node;
+    }
+
+    let mut cur = parent;
+    while cur != GUARD {
+        let l = links_mut(values, cur);
+        l.size += 1;
+        cur = l.parent;
+    }
+
+    loop {
+        let parent = links(values, node).parent;
+        if parent == GUARD || priority(values, parent) > priority(values, node) {
+            break;
+        }
+        rotate_up(values, node);
+    }
+}
+
+// Removes `node` from the tree, leaving its tree links invalid.
+pub(super) unsafe fn detach<K, V>(values: *mut Node<K, V>, node: u32) {
+    // Rotate the node down until it has at most one child, keeping the heap order intact.
+    loop {
+        let OrderLinks { left, right, .. } = *links(values, node);
+        if left == NIL || right == NIL {
+            break;
+        }
+        if priority(values, left) > priority(values, right) {
+            rotate_up(values, left);
+        } else {
+            rotate_up(values, right);
+        }
+    }
+
+    let OrderLinks {
+        parent,
+        left,
+        right,
+        ..
+    } = *links(values, node);
+    let child = if left != NIL { left } else { right };
+    replace_child(values, parent, node, child);
+    if child != NIL {
+        links_mut(values, child).parent = parent;
+    }
+
+    let mut cur = parent;
+    while cur != GUARD {
+        let l = links_mut(values, cur);
+        l.size -= 1;
+        cur = l.parent;
+    }
+}
+
+// Returns the position of `node` in the list.
+pub(super) unsafe fn position<K, V>(values: *const Node<K, V>, node: u32) -> usize {
+    let mut pos = size(values, links(values, node).left);
+    let mut cur = node;
+    let mut parent = links(values, node).parent;
+    while parent != GUARD {
+        let l = links(values, parent);
+        if l.right == cur {
+            pos += size(values, l.left) + 1;
+        }
+        cur = parent;
+        parent = l.parent;
+    }
+    pos as usize
+}
+
+// Returns the node at position `index` in the list, which must be less than the list's length.
+pub(super) unsafe fn select<K, V>(values: *const Node<K, V>, index: usize) -> u32 {
+    let mut index = index as u32;
+    let mut cur = root(values);
+    loop {
+        let l = links(values, cur);
+        let left_size = size(values, l.left);
+        if index < left_size {
+            cur = l.left;
+        } else if index == left_size {
+            return cur;
+        } else {
+            index -= left_size + 1;
+            cur = l.right;
+        }
+    }
+}
+
+// Rebuilds the whole tree from the list order in `O(n)`, for operations which relink many nodes
+// at once.
+pub(super) unsafe fn rebuild<K, V>(values: *mut Node<K, V>) {
+    links_mut(values, GUARD).left = NIL;
+
+    // Build a Cartesian tree by keeping the right spine of the tree built so far, walking up the
+    // spine with parent links.  A node leaves the spine once its subtree is complete, which is
+    // when its size can be computed.
+    let mut last = GUARD;
+    let mut cur = (*node_at(values, GUARD)).links.next;
+    while cur != GUARD {
+        let mut spine = last;
+        let mut popped = NIL;
+        while spine != GUARD && priority(values, spine) < priority(values, cur) {
+            update_size(values, spine);
+            popped = spine;
+            spine = links(values, spine).parent;
+        }
+
+        *links_mut(values, cur) = OrderLinks {
+            parent: spine,
+            left: popped,
+            right: NIL,
+            size: 1,
+        };
+        if popped != NIL {
+            links_mut(values, popped).parent = cur;
+        }
+        if spine == GUARD {
+            links_mut(values, GUARD).left = cur;
+        } else {
+            links_mut(values, spine).right = cur;
+        }
+
+        last = cur;
+        cur = (*node_at(values, cur)).links.next;
+    }
+
+    while last != GUARD {
+        update_size(values, last);
+        last = links(values, last).parent;
+    }
+}
+
+// Rotates `node` above its parent, which must not be the guard node.
+unsafe fn rotate_up<K, V>(values: *mut Node<K, V>, node: u32) {
+    let parent = links(values, node).parent;
+    let grandparent = links(values, parent).parent;
+
+    if links(values, parent).left == node {
+        let inner = links(values, node).right;
+        links_mut(values, parent).left = inner;
+        if inner != NIL {
+            links_mut(values, inner).parent = parent;
+        }
+        links_mut(values, node).right = parent;
+    } else {
+        let inner = links(values, node).left;
+        links_mut(values, parent).right = inner;
+        if inner != NIL {
+            links_mut(values, inner).parent = parent;
+        }
+        links_mut(values, node).left = parent;
+    }
+
+    links_mut(values, parent).parent = node;
+    links_mut(values, node).parent = grandparent;
+    replace_child(values, grandparent, parent, node);
+
+    links_mut(values, node).size = links(values, parent).size;
+    update_size(values, parent);
+}
+
+#[inline]
+unsafe fn replace_child<K, V>(values: *mut Node<K, V>, parent: u32, old: u32, new: u32) {
+    let l = links_mut(values, parent);
+    if l.left == old {
+        l.left = new;
+    } else {
+        l.right = new;
+    }
+}
+
+#[inline]
+unsafe fn update_size<K, V>(values: *mut Node<K, V>, node: u32) {
+    let OrderLinks { left, right, .. } = *links(values, node);
+    links_mut(values, node).size = size(values, left) + size(values, right) + 1;
+}
+
+#[inline]
+unsafe fn rightmost<K, V>(values: *const Node<K, V>, mut node: u32) -> u32 {
+    loop {
+        match links(values, node).right {
+            NIL => return node,
+            right => node = right,
+        }
+    }
+}
+
+#[inline]
+unsafe fn root<K, V>(values: *const Node<K, V>) -> u32 {
+    links(values, GUARD).left
+}
+
+#[inline]
+unsafe fn size<K, V>(values: *const Node<K, V>, node: u32) -> u32 {
+    if node == NIL {
+        0
+    } else {
+        links(values, node).size
+    }
+}
+
+#[inline]
+unsafe fn links<'a, K: 'a, V: 'a>(values: *const Node<K, V>, node: u32) -> &'a OrderLinks {
+    &(*node_at(values, node)).order
+}
+
+#[inline]
+unsafe fn links_mut<'a, K: 'a, V: 'a>(values: *mut Node<K, V>, node: u32) -> &'a mut OrderLinks {
+    &mut (*node_at_mut(values, node)).order
+}
+
+// A bijective integer hash of the slab index mixed with the seed, so no two nodes ever share a
+// priority.
+#[inline]
+unsafe fn priority<K, V>(values: *const Node<K, V>, node: u32) -> u32 {
+    let mut x = node ^ links(values, GUARD).parent;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
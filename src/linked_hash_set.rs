@@ -1,5 +1,5 @@
 use core::{
-    borrow::Borrow,
+    cmp::Ordering,
     fmt,
     hash::{BuildHasher, Hash, Hasher},
     iter::{Chain, FromIterator},
@@ -7,10 +7,10 @@ use core::{
 };
 
 use crate::linked_hash_map::{self, LinkedHashMap, TryReserveError};
-use crate::DefaultHashBuilder;
+use crate::{Allocator, DefaultHashBuilder, Equivalent, Global};
 
-pub struct LinkedHashSet<T, S = DefaultHashBuilder> {
-    map: LinkedHashMap<T, (), S>,
+pub struct LinkedHashSet<T, S = DefaultHashBuilder, A: Allocator = Global> {
+    map: LinkedHashMap<T, (), S, A>,
 }
 
 impl<T: Hash + Eq> LinkedHashSet<T, DefaultHashBuilder> {
@@ -29,7 +29,13 @@ impl<T: Hash + Eq> LinkedHashSet<T, DefaultHashBuilder> {
     }
 }
 
-impl<T, S> LinkedHashSet<T, S> {
+impl<T, S, A: Allocator> LinkedHashSet<T, S, A> {
+    /// Returns a reference to the allocator used by this set.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.map.allocator()
+    }
+
     #[inline]
     pub fn capacity(&self) -> usize {
         self.map.capacity()
@@ -53,7 +59,7 @@ impl<T, S> LinkedHashSet<T, S> {
     }
 
     #[inline]
-    pub fn drain(&mut self) -> Drain<T> {
+    pub fn drain(&mut self) -> Drain<'_, T> {
         Drain {
             iter: self.map.drain(),
         }
@@ -71,6 +77,70 @@ impl<T, S> LinkedHashSet<T, S> {
     {
         self.map.retain(|k, _| f(k));
     }
+
+    /// Sorts the set's values with the given comparison function.
+    ///
+    /// This sort is stable, and does not rehash any values.
+    #[inline]
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        A: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.map.sort_by(|a, _, b, _| cmp(a, b));
+    }
+
+    /// Sorts the set's values with the given key extraction function.
+    ///
+    /// This sort is stable, and does not rehash any values.
+    #[inline]
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        A: Clone,
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.map.sort_by_key(|k, _| f(k));
+    }
+
+    /// Sorts the set's values.
+    ///
+    /// This sort is stable, and does not rehash any values.
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        A: Clone,
+        T: Ord,
+    {
+        self.map.sort_keys();
+    }
+
+    /// Sorts the set's values with the given comparison function, but might not preserve the
+    /// order of equal values.
+    ///
+    /// Does not rehash any values.
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, mut cmp: F)
+    where
+        A: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.map.sort_unstable_by(|a, _, b, _| cmp(a, b));
+    }
+
+    /// Sorts the set's values with the given key extraction function, calling it only once per
+    /// value.
+    ///
+    /// This sort is stable, and does not rehash any values.
+    #[inline]
+    pub fn sort_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        A: Clone,
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.map.sort_by_cached_key(|k, _| f(k));
+    }
 }
 
 impl<T, S> LinkedHashSet<T, S>
@@ -91,7 +161,54 @@ where
             map: LinkedHashMap::with_capacity_and_hasher(capacity, hasher),
         }
     }
+}
+
+impl<T: Hash + Eq, A: Allocator + Clone> LinkedHashSet<T, DefaultHashBuilder, A> {
+    /// Creates an empty `LinkedHashSet` which allocates with the given allocator.
+    #[inline]
+    pub fn new_in(alloc: A) -> LinkedHashSet<T, DefaultHashBuilder, A> {
+        LinkedHashSet {
+            map: LinkedHashMap::new_in(alloc),
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> LinkedHashSet<T, DefaultHashBuilder, A> {
+        LinkedHashSet {
+            map: LinkedHashMap::with_capacity_in(capacity, alloc),
+        }
+    }
+}
+
+impl<T, S, A: Allocator + Clone> LinkedHashSet<T, S, A>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn with_hasher_in(hasher: S, alloc: A) -> LinkedHashSet<T, S, A> {
+        LinkedHashSet {
+            map: LinkedHashMap::with_hasher_in(hasher, alloc),
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity_and_hasher_in(
+        capacity: usize,
+        hasher: S,
+        alloc: A,
+    ) -> LinkedHashSet<T, S, A> {
+        LinkedHashSet {
+            map: LinkedHashMap::with_capacity_and_hasher_in(capacity, hasher, alloc),
+        }
+    }
+}
 
+impl<T, S, A: Allocator> LinkedHashSet<T, S, A>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
     #[inline]
     pub fn hasher(&self) -> &S {
         self.map.hasher()
@@ -108,12 +225,15 @@ where
     }
 
     #[inline]
-    pub fn shrink_to_fit(&mut self) {
+    pub fn shrink_to_fit(&mut self)
+    where
+        A: Clone,
+    {
         self.map.shrink_to_fit()
     }
 
     #[inline]
-    pub fn difference<'a>(&'a self, other: &'a LinkedHashSet<T, S>) -> Difference<'a, T, S> {
+    pub fn difference<'a>(&'a self, other: &'a LinkedHashSet<T, S, A>) -> Difference<'a, T, S, A> {
         Difference {
             iter: self.iter(),
             other,
@@ -123,15 +243,18 @@ where
     #[inline]
     pub fn symmetric_difference<'a>(
         &'a self,
-        other: &'a LinkedHashSet<T, S>,
-    ) -> SymmetricDifference<'a, T, S> {
+        other: &'a LinkedHashSet<T, S, A>,
+    ) -> SymmetricDifference<'a, T, S, A> {
         SymmetricDifference {
             iter: self.difference(other).chain(other.difference(self)),
         }
     }
 
     #[inline]
-    pub fn intersection<'a>(&'a self, other: &'a LinkedHashSet<T, S>) -> Intersection<'a, T, S> {
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a LinkedHashSet<T, S, A>,
+    ) -> Intersection<'a, T, S, A> {
         Intersection {
             iter: self.iter(),
             other,
@@ -139,7 +262,7 @@ where
     }
 
     #[inline]
-    pub fn union<'a>(&'a self, other: &'a LinkedHashSet<T, S>) -> Union<'a, T, S> {
+    pub fn union<'a>(&'a self, other: &'a LinkedHashSet<T, S, A>) -> Union<'a, T, S, A> {
         Union {
             iter: self.iter().chain(other.difference(self)),
         }
@@ -148,8 +271,7 @@ where
     #[inline]
     pub fn contains<Q>(&self, value: &Q) -> bool
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
     {
         self.map.contains_key(value)
     }
@@ -157,8 +279,7 @@ where
     #[inline]
     pub fn get<Q>(&self, value: &Q) -> Option<&T>
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
     {
         self.map.raw_entry().from_key(value).map(|p| p.0)
     }
@@ -175,8 +296,7 @@ where
     #[inline]
     pub fn get_or_insert_with<Q, F>(&mut self, value: &Q, f: F) -> &T
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
         F: FnOnce(&Q) -> T,
     {
         self.map
@@ -187,17 +307,17 @@ where
     }
 
     #[inline]
-    pub fn is_disjoint(&self, other: &LinkedHashSet<T, S>) -> bool {
+    pub fn is_disjoint(&self, other: &LinkedHashSet<T, S, A>) -> bool {
         self.iter().all(|v| !other.contains(v))
     }
 
     #[inline]
-    pub fn is_subset(&self, other: &LinkedHashSet<T, S>) -> bool {
+    pub fn is_subset(&self, other: &LinkedHashSet<T, S, A>) -> bool {
         self.iter().all(|v| other.contains(v))
     }
 
     #[inline]
-    pub fn is_superset(&self, other: &LinkedHashSet<T, S>) -> bool {
+    pub fn is_superset(&self, other: &LinkedHashSet<T, S, A>) -> bool {
         other.is_subset(self)
     }
 
@@ -211,6 +331,41 @@ where
         self.map.insert(value, ()).is_none()
     }
 
+    /// Similar to `LinkedHashSet::insert`, but returns an error rather than panicking or aborting
+    /// if room for a new value cannot be allocated.
+    ///
+    /// If an error is returned, the set is left unchanged.
+    #[inline]
+    pub fn try_insert(&mut self, value: T) -> Result<bool, TryReserveError> {
+        Ok(self.map.try_insert(value, ())?.is_none())
+    }
+
+    /// Similar to `Extend::extend`, but returns an error rather than panicking or aborting if room
+    /// for a new value cannot be allocated.
+    ///
+    /// If an error is returned, the set is left unchanged and the values from the iterator are
+    /// dropped.
+    #[inline]
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError>
+    where
+        A: Clone,
+    {
+        self.map.try_extend(iter.into_iter().map(|x| (x, ())))
+    }
+
+    /// Similar to `Clone::clone`, but returns an error rather than panicking or aborting if the
+    /// new set cannot be allocated.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, TryReserveError>
+    where
+        T: Clone,
+        S: Clone,
+        A: Clone,
+    {
+        let map = self.map.try_clone()?;
+        Ok(Self { map })
+    }
+
     /// Adds the given value to the set, replacing the existing value.
     ///
     /// If a previous value existed, returns the replaced value.  In this case, the value's position
@@ -218,7 +373,7 @@ where
     #[inline]
     pub fn replace(&mut self, value: T) -> Option<T> {
         match self.map.entry(value) {
-            linked_hash_map::Entry::Occupied(occupied) => Some(occupied.replace_key()),
+            linked_hash_map::Entry::Occupied(occupied) => occupied.replace_key().ok(),
             linked_hash_map::Entry::Vacant(vacant) => {
                 vacant.insert(());
                 None
@@ -229,8 +384,7 @@ where
     #[inline]
     pub fn remove<Q>(&mut self, value: &Q) -> bool
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
     {
         self.map.remove(value).is_some()
     }
@@ -238,8 +392,7 @@ where
     #[inline]
     pub fn take<Q>(&mut self, value: &Q) -> Option<T>
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
     {
         match self.map.raw_entry_mut().from_key(value) {
             linked_hash_map::RawEntryMut::Occupied(occupied) => Some(occupied.remove_entry().0),
@@ -270,8 +423,7 @@ where
     #[inline]
     pub fn to_front<Q>(&mut self, value: &Q) -> bool
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
     {
         match self.map.raw_entry_mut().from_key(value) {
             linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
@@ -285,8 +437,7 @@ where
     #[inline]
     pub fn to_back<Q>(&mut self, value: &Q) -> bool
     where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<T> + ?Sized,
     {
         match self.map.raw_entry_mut().from_key(value) {
             linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
@@ -304,9 +455,27 @@ where
     {
         self.map.retain_with_order(|k, _| f(k));
     }
+
+    /// Returns an iterator which walks the set in order, removing and yielding every value for
+    /// which `pred` returns `true`.
+    ///
+    /// Values are only removed as the iterator is advanced.  If it is dropped before being fully
+    /// consumed, the remaining values are simply kept.
+    #[inline]
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, S, A>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        ExtractIf {
+            state: linked_hash_map::ExtractIfState::new(&mut self.map),
+            pred,
+        }
+    }
 }
 
-impl<T: Hash + Eq + Clone, S: BuildHasher + Clone> Clone for LinkedHashSet<T, S> {
+impl<T: Hash + Eq + Clone, S: BuildHasher + Clone, A: Allocator + Clone> Clone
+    for LinkedHashSet<T, S, A>
+{
     #[inline]
     fn clone(&self) -> Self {
         let map = self.map.clone();
@@ -314,7 +483,7 @@ impl<T: Hash + Eq + Clone, S: BuildHasher + Clone> Clone for LinkedHashSet<T, S>
     }
 }
 
-impl<T, S> PartialEq for LinkedHashSet<T, S>
+impl<T, S, A: Allocator> PartialEq for LinkedHashSet<T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
@@ -325,7 +494,7 @@ where
     }
 }
 
-impl<T, S> Hash for LinkedHashSet<T, S>
+impl<T, S, A: Allocator> Hash for LinkedHashSet<T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
@@ -338,14 +507,14 @@ where
     }
 }
 
-impl<T, S> Eq for LinkedHashSet<T, S>
+impl<T, S, A: Allocator> Eq for LinkedHashSet<T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
 {
 }
 
-impl<T, S> fmt::Debug for LinkedHashSet<T, S>
+impl<T, S, A: Allocator> fmt::Debug for LinkedHashSet<T, S, A>
 where
     T: fmt::Debug,
 {
@@ -355,20 +524,21 @@ where
     }
 }
 
-impl<T, S> FromIterator<T> for LinkedHashSet<T, S>
+impl<T, S, A> FromIterator<T> for LinkedHashSet<T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher + Default,
+    A: Allocator + Default,
 {
     #[inline]
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> LinkedHashSet<T, S> {
-        let mut set = LinkedHashSet::with_hasher(Default::default());
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> LinkedHashSet<T, S, A> {
+        let mut set = LinkedHashSet::default();
         set.extend(iter);
         set
     }
 }
 
-impl<T, S> Extend<T> for LinkedHashSet<T, S>
+impl<T, S, A: Allocator> Extend<T> for LinkedHashSet<T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
@@ -379,7 +549,7 @@ where
     }
 }
 
-impl<'a, T, S> Extend<&'a T> for LinkedHashSet<T, S>
+impl<'a, T, S, A: Allocator> Extend<&'a T> for LinkedHashSet<T, S, A>
 where
     T: 'a + Eq + Hash + Copy,
     S: BuildHasher,
@@ -390,66 +560,71 @@ where
     }
 }
 
-impl<T, S> Default for LinkedHashSet<T, S>
+impl<T, S, A> Default for LinkedHashSet<T, S, A>
 where
     S: Default,
+    A: Allocator + Default,
 {
     #[inline]
-    fn default() -> LinkedHashSet<T, S> {
+    fn default() -> LinkedHashSet<T, S, A> {
         LinkedHashSet {
             map: LinkedHashMap::default(),
         }
     }
 }
 
-impl<T, S> BitOr<&LinkedHashSet<T, S>> for &LinkedHashSet<T, S>
+impl<T, S, A> BitOr<&LinkedHashSet<T, S, A>> for &LinkedHashSet<T, S, A>
 where
     T: Eq + Hash + Clone,
     S: BuildHasher + Default,
+    A: Allocator + Default,
 {
-    type Output = LinkedHashSet<T, S>;
+    type Output = LinkedHashSet<T, S, A>;
 
     #[inline]
-    fn bitor(self, rhs: &LinkedHashSet<T, S>) -> LinkedHashSet<T, S> {
+    fn bitor(self, rhs: &LinkedHashSet<T, S, A>) -> LinkedHashSet<T, S, A> {
         self.union(rhs).cloned().collect()
     }
 }
 
-impl<T, S> BitAnd<&LinkedHashSet<T, S>> for &LinkedHashSet<T, S>
+impl<T, S, A> BitAnd<&LinkedHashSet<T, S, A>> for &LinkedHashSet<T, S, A>
 where
     T: Eq + Hash + Clone,
     S: BuildHasher + Default,
+    A: Allocator + Default,
 {
-    type Output = LinkedHashSet<T, S>;
+    type Output = LinkedHashSet<T, S, A>;
 
     #[inline]
-    fn bitand(self, rhs: &LinkedHashSet<T, S>) -> LinkedHashSet<T, S> {
+    fn bitand(self, rhs: &LinkedHashSet<T, S, A>) -> LinkedHashSet<T, S, A> {
         self.intersection(rhs).cloned().collect()
     }
 }
 
-impl<T, S> BitXor<&LinkedHashSet<T, S>> for &LinkedHashSet<T, S>
+impl<T, S, A> BitXor<&LinkedHashSet<T, S, A>> for &LinkedHashSet<T, S, A>
 where
     T: Eq + Hash + Clone,
     S: BuildHasher + Default,
+    A: Allocator + Default,
 {
-    type Output = LinkedHashSet<T, S>;
+    type Output = LinkedHashSet<T, S, A>;
 
     #[inline]
-    fn bitxor(self, rhs: &LinkedHashSet<T, S>) -> LinkedHashSet<T, S> {
+    fn bitxor(self, rhs: &LinkedHashSet<T, S, A>) -> LinkedHashSet<T, S, A> {
         self.symmetric_difference(rhs).cloned().collect()
     }
 }
 
-impl<T, S> Sub<&LinkedHashSet<T, S>> for &LinkedHashSet<T, S>
+impl<T, S, A> Sub<&LinkedHashSet<T, S, A>> for &LinkedHashSet<T, S, A>
 where
     T: Eq + Hash + Clone,
     S: BuildHasher + Default,
+    A: Allocator + Default,
 {
-    type Output = LinkedHashSet<T, S>;
+    type Output = LinkedHashSet<T, S, A>;
 
     #[inline]
-    fn sub(self, rhs: &LinkedHashSet<T, S>) -> LinkedHashSet<T, S> {
+    fn sub(self, rhs: &LinkedHashSet<T, S, A>) -> LinkedHashSet<T, S, A> {
         self.difference(rhs).cloned().collect()
     }
 }
@@ -458,33 +633,38 @@ pub struct Iter<'a, K> {
     iter: linked_hash_map::Keys<'a, K, ()>,
 }
 
-pub struct IntoIter<K> {
-    iter: linked_hash_map::IntoIter<K, ()>,
+pub struct IntoIter<K, A: Allocator = Global> {
+    iter: linked_hash_map::IntoIter<K, (), A>,
 }
 
 pub struct Drain<'a, K: 'a> {
     iter: linked_hash_map::Drain<'a, K, ()>,
 }
 
-pub struct Intersection<'a, T, S> {
+pub struct ExtractIf<'a, K, F, S = DefaultHashBuilder, A: Allocator = Global> {
+    state: linked_hash_map::ExtractIfState<'a, K, (), S, A>,
+    pred: F,
+}
+
+pub struct Intersection<'a, T, S, A: Allocator = Global> {
     iter: Iter<'a, T>,
-    other: &'a LinkedHashSet<T, S>,
+    other: &'a LinkedHashSet<T, S, A>,
 }
 
-pub struct Difference<'a, T, S> {
+pub struct Difference<'a, T, S, A: Allocator = Global> {
     iter: Iter<'a, T>,
-    other: &'a LinkedHashSet<T, S>,
+    other: &'a LinkedHashSet<T, S, A>,
 }
 
-pub struct SymmetricDifference<'a, T, S> {
-    iter: Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+pub struct SymmetricDifference<'a, T, S, A: Allocator = Global> {
+    iter: Chain<Difference<'a, T, S, A>, Difference<'a, T, S, A>>,
 }
 
-pub struct Union<'a, T, S> {
-    iter: Chain<Iter<'a, T>, Difference<'a, T, S>>,
+pub struct Union<'a, T, S, A: Allocator = Global> {
+    iter: Chain<Iter<'a, T>, Difference<'a, T, S, A>>,
 }
 
-impl<'a, T, S> IntoIterator for &'a LinkedHashSet<T, S> {
+impl<'a, T, S, A: Allocator> IntoIterator for &'a LinkedHashSet<T, S, A> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
 
@@ -494,12 +674,12 @@ impl<'a, T, S> IntoIterator for &'a LinkedHashSet<T, S> {
     }
 }
 
-impl<T, S> IntoIterator for LinkedHashSet<T, S> {
+impl<T, S, A: Allocator> IntoIterator for LinkedHashSet<T, S, A> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = IntoIter<T, A>;
 
     #[inline]
-    fn into_iter(self) -> IntoIter<T> {
+    fn into_iter(self) -> IntoIter<T, A> {
         IntoIter {
             iter: self.map.into_iter(),
         }
@@ -544,7 +724,7 @@ impl<K: fmt::Debug> fmt::Debug for Iter<'_, K> {
     }
 }
 
-impl<K> Iterator for IntoIter<K> {
+impl<K, A: Allocator> Iterator for IntoIter<K, A> {
     type Item = K;
 
     #[inline]
@@ -558,9 +738,9 @@ impl<K> Iterator for IntoIter<K> {
     }
 }
 
-impl<K> ExactSizeIterator for IntoIter<K> {}
+impl<K, A: Allocator> ExactSizeIterator for IntoIter<K, A> {}
 
-impl<K> DoubleEndedIterator for IntoIter<K> {
+impl<K, A: Allocator> DoubleEndedIterator for IntoIter<K, A> {
     #[inline]
     fn next_back(&mut self) -> Option<K> {
         self.iter.next_back().map(|(k, _)| k)
@@ -590,9 +770,37 @@ impl<K> DoubleEndedIterator for Drain<'_, K> {
 
 impl<K> ExactSizeIterator for Drain<'_, K> {}
 
-impl<'a, T, S> Clone for Intersection<'a, T, S> {
+impl<K, F, S, A> Iterator for ExtractIf<'_, K, F, S, A>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+    A: Allocator,
+    F: FnMut(&K) -> bool,
+{
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        let pred = &mut self.pred;
+        self.state.next(|k, _| pred(k)).map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.state.size_hint()
+    }
+}
+
+impl<K, F, S, A: Allocator> fmt::Debug for ExtractIf<'_, K, F, S, A> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractIf").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, S, A: Allocator> Clone for Intersection<'a, T, S, A> {
     #[inline]
-    fn clone(&self) -> Intersection<'a, T, S> {
+    fn clone(&self) -> Intersection<'a, T, S, A> {
         Intersection {
             iter: self.iter.clone(),
             ..*self
@@ -600,7 +808,7 @@ impl<'a, T, S> Clone for Intersection<'a, T, S> {
     }
 }
 
-impl<'a, T, S> Iterator for Intersection<'a, T, S>
+impl<'a, T, S, A: Allocator> Iterator for Intersection<'a, T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
@@ -628,7 +836,7 @@ where
     }
 }
 
-impl<T, S> fmt::Debug for Intersection<'_, T, S>
+impl<T, S, A: Allocator> fmt::Debug for Intersection<'_, T, S, A>
 where
     T: fmt::Debug + Eq + Hash,
     S: BuildHasher,
@@ -639,9 +847,9 @@ where
     }
 }
 
-impl<'a, T, S> Clone for Difference<'a, T, S> {
+impl<'a, T, S, A: Allocator> Clone for Difference<'a, T, S, A> {
     #[inline]
-    fn clone(&self) -> Difference<'a, T, S> {
+    fn clone(&self) -> Difference<'a, T, S, A> {
         Difference {
             iter: self.iter.clone(),
             ..*self
@@ -649,7 +857,7 @@ impl<'a, T, S> Clone for Difference<'a, T, S> {
     }
 }
 
-impl<'a, T, S> Iterator for Difference<'a, T, S>
+impl<'a, T, S, A: Allocator> Iterator for Difference<'a, T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
@@ -677,7 +885,7 @@ where
     }
 }
 
-impl<T, S> fmt::Debug for Difference<'_, T, S>
+impl<T, S, A: Allocator> fmt::Debug for Difference<'_, T, S, A>
 where
     T: fmt::Debug + Eq + Hash,
     S: BuildHasher,
@@ -688,16 +896,16 @@ where
     }
 }
 
-impl<'a, T, S> Clone for SymmetricDifference<'a, T, S> {
+impl<'a, T, S, A: Allocator> Clone for SymmetricDifference<'a, T, S, A> {
     #[inline]
-    fn clone(&self) -> SymmetricDifference<'a, T, S> {
+    fn clone(&self) -> SymmetricDifference<'a, T, S, A> {
         SymmetricDifference {
             iter: self.iter.clone(),
         }
     }
 }
 
-impl<'a, T, S> Iterator for SymmetricDifference<'a, T, S>
+impl<'a, T, S, A: Allocator> Iterator for SymmetricDifference<'a, T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
@@ -715,7 +923,7 @@ where
     }
 }
 
-impl<T, S> fmt::Debug for SymmetricDifference<'_, T, S>
+impl<T, S, A: Allocator> fmt::Debug for SymmetricDifference<'_, T, S, A>
 where
     T: fmt::Debug + Eq + Hash,
     S: BuildHasher,
@@ -726,16 +934,16 @@ where
     }
 }
 
-impl<'a, T, S> Clone for Union<'a, T, S> {
+impl<'a, T, S, A: Allocator> Clone for Union<'a, T, S, A> {
     #[inline]
-    fn clone(&self) -> Union<'a, T, S> {
+    fn clone(&self) -> Union<'a, T, S, A> {
         Union {
             iter: self.iter.clone(),
         }
     }
 }
 
-impl<T, S> fmt::Debug for Union<'_, T, S>
+impl<T, S, A: Allocator> fmt::Debug for Union<'_, T, S, A>
 where
     T: fmt::Debug + Eq + Hash,
     S: BuildHasher,
@@ -746,7 +954,7 @@ where
     }
 }
 
-impl<'a, T, S> Iterator for Union<'a, T, S>
+impl<'a, T, S, A: Allocator> Iterator for Union<'a, T, S, A>
 where
     T: Eq + Hash,
     S: BuildHasher,
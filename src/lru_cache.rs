@@ -1,20 +1,80 @@
+#[cfg(feature = "stats")]
+use core::sync::atomic::{AtomicUsize, Ordering};
 use core::{
-    borrow::Borrow,
+    convert::Infallible,
     fmt,
     hash::{BuildHasher, Hash},
 };
 
-use crate::linked_hash_map::{self, LinkedHashMap};
-use crate::DefaultHashBuilder;
+use crate::linked_hash_map::{self, hash_key, LinkedHashMap, TryReserveError};
+use crate::{Allocator, DefaultHashBuilder, Equivalent, Global};
 
 pub use crate::linked_hash_map::{
-    Drain, Entry, IntoIter, Iter, IterMut, OccupiedEntry, RawEntryBuilder, RawEntryBuilderMut,
-    RawOccupiedEntryMut, RawVacantEntryMut, VacantEntry,
+    Drain, Entry, EntryRef, ExtractIf, IntoIter, Iter, IterMut, OccupiedEntry, OccupiedEntryRef,
+    RawEntryBuilder, RawEntryBuilderMut, RawOccupiedEntryMut, RawVacantEntryMut, VacantEntry,
+    VacantEntryRef,
 };
 
-pub struct LruCache<K, V, S = DefaultHashBuilder> {
-    map: LinkedHashMap<K, V, S>,
+/// Receives the entries which an `LruCache` drops itself, rather than returning them.
+///
+/// The listener is called by exactly these methods:
+///
+/// * `insert`, `try_insert`, `get_or_insert_with`, `try_get_or_insert_with`,
+///   `get_or_insert_with_ref`, `entry`, `entry_ref`, `raw_entry_mut`, `set_capacity` and
+///   `Extend::extend`, for entries evicted with `EvictionCause::Capacity`.
+/// * `retain`, for entries it removes with `EvictionCause::Removed`.
+/// * `Extend::extend`, for values it replaces with `EvictionCause::Replaced`.
+/// * `clear`, for every entry with `EvictionCause::Cleared`.
+///
+/// Every other method which removes an entry or replaces a value hands it back to the caller
+/// instead, and does not call the listener.  This includes `remove`, `remove_entry`, `remove_lru`,
+/// `extract_if`, the value returned by `insert` and `try_insert`, and `drain`, whose iterator drops
+/// any entries it has not yielded without calling the listener.  Neither does dropping the cache or
+/// removing entries through the entry and raw entry APIs.
+///
+/// This is implemented for every `FnMut(K, V, EvictionCause)`, and as a no-op for `()`, which is
+/// the listener used unless another one is given.
+pub trait EvictionListener<K, V> {
+    fn on_evict(&mut self, key: K, value: V, cause: EvictionCause);
+}
+
+impl<K, V> EvictionListener<K, V> for () {
+    #[inline]
+    fn on_evict(&mut self, _key: K, _value: V, _cause: EvictionCause) {}
+}
+
+impl<K, V, F> EvictionListener<K, V> for F
+where
+    F: FnMut(K, V, EvictionCause),
+{
+    #[inline]
+    fn on_evict(&mut self, key: K, value: V, cause: EvictionCause) {
+        self(key, value, cause)
+    }
+}
+
+/// The reason an entry was passed to an `EvictionListener`.
+///
+/// See `EvictionListener` for exactly which methods call the listener with each cause.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EvictionCause {
+    /// The entry was the least recently used one while the cache was over capacity, or needed
+    /// room for a new entry.
+    Capacity,
+    /// The entry was removed by `LruCache::retain`.
+    Removed,
+    /// The value was replaced by a new value for the same key by `Extend::extend`.  The key passed
+    /// to the listener is the new key, which the cache did not keep.
+    Replaced,
+    /// The entry was removed by `LruCache::clear`.
+    Cleared,
+}
+
+pub struct LruCache<K, V, S = DefaultHashBuilder, A: Allocator = Global, L = ()> {
+    map: LinkedHashMap<K, V, S, A>,
     max_size: usize,
+    listener: L,
+    stats: Stats,
 }
 
 impl<K: Eq + Hash, V> LruCache<K, V> {
@@ -23,6 +83,8 @@ impl<K: Eq + Hash, V> LruCache<K, V> {
         LruCache {
             map: LinkedHashMap::new(),
             max_size: capacity,
+            listener: (),
+            stats: Stats::new(),
         }
     }
 
@@ -41,8 +103,98 @@ impl<K, V, S> LruCache<K, V, S> {
         LruCache {
             map: LinkedHashMap::with_hasher(hash_builder),
             max_size: capacity,
+            listener: (),
+            stats: Stats::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, A: Allocator + Clone> LruCache<K, V, DefaultHashBuilder, A> {
+    /// Creates an empty `LruCache` which allocates with the given allocator.
+    #[inline]
+    pub fn new_in(capacity: usize, alloc: A) -> Self {
+        LruCache {
+            map: LinkedHashMap::new_in(alloc),
+            max_size: capacity,
+            listener: (),
+            stats: Stats::new(),
+        }
+    }
+}
+
+impl<K, V, S, A: Allocator + Clone> LruCache<K, V, S, A> {
+    #[inline]
+    pub fn with_hasher_in(capacity: usize, hash_builder: S, alloc: A) -> Self {
+        LruCache {
+            map: LinkedHashMap::with_hasher_in(hash_builder, alloc),
+            max_size: capacity,
+            listener: (),
+            stats: Stats::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, L> LruCache<K, V, DefaultHashBuilder, Global, L> {
+    /// Creates an empty `LruCache` which passes the entries it evicts to the given listener.
+    #[inline]
+    pub fn with_listener(capacity: usize, listener: L) -> Self {
+        LruCache {
+            map: LinkedHashMap::new(),
+            max_size: capacity,
+            listener,
+            stats: Stats::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, A: Allocator + Clone, L> LruCache<K, V, DefaultHashBuilder, A, L> {
+    /// Similar to `LruCache::with_listener`, but allocates with the given allocator.
+    #[inline]
+    pub fn with_listener_in(capacity: usize, alloc: A, listener: L) -> Self {
+        LruCache {
+            map: LinkedHashMap::new_in(alloc),
+            max_size: capacity,
+            listener,
+            stats: Stats::new(),
+        }
+    }
+}
+
+impl<K, V, S, L> LruCache<K, V, S, Global, L> {
+    #[inline]
+    pub fn with_hasher_and_listener(capacity: usize, hash_builder: S, listener: L) -> Self {
+        LruCache {
+            map: LinkedHashMap::with_hasher(hash_builder),
+            max_size: capacity,
+            listener,
+            stats: Stats::new(),
+        }
+    }
+}
+
+impl<K, V, S, A: Allocator + Clone, L> LruCache<K, V, S, A, L> {
+    #[inline]
+    pub fn with_hasher_and_listener_in(
+        capacity: usize,
+        hash_builder: S,
+        alloc: A,
+        listener: L,
+    ) -> Self {
+        LruCache {
+            map: LinkedHashMap::with_hasher_in(hash_builder, alloc),
+            max_size: capacity,
+            listener,
+            stats: Stats::new(),
         }
     }
+}
+
+impl<K, V, S, A: Allocator, L> LruCache<K, V, S, A, L> {
+    /// Returns a reference to the allocator used by this cache.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.map.allocator()
+    }
 
     #[inline]
     pub fn capacity(&self) -> usize {
@@ -60,43 +212,83 @@ impl<K, V, S> LruCache<K, V, S> {
     }
 
     #[inline]
-    pub fn clear(&mut self) {
-        self.map.clear();
+    pub fn listener(&self) -> &L {
+        &self.listener
+    }
+
+    #[inline]
+    pub fn listener_mut(&mut self) -> &mut L {
+        &mut self.listener
+    }
+
+    /// Returns the counters of what this cache has done so far.
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn stats(&self) -> CacheStats {
+        self.stats.snapshot()
+    }
+
+    /// Sets every counter returned by `LruCache::stats` back to zero.
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::new();
     }
 
     #[inline]
-    pub fn iter(&self) -> Iter<K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
         self.map.iter()
     }
 
     #[inline]
-    pub fn iter_mut(&mut self) -> IterMut<K, V> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
         self.map.iter_mut()
     }
 
+    /// Removes every entry, returning them in an iterator from least to most recently used.
+    ///
+    /// Unlike `LruCache::clear`, this does not call the listener, even for entries which are
+    /// dropped because the iterator is dropped before being fully consumed.
     #[inline]
-    pub fn drain(&mut self) -> Drain<K, V> {
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
         self.map.drain()
     }
+}
+
+impl<K, V, S, A: Allocator, L: EvictionListener<K, V>> LruCache<K, V, S, A, L> {
+    /// Removes every entry, passing each one to the listener with `EvictionCause::Cleared`.
+    #[inline]
+    pub fn clear(&mut self) {
+        for (k, v) in self.map.drain() {
+            self.stats.remove();
+            self.listener.on_evict(k, v, EvictionCause::Cleared);
+        }
+    }
 
+    /// Retains only the entries for which the given function returns true, passing every other
+    /// entry to the listener with `EvictionCause::Removed`.
     #[inline]
     pub fn retain<F>(&mut self, f: F)
     where
         F: FnMut(&K, &mut V) -> bool,
     {
-        self.map.retain(f);
+        let listener = &mut self.listener;
+        let stats = &mut self.stats;
+        self.map.retain_evicting(f, |k, v| {
+            stats.remove();
+            listener.on_evict(k, v, EvictionCause::Removed)
+        });
     }
 }
 
-impl<K: Eq + Hash, V, S> LruCache<K, V, S>
+impl<K: Eq + Hash, V, S, A: Allocator, L: EvictionListener<K, V>> LruCache<K, V, S, A, L>
 where
     S: BuildHasher,
 {
     #[inline]
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.map.contains_key(key)
     }
@@ -107,21 +299,56 @@ where
     #[inline]
     pub fn insert(&mut self, k: K, v: V) -> Option<V> {
         let old_val = self.map.insert(k, v);
+        self.stats.insert(old_val.is_some());
         if self.len() > self.capacity() {
-            self.remove_lru();
+            self.evict_lru();
         }
         old_val
     }
 
+    /// Similar to `LruCache::insert`, but returns an error rather than panicking or aborting if
+    /// room for a new entry cannot be allocated.
+    ///
+    /// If an error is returned, the cache is left unchanged.
+    #[inline]
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<Option<V>, TryReserveError> {
+        let old_val = self.map.try_insert(k, v)?;
+        self.stats.insert(old_val.is_some());
+        if self.len() > self.capacity() {
+            self.evict_lru();
+        }
+        Ok(old_val)
+    }
+
+    /// Similar to `Clone::clone`, but returns an error rather than panicking or aborting if the
+    /// new cache cannot be allocated.
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, TryReserveError>
+    where
+        K: Clone,
+        V: Clone,
+        S: Clone,
+        A: Clone,
+        L: Clone,
+    {
+        Ok(LruCache {
+            map: self.map.try_clone()?,
+            max_size: self.max_size,
+            listener: self.listener.clone(),
+            stats: self.stats.clone(),
+        })
+    }
+
     /// Get the value for the given key, *without* marking the value as recently used and moving it
     /// to the back of the LRU list.
     #[inline]
     pub fn peek<Q>(&self, k: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.map.get(k)
+        let value = self.map.get(k);
+        self.stats.lookup(value.is_some());
+        value
     }
 
     /// Get the value for the given key mutably, *without* marking the value as recently used and
@@ -129,10 +356,11 @@ where
     #[inline]
     pub fn peek_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.map.get_mut(k)
+        let value = self.map.get_mut(k);
+        self.stats.lookup(value.is_some());
+        value
     }
 
     /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
@@ -140,8 +368,7 @@ where
     #[inline]
     pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.get_mut(k).map(|v| &*v)
     }
@@ -151,18 +378,114 @@ where
     #[inline]
     pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         match self.map.raw_entry_mut().from_key(k) {
             linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
+                self.stats.lookup(true);
                 occupied.to_back();
                 Some(occupied.into_mut())
             }
-            linked_hash_map::RawEntryMut::Vacant(_) => None,
+            linked_hash_map::RawEntryMut::Vacant(_) => {
+                self.stats.lookup(false);
+                None
+            }
+        }
+    }
+
+    /// Retrieve mutable references to the values for all of the given keys at once, marking each
+    /// of them as recently used in the given order.
+    ///
+    /// Returns `None` without changing the LRU order if any of the keys is missing, or if any two of
+    /// the keys refer to the same entry.
+    #[inline]
+    pub fn get_many_mut<Q, const N: usize>(&mut self, ks: [&Q; N]) -> Option<[&mut V; N]>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let stats = &self.stats;
+        unsafe {
+            self.map
+                .get_many_nodes_mut(ks, true, true, |hit| stats.lookup(hit))
         }
     }
 
+    /// Retrieve mutable references to the values for all of the given keys at once, marking each
+    /// of them as recently used in the given order, without checking that the keys refer to
+    /// distinct entries.
+    ///
+    /// Returns `None` without changing the LRU order if any of the keys is missing.
+    ///
+    /// # Safety
+    ///
+    /// No two of the given keys may refer to the same entry, otherwise the returned references
+    /// would alias.
+    #[inline]
+    pub unsafe fn get_many_unchecked_mut<Q, const N: usize>(
+        &mut self,
+        ks: [&Q; N],
+    ) -> Option<[&mut V; N]>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let stats = &self.stats;
+        self.map
+            .get_many_nodes_mut(ks, false, true, |hit| stats.lookup(hit))
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list, or insert the value returned by `f` if it is missing.
+    ///
+    /// Unlike `LruCache::entry`, this evicts least recently used entries *before* inserting a new
+    /// one, until there is room for it, so the cache holds at most `capacity` entries afterwards.
+    ///
+    /// A cache with a capacity of zero is the one exception: returning a reference to the new value
+    /// requires storing it, so such a cache holds the new entry until the next insertion or
+    /// eviction, exceeding its capacity by one.
+    #[inline]
+    pub fn get_or_insert_with<F>(&mut self, k: K, f: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self.try_get_or_insert_with(k, || Ok::<V, Infallible>(f())) {
+            Ok(v) => v,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Similar to `LruCache::get_or_insert_with`, but `f` may fail.
+    ///
+    /// If `f` returns an error, it is returned as is, and nothing is inserted or evicted.
+    #[inline]
+    pub fn try_get_or_insert_with<E, F>(&mut self, k: K, f: F) -> Result<&mut V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        let hash = hash_key(self.map.hasher(), &k);
+        if self.promote_hashed(hash, &k) {
+            return Ok(self.back_mut());
+        }
+        let v = f()?;
+        Ok(self.insert_missing_hashed(hash, k, v))
+    }
+
+    /// Similar to `LruCache::get_or_insert_with`, but takes a borrowed form of the key, and only
+    /// converts it into an owned key if a new entry is inserted.
+    #[inline]
+    pub fn get_or_insert_with_ref<'b, Q, F>(&mut self, k: &'b Q, f: F) -> &mut V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        K: From<&'b Q>,
+        F: FnOnce() -> V,
+    {
+        let hash = hash_key(self.map.hasher(), k);
+        if self.promote_hashed(hash, k) {
+            return self.back_mut();
+        }
+        let v = f();
+        self.insert_missing_hashed(hash, K::from(k), v)
+    }
+
     /// If the returned entry is vacant, it will always have room to insert a single value.  By
     /// using the entry API, you can exceed the configured capacity by 1.
     ///
@@ -170,18 +493,38 @@ where
     /// `Entry::to_back` / `Entry::to_front` you can manually control the position of this entry in
     /// the LRU list.
     #[inline]
-    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S, A> {
         if self.len() > self.capacity() {
-            self.remove_lru();
+            self.evict_lru();
         }
         self.map.entry(key)
     }
 
+    /// Similar to `LruCache::entry`, but takes a borrowed form of the key, and only converts it
+    /// into an owned key if a new entry is inserted.
+    ///
+    /// If the returned entry is vacant, it will always have room to insert a single value.  By
+    /// using the entry API, you can exceed the configured capacity by 1.
+    ///
+    /// The returned entry is not automatically moved to the back of the LRU list.  By calling
+    /// `EntryRef::to_back` / `EntryRef::to_front` you can manually control the position of this
+    /// entry in the LRU list.
+    #[inline]
+    pub fn entry_ref<'b, Q>(&mut self, key: &'b Q) -> EntryRef<'_, 'b, K, Q, V, S, A>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if self.len() > self.capacity() {
+            self.evict_lru();
+        }
+        self.map.entry_ref(key)
+    }
+
     /// The constructed raw entry is never automatically moved to the back of the LRU list.  By
     /// calling `Entry::to_back` / `Entry::to_front` you can manually control the position of this
     /// entry in the LRU list.
     #[inline]
-    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S> {
+    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S, A> {
         self.map.raw_entry()
     }
 
@@ -192,9 +535,9 @@ where
     /// calling `Entry::to_back` / `Entry::to_front` you can manually control the position of this
     /// entry in the LRU list.
     #[inline]
-    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S> {
+    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S, A> {
         if self.len() > self.capacity() {
-            self.remove_lru();
+            self.evict_lru();
         }
         self.map.raw_entry_mut()
     }
@@ -202,19 +545,21 @@ where
     #[inline]
     pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.map.remove(k)
+        self.remove_entry(k).map(|(_, v)| v)
     }
 
     #[inline]
     pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.map.remove_entry(k)
+        let entry = self.map.remove_entry(k);
+        if entry.is_some() {
+            self.stats.remove();
+        }
+        entry
     }
 
     /// Set the new cache capacity for the `LruCache`.
@@ -224,50 +569,151 @@ where
     #[inline]
     pub fn set_capacity(&mut self, capacity: usize) {
         for _ in capacity..self.len() {
-            self.remove_lru();
+            self.evict_lru();
         }
         self.max_size = capacity;
     }
 
+    /// Returns an iterator which walks the cache from least to most recently used, removing and
+    /// yielding every entry for which `pred` returns `true`.
+    ///
+    /// Visiting an entry does not mark it as recently used.  Entries are only removed as the
+    /// iterator is advanced, and if it is dropped before being fully consumed, the remaining
+    /// entries are simply kept.
+    #[inline]
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F, S, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.extract_if(pred)
+    }
+
     /// Remove the least recently used entry and return it.
     ///
     /// If the `LruCache` is empty this will return None.
     #[inline]
     pub fn remove_lru(&mut self) -> Option<(K, V)> {
-        self.map.pop_front()
+        let entry = self.map.pop_front();
+        if entry.is_some() {
+            self.stats.remove();
+        }
+        entry
+    }
+
+    // Moves the entry for the given key to the back of the LRU list and returns true, or returns
+    // false if it is missing.
+    #[inline]
+    fn promote_hashed<Q>(&mut self, hash: u64, k: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        match self.map.raw_entry_mut().from_key_hashed_nocheck(hash, k) {
+            linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
+                self.stats.lookup(true);
+                occupied.to_back();
+                true
+            }
+            linked_hash_map::RawEntryMut::Vacant(_) => {
+                self.stats.lookup(false);
+                false
+            }
+        }
+    }
+
+    // Inserts an entry for a key which is known to be missing, evicting first until there is room
+    // for it.  The entry API may have left the cache over capacity, so this may evict several.
+    #[inline]
+    fn insert_missing_hashed(&mut self, hash: u64, k: K, v: V) -> &mut V {
+        while self.len() >= self.capacity() && !self.is_empty() {
+            self.evict_lru();
+        }
+        self.stats.insert(false);
+        match self.map.raw_entry_mut().from_hash(hash, |_| false) {
+            linked_hash_map::RawEntryMut::Vacant(vacant) => {
+                vacant.insert_hashed_nocheck(hash, k, v).1
+            }
+            linked_hash_map::RawEntryMut::Occupied(_) => unreachable!(),
+        }
+    }
+
+    // Returns the value of the most recently used entry, which must exist.
+    #[inline]
+    fn back_mut(&mut self) -> &mut V {
+        // Creating the iterator and stepping it from the back are both O(1).
+        self.map.values_mut().next_back().unwrap()
+    }
+
+    /// Evict the least recently used entry, passing it to the listener.
+    #[inline]
+    fn evict_lru(&mut self) {
+        if let Some((k, v)) = self.map.pop_front() {
+            self.stats.evict();
+            self.listener.on_evict(k, v, EvictionCause::Capacity);
+        }
     }
 }
 
-impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> Clone for LruCache<K, V, S> {
+impl<K, V, S, A, L> Clone for LruCache<K, V, S, A, L>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+    A: Allocator + Clone,
+    L: Clone,
+{
     #[inline]
     fn clone(&self) -> Self {
         LruCache {
             map: self.map.clone(),
             max_size: self.max_size,
+            listener: self.listener.clone(),
+            stats: self.stats.clone(),
         }
     }
 }
 
-impl<K: Eq + Hash, V, S: BuildHasher> Extend<(K, V)> for LruCache<K, V, S> {
+impl<K, V, S, A, L> Extend<(K, V)> for LruCache<K, V, S, A, L>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+    A: Allocator,
+    L: EvictionListener<K, V>,
+{
+    /// Inserts every entry like `LruCache::insert`, passing any value which is replaced to the
+    /// listener with `EvictionCause::Replaced`.
     #[inline]
     fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
         for (k, v) in iter {
-            self.insert(k, v);
+            match self.map.raw_entry_mut().from_key(&k) {
+                linked_hash_map::RawEntryMut::Occupied(mut occupied) => {
+                    occupied.to_back();
+                    let old = occupied.replace_value(v);
+                    self.stats.insert(true);
+                    self.listener.on_evict(k, old, EvictionCause::Replaced);
+                }
+                linked_hash_map::RawEntryMut::Vacant(vacant) => {
+                    vacant.insert(k, v);
+                    self.stats.insert(false);
+                    if self.len() > self.capacity() {
+                        self.evict_lru();
+                    }
+                }
+            }
         }
     }
 }
 
-impl<K, V, S> IntoIterator for LruCache<K, V, S> {
+impl<K, V, S, A: Allocator, L> IntoIterator for LruCache<K, V, S, A, L> {
     type Item = (K, V);
-    type IntoIter = IntoIter<K, V>;
+    type IntoIter = IntoIter<K, V, A>;
 
     #[inline]
-    fn into_iter(self) -> IntoIter<K, V> {
+    fn into_iter(self) -> IntoIter<K, V, A> {
         self.map.into_iter()
     }
 }
 
-impl<'a, K, V, S> IntoIterator for &'a LruCache<K, V, S> {
+impl<'a, K, V, S, A: Allocator, L> IntoIterator for &'a LruCache<K, V, S, A, L> {
     type Item = (&'a K, &'a V);
     type IntoIter = Iter<'a, K, V>;
 
@@ -277,7 +723,7 @@ impl<'a, K, V, S> IntoIterator for &'a LruCache<K, V, S> {
     }
 }
 
-impl<'a, K, V, S> IntoIterator for &'a mut LruCache<K, V, S> {
+impl<'a, K, V, S, A: Allocator, L> IntoIterator for &'a mut LruCache<K, V, S, A, L> {
     type Item = (&'a K, &'a mut V);
     type IntoIter = IterMut<'a, K, V>;
 
@@ -287,7 +733,7 @@ impl<'a, K, V, S> IntoIterator for &'a mut LruCache<K, V, S> {
     }
 }
 
-impl<K, V, S> fmt::Debug for LruCache<K, V, S>
+impl<K, V, S, A: Allocator, L> fmt::Debug for LruCache<K, V, S, A, L>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -296,3 +742,134 @@ where
         f.debug_map().entries(self.iter().rev()).finish()
     }
 }
+
+/// Counters of what an `LruCache` has done, returned by `LruCache::stats`.
+///
+/// Entries inserted or removed through the entry, raw entry and `extract_if` APIs, and entries
+/// removed by `drain`, are not counted.
+#[cfg(feature = "stats")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CacheStats {
+    /// Lookups by `get`, `get_mut`, `peek`, `peek_mut`, the `get_many_mut` methods or the
+    /// `get_or_insert_with` methods which found an entry.  The `get_many_mut` methods count one
+    /// lookup per key.
+    pub hits: usize,
+    /// Lookups by `get`, `get_mut`, `peek`, `peek_mut`, the `get_many_mut` methods or the
+    /// `get_or_insert_with` methods which did not find an entry.
+    pub misses: usize,
+    /// New entries inserted by `insert`, `try_insert`, `Extend::extend` or the
+    /// `get_or_insert_with` methods.
+    pub inserts: usize,
+    /// Existing entries given a new value by `insert`, `try_insert` or `Extend::extend`.
+    pub updates: usize,
+    /// Entries evicted because the cache was over capacity.
+    pub evictions: usize,
+    /// Entries removed by `remove`, `remove_entry`, `remove_lru`, `retain` or `clear`.
+    pub removals: usize,
+}
+
+// Without the `stats` feature this is empty, and recording anything does nothing.
+#[cfg(not(feature = "stats"))]
+#[derive(Clone)]
+struct Stats;
+
+#[cfg(not(feature = "stats"))]
+impl Stats {
+    #[inline]
+    fn new() -> Self {
+        Stats
+    }
+
+    #[inline]
+    fn lookup(&self, _hit: bool) {}
+
+    #[inline]
+    fn insert(&mut self, _updated: bool) {}
+
+    #[inline]
+    fn evict(&mut self) {}
+
+    #[inline]
+    fn remove(&mut self) {}
+}
+
+#[cfg(feature = "stats")]
+#[derive(Default)]
+struct Stats {
+    // `peek` only has `&self`, so lookups are counted atomically to keep the cache `Sync`.
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    inserts: usize,
+    updates: usize,
+    evictions: usize,
+    removals: usize,
+}
+
+#[cfg(feature = "stats")]
+impl Stats {
+    #[inline]
+    fn new() -> Self {
+        Stats::default()
+    }
+
+    #[inline]
+    fn lookup(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        #[cfg(target_has_atomic = "ptr")]
+        counter.fetch_add(1, Ordering::Relaxed);
+        // Targets without atomic read-modify-write operations can only load and store, so lookups
+        // racing through `peek` on several threads may be counted once.
+        #[cfg(not(target_has_atomic = "ptr"))]
+        counter.store(
+            counter.load(Ordering::Relaxed).wrapping_add(1),
+            Ordering::Relaxed,
+        );
+    }
+
+    #[inline]
+    fn insert(&mut self, updated: bool) {
+        if updated {
+            self.updates += 1;
+        } else {
+            self.inserts += 1;
+        }
+    }
+
+    #[inline]
+    fn evict(&mut self) {
+        self.evictions += 1;
+    }
+
+    #[inline]
+    fn remove(&mut self) {
+        self.removals += 1;
+    }
+
+    #[inline]
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts,
+            updates: self.updates,
+            evictions: self.evictions,
+            removals: self.removals,
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl Clone for Stats {
+    #[inline]
+    fn clone(&self) -> Self {
+        let stats = self.snapshot();
+        Stats {
+            hits: AtomicUsize::new(stats.hits),
+            misses: AtomicUsize::new(stats.misses),
+            inserts: stats.inserts,
+            updates: stats.updates,
+            evictions: stats.evictions,
+            removals: stats.removals,
+        }
+    }
+}
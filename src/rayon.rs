@@ -0,0 +1,14 @@
+//! Rayon parallel iterator support, enabled by the `rayon` feature.
+//!
+//! The entries of these containers live in a linked list, which cannot be split up without walking
+//! it, so every parallel iterator here first walks the list once on the calling thread, gathering
+//! references to (or taking ownership of) the entries in list order.  The expensive per-entry work
+//! is then done in parallel, and all of the parallel iterators are indexed, so order-sensitive
+//! operations like `collect` into a `Vec` preserve the list order.
+//!
+//! This serial pre-pass takes time proportional to the length of the container and allocates a
+//! `Vec` of that length, before any work is handed to other threads.  Parallel iteration only pays
+//! off when the work done per entry is much more expensive than walking the list.
+
+pub mod linked_hash_map;
+pub mod linked_hash_set;
@@ -0,0 +1,371 @@
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+
+use rayon::iter::{
+    plumbing::{Consumer, ProducerCallback, UnindexedConsumer},
+    FromParallelIterator, IndexedParallelIterator, IntoParallelIterator, ParallelExtend,
+    ParallelIterator,
+};
+
+use crate::lru_cache::EvictionListener;
+use crate::{Allocator, LinkedHashMap, LruCache};
+
+// Every parallel iterator here is a thin wrapper around a `rayon::vec::IntoIter` of the entries
+// gathered in list order, so the trait impls simply forward to it.
+macro_rules! delegate_indexed {
+    ($name:ident<$($lt:lifetime,)* $($param:ident),*>, $item:ty, where $($bound:tt)*) => {
+        impl<$($lt,)* $($param),*> ParallelIterator for $name<$($lt,)* $($param),*>
+        where
+            $($bound)*
+        {
+            type Item = $item;
+
+            #[inline]
+            fn drive_unindexed<C>(self, consumer: C) -> C::Result
+            where
+                C: UnindexedConsumer<Self::Item>,
+            {
+                self.inner.drive_unindexed(consumer)
+            }
+
+            #[inline]
+            fn opt_len(&self) -> Option<usize> {
+                Some(self.inner.len())
+            }
+        }
+
+        impl<$($lt,)* $($param),*> IndexedParallelIterator for $name<$($lt,)* $($param),*>
+        where
+            $($bound)*
+        {
+            #[inline]
+            fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+                self.inner.drive(consumer)
+            }
+
+            #[inline]
+            fn len(&self) -> usize {
+                self.inner.len()
+            }
+
+            #[inline]
+            fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+                self.inner.with_producer(callback)
+            }
+        }
+    };
+}
+
+pub(crate) use delegate_indexed;
+
+/// Parallel iterator over the entries of a `LinkedHashMap` or `LruCache`, in list order.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParIter<'a, K, V> {
+    inner: rayon::vec::IntoIter<(&'a K, &'a V)>,
+}
+
+delegate_indexed!(ParIter<'a, K, V>, (&'a K, &'a V), where K: Sync, V: Sync);
+
+/// Parallel iterator over the entries of a `LinkedHashMap` or `LruCache` with mutable references
+/// to the values, in list order.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParIterMut<'a, K, V> {
+    inner: rayon::vec::IntoIter<(&'a K, &'a mut V)>,
+}
+
+delegate_indexed!(ParIterMut<'a, K, V>, (&'a K, &'a mut V), where K: Sync, V: Send);
+
+/// Parallel iterator over the keys of a `LinkedHashMap` or `LruCache`, in list order.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParKeys<'a, K> {
+    inner: rayon::vec::IntoIter<&'a K>,
+}
+
+delegate_indexed!(ParKeys<'a, K>, &'a K, where K: Sync);
+
+/// Parallel iterator over the values of a `LinkedHashMap` or `LruCache`, in list order.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParValues<'a, V> {
+    inner: rayon::vec::IntoIter<&'a V>,
+}
+
+delegate_indexed!(ParValues<'a, V>, &'a V, where V: Sync);
+
+/// Parallel iterator over mutable references to the values of a `LinkedHashMap` or `LruCache`, in
+/// list order.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParValuesMut<'a, V> {
+    inner: rayon::vec::IntoIter<&'a mut V>,
+}
+
+delegate_indexed!(ParValuesMut<'a, V>, &'a mut V, where V: Send);
+
+/// Owning parallel iterator over the entries of a `LinkedHashMap` or `LruCache`, in list order.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct IntoParIter<K, V> {
+    inner: rayon::vec::IntoIter<(K, V)>,
+}
+
+delegate_indexed!(IntoParIter<K, V>, (K, V), where K: Send, V: Send);
+
+/// Parallel draining iterator over the entries of a `LinkedHashMap` or `LruCache`, in list order.
+///
+/// The container is emptied as soon as this iterator is created.
+///
+/// The entries are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParDrain<K, V> {
+    inner: rayon::vec::IntoIter<(K, V)>,
+}
+
+delegate_indexed!(ParDrain<K, V>, (K, V), where K: Send, V: Send);
+
+impl<K, V, S, A: Allocator> LinkedHashMap<K, V, S, A> {
+    /// Returns a parallel iterator over the keys of the map, in list order.
+    #[inline]
+    pub fn par_keys(&self) -> ParKeys<'_, K>
+    where
+        K: Sync,
+    {
+        ParKeys {
+            inner: self.keys().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+
+    /// Returns a parallel iterator over the values of the map, in list order.
+    #[inline]
+    pub fn par_values(&self) -> ParValues<'_, V>
+    where
+        V: Sync,
+    {
+        ParValues {
+            inner: self.values().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+
+    /// Returns a parallel iterator over mutable references to the values of the map, in list
+    /// order.
+    #[inline]
+    pub fn par_values_mut(&mut self) -> ParValuesMut<'_, V>
+    where
+        V: Send,
+    {
+        ParValuesMut {
+            inner: self.values_mut().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+
+    /// Removes every entry from the map and returns them as a parallel iterator, in list order.
+    ///
+    /// Unlike [`LinkedHashMap::drain`], the map is emptied immediately, whether or not the returned
+    /// iterator is consumed.
+    #[inline]
+    pub fn par_drain(&mut self) -> ParDrain<K, V>
+    where
+        K: Send,
+        V: Send,
+    {
+        ParDrain {
+            inner: self.drain().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<'a, K: Sync, V: Sync, S, A: Allocator> IntoParallelIterator for &'a LinkedHashMap<K, V, S, A> {
+    type Iter = ParIter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn into_par_iter(self) -> ParIter<'a, K, V> {
+        ParIter {
+            inner: self.iter().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<'a, K: Sync, V: Send, S, A: Allocator> IntoParallelIterator
+    for &'a mut LinkedHashMap<K, V, S, A>
+{
+    type Iter = ParIterMut<'a, K, V>;
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn into_par_iter(self) -> ParIterMut<'a, K, V> {
+        ParIterMut {
+            inner: self.iter_mut().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<K: Send, V: Send, S, A: Allocator> IntoParallelIterator for LinkedHashMap<K, V, S, A> {
+    type Iter = IntoParIter<K, V>;
+    type Item = (K, V);
+
+    #[inline]
+    fn into_par_iter(self) -> IntoParIter<K, V> {
+        IntoParIter {
+            inner: self.into_iter().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<K, V, S, A> FromParallelIterator<(K, V)> for LinkedHashMap<K, V, S, A>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher + Default + Send,
+    A: Allocator + Default + Clone + Send,
+{
+    #[inline]
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let mut map = Self::with_hasher_in(S::default(), A::default());
+        map.par_extend(par_iter);
+        map
+    }
+}
+
+/// Entries are produced in parallel but inserted on the calling thread, in the order of the
+/// parallel iterator.
+impl<K, V, S, A> ParallelExtend<(K, V)> for LinkedHashMap<K, V, S, A>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher,
+    A: Allocator,
+{
+    #[inline]
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let entries: Vec<(K, V)> = par_iter.into_par_iter().collect();
+        self.reserve(entries.len());
+        self.extend(entries);
+    }
+}
+
+impl<'a, K, V, S, A> ParallelExtend<(&'a K, &'a V)> for LinkedHashMap<K, V, S, A>
+where
+    K: Eq + Hash + Copy + Sync,
+    V: Copy + Sync,
+    S: BuildHasher,
+    A: Allocator,
+{
+    #[inline]
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (&'a K, &'a V)>,
+    {
+        let entries: Vec<(&K, &V)> = par_iter.into_par_iter().collect();
+        self.reserve(entries.len());
+        self.extend(entries);
+    }
+}
+
+impl<K, V, S, A: Allocator, L> LruCache<K, V, S, A, L> {
+    /// Returns a parallel iterator over mutable references to the values of the cache, from least
+    /// to most recently used.
+    ///
+    /// Visiting an entry does not mark it as recently used.
+    #[inline]
+    pub fn par_values_mut(&mut self) -> ParValuesMut<'_, V>
+    where
+        V: Send,
+    {
+        ParValuesMut {
+            inner: self
+                .iter_mut()
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>()
+                .into_par_iter(),
+        }
+    }
+
+    /// Removes every entry from the cache and returns them as a parallel iterator, from least to
+    /// most recently used.
+    #[inline]
+    pub fn par_drain(&mut self) -> ParDrain<K, V>
+    where
+        K: Send,
+        V: Send,
+    {
+        ParDrain {
+            inner: self.drain().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<'a, K: Sync, V: Sync, S, A: Allocator, L> IntoParallelIterator
+    for &'a LruCache<K, V, S, A, L>
+{
+    type Iter = ParIter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn into_par_iter(self) -> ParIter<'a, K, V> {
+        ParIter {
+            inner: self.iter().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<'a, K: Sync, V: Send, S, A: Allocator, L> IntoParallelIterator
+    for &'a mut LruCache<K, V, S, A, L>
+{
+    type Iter = ParIterMut<'a, K, V>;
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn into_par_iter(self) -> ParIterMut<'a, K, V> {
+        ParIterMut {
+            inner: self.iter_mut().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<K: Send, V: Send, S, A: Allocator, L> IntoParallelIterator for LruCache<K, V, S, A, L> {
+    type Iter = IntoParIter<K, V>;
+    type Item = (K, V);
+
+    #[inline]
+    fn into_par_iter(self) -> IntoParIter<K, V> {
+        IntoParIter {
+            inner: self.into_iter().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+/// Entries are produced in parallel but inserted on the calling thread, in the order of the
+/// parallel iterator, evicting the least recently used entries as necessary.
+impl<K, V, S, A, L> ParallelExtend<(K, V)> for LruCache<K, V, S, A, L>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher,
+    A: Allocator,
+    L: EvictionListener<K, V>,
+{
+    #[inline]
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let entries: Vec<(K, V)> = par_iter.into_par_iter().collect();
+        self.extend(entries);
+    }
+}
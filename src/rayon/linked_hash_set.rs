@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+
+use rayon::iter::{
+    plumbing::{Consumer, ProducerCallback, UnindexedConsumer},
+    FromParallelIterator, IndexedParallelIterator, IntoParallelIterator, ParallelExtend,
+    ParallelIterator,
+};
+
+use super::linked_hash_map::delegate_indexed;
+use crate::{Allocator, LinkedHashSet};
+
+/// Parallel iterator over the values of a `LinkedHashSet`, in list order.
+///
+/// The values are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParIter<'a, T> {
+    inner: rayon::vec::IntoIter<&'a T>,
+}
+
+delegate_indexed!(ParIter<'a, T>, &'a T, where T: Sync);
+
+/// Owning parallel iterator over the values of a `LinkedHashSet`, in list order.
+///
+/// The values are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct IntoParIter<T> {
+    inner: rayon::vec::IntoIter<T>,
+}
+
+delegate_indexed!(IntoParIter<T>, T, where T: Send);
+
+/// Parallel draining iterator over the values of a `LinkedHashSet`, in list order.
+///
+/// The set is emptied as soon as this iterator is created.
+///
+/// The values are gathered on the calling thread first, see the [module docs](crate::rayon).
+#[derive(Debug)]
+pub struct ParDrain<T> {
+    inner: rayon::vec::IntoIter<T>,
+}
+
+delegate_indexed!(ParDrain<T>, T, where T: Send);
+
+impl<T, S, A: Allocator> LinkedHashSet<T, S, A> {
+    /// Removes every value from the set and returns them as a parallel iterator, in list order.
+    ///
+    /// Unlike [`LinkedHashSet::drain`], the set is emptied immediately, whether or not the returned
+    /// iterator is consumed.
+    #[inline]
+    pub fn par_drain(&mut self) -> ParDrain<T>
+    where
+        T: Send,
+    {
+        ParDrain {
+            inner: self.drain().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<'a, T: Sync, S, A: Allocator> IntoParallelIterator for &'a LinkedHashSet<T, S, A> {
+    type Iter = ParIter<'a, T>;
+    type Item = &'a T;
+
+    #[inline]
+    fn into_par_iter(self) -> ParIter<'a, T> {
+        ParIter {
+            inner: self.iter().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<T: Send, S, A: Allocator> IntoParallelIterator for LinkedHashSet<T, S, A> {
+    type Iter = IntoParIter<T>;
+    type Item = T;
+
+    #[inline]
+    fn into_par_iter(self) -> IntoParIter<T> {
+        IntoParIter {
+            inner: self.into_iter().collect::<Vec<_>>().into_par_iter(),
+        }
+    }
+}
+
+impl<T, S, A> FromParallelIterator<T> for LinkedHashSet<T, S, A>
+where
+    T: Eq + Hash + Send,
+    S: BuildHasher + Default + Send,
+    A: Allocator + Default + Clone + Send,
+{
+    #[inline]
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let mut set = Self::with_hasher_in(S::default(), A::default());
+        set.par_extend(par_iter);
+        set
+    }
+}
+
+/// Values are produced in parallel but inserted on the calling thread, in the order of the
+/// parallel iterator.
+impl<T, S, A> ParallelExtend<T> for LinkedHashSet<T, S, A>
+where
+    T: Eq + Hash + Send,
+    S: BuildHasher,
+    A: Allocator,
+{
+    #[inline]
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let values: Vec<T> = par_iter.into_par_iter().collect();
+        self.reserve(values.len());
+        self.extend(values);
+    }
+}
+
+impl<'a, T, S, A> ParallelExtend<&'a T> for LinkedHashSet<T, S, A>
+where
+    T: Eq + Hash + Copy + Sync,
+    S: BuildHasher,
+    A: Allocator,
+{
+    #[inline]
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = &'a T>,
+    {
+        let values: Vec<&T> = par_iter.into_par_iter().collect();
+        self.reserve(values.len());
+        self.extend(values);
+    }
+}
@@ -10,15 +10,16 @@ use serde::{
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
-use crate::{LinkedHashMap, LinkedHashSet};
+use crate::{Allocator, LinkedHashMap, LinkedHashSet};
 
 // LinkedHashMap impls
 
-impl<K, V, S> Serialize for LinkedHashMap<K, V, S>
+impl<K, V, S, A> Serialize for LinkedHashMap<K, V, S, A>
 where
     K: Serialize + Eq + Hash,
     V: Serialize,
     S: BuildHasher,
+    A: Allocator,
 {
     #[inline]
     fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
@@ -31,19 +32,20 @@ where
     }
 }
 
-impl<'de, K, V, S> Deserialize<'de> for LinkedHashMap<K, V, S>
+impl<'de, K, V, S, A> Deserialize<'de> for LinkedHashMap<K, V, S, A>
 where
     K: Deserialize<'de> + Eq + Hash,
     V: Deserialize<'de>,
     S: BuildHasher + Default,
+    A: Allocator + Default + Clone,
 {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         #[derive(Debug)]
-        pub struct LinkedHashMapVisitor<K, V, S> {
-            marker: PhantomData<LinkedHashMap<K, V, S>>,
+        pub struct LinkedHashMapVisitor<K, V, S, A: Allocator> {
+            marker: PhantomData<LinkedHashMap<K, V, S, A>>,
         }
 
-        impl<K, V, S> LinkedHashMapVisitor<K, V, S> {
+        impl<K, V, S, A: Allocator> LinkedHashMapVisitor<K, V, S, A> {
             fn new() -> Self {
                 LinkedHashMapVisitor {
                     marker: PhantomData,
@@ -51,19 +53,20 @@ where
             }
         }
 
-        impl<K, V, S> Default for LinkedHashMapVisitor<K, V, S> {
+        impl<K, V, S, A: Allocator> Default for LinkedHashMapVisitor<K, V, S, A> {
             fn default() -> Self {
                 Self::new()
             }
         }
 
-        impl<'de, K, V, S> Visitor<'de> for LinkedHashMapVisitor<K, V, S>
+        impl<'de, K, V, S, A> Visitor<'de> for LinkedHashMapVisitor<K, V, S, A>
         where
             K: Deserialize<'de> + Eq + Hash,
             V: Deserialize<'de>,
             S: BuildHasher + Default,
+            A: Allocator + Default + Clone,
         {
-            type Value = LinkedHashMap<K, V, S>;
+            type Value = LinkedHashMap<K, V, S, A>;
 
             fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
                 write!(formatter, "a map")
@@ -71,9 +74,10 @@ where
 
             #[inline]
             fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
-                let mut values = LinkedHashMap::with_capacity_and_hasher(
+                let mut values = LinkedHashMap::with_capacity_and_hasher_in(
                     map.size_hint().unwrap_or(0),
                     S::default(),
+                    A::default(),
                 );
 
                 while let Some((k, v)) = map.next_entry()? {
@@ -90,10 +94,11 @@ where
 
 // LinkedHashSet impls
 
-impl<T, S> Serialize for LinkedHashSet<T, S>
+impl<T, S, A> Serialize for LinkedHashSet<T, S, A>
 where
     T: Serialize + Eq + Hash,
     S: BuildHasher,
+    A: Allocator,
 {
     #[inline]
     fn serialize<U: Serializer>(&self, serializer: U) -> Result<U::Ok, U::Error> {
@@ -105,18 +110,19 @@ where
     }
 }
 
-impl<'de, T, S> Deserialize<'de> for LinkedHashSet<T, S>
+impl<'de, T, S, A> Deserialize<'de> for LinkedHashSet<T, S, A>
 where
     T: Deserialize<'de> + Eq + Hash,
     S: BuildHasher + Default,
+    A: Allocator + Default + Clone,
 {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         #[derive(Debug)]
-        pub struct LinkedHashSetVisitor<T, S> {
-            marker: PhantomData<LinkedHashSet<T, S>>,
+        pub struct LinkedHashSetVisitor<T, S, A: Allocator> {
+            marker: PhantomData<LinkedHashSet<T, S, A>>,
         }
 
-        impl<T, S> LinkedHashSetVisitor<T, S> {
+        impl<T, S, A: Allocator> LinkedHashSetVisitor<T, S, A> {
             fn new() -> Self {
                 LinkedHashSetVisitor {
                     marker: PhantomData,
@@ -124,18 +130,19 @@ where
             }
         }
 
-        impl<T, S> Default for LinkedHashSetVisitor<T, S> {
+        impl<T, S, A: Allocator> Default for LinkedHashSetVisitor<T, S, A> {
             fn default() -> Self {
                 Self::new()
             }
         }
 
-        impl<'de, T, S> Visitor<'de> for LinkedHashSetVisitor<T, S>
+        impl<'de, T, S, A> Visitor<'de> for LinkedHashSetVisitor<T, S, A>
         where
             T: Deserialize<'de> + Eq + Hash,
             S: BuildHasher + Default,
+            A: Allocator + Default + Clone,
         {
-            type Value = LinkedHashSet<T, S>;
+            type Value = LinkedHashSet<T, S, A>;
 
             fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
                 write!(formatter, "a sequence")
@@ -143,9 +150,10 @@ where
 
             #[inline]
             fn visit_seq<SA: SeqAccess<'de>>(self, mut seq: SA) -> Result<Self::Value, SA::Error> {
-                let mut values = LinkedHashSet::with_capacity_and_hasher(
+                let mut values = LinkedHashSet::with_capacity_and_hasher_in(
                     seq.size_hint().unwrap_or(0),
                     S::default(),
+                    A::default(),
                 );
 
                 while let Some(v) = seq.next_element()? {
@@ -0,0 +1,2012 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    iter::FromIterator,
+    mem::{self, MaybeUninit},
+    ops::{Index, IndexMut},
+    ptr, slice,
+};
+
+use crate::linked_hash_map::{self, LinkedHashMap, RawEntryMut, TryReserveError};
+use crate::{DefaultHashBuilder, Equivalent};
+
+/// A `LinkedHashMap` which stores up to `N` entries inline, without allocating.
+///
+/// While the map holds at most `N` entries, they are kept in list order in a fixed size array and
+/// found by linear scan, which for small `N` is usually cheaper than hashing the key.  Inserting a
+/// new entry into a full array moves every entry into an internal `LinkedHashMap`, which is then
+/// used until `SmallLinkedHashMap::shrink_to_fit` finds that the entries fit inline again.
+///
+/// Other than when it allocates, a `SmallLinkedHashMap` orders its entries exactly like a
+/// `LinkedHashMap`, including how `insert` and the entry API move existing entries to the back of
+/// the list.  It only offers part of the `LinkedHashMap` API though, and is missing among others:
+///
+/// * the raw entry API,
+/// * cursors and range iteration such as `iter_from`,
+/// * `get_many_mut`, `append` and `split_off`,
+/// * `try_entry`, `try_extend` and `try_clone`,
+/// * an allocator parameter.
+///
+/// `SmallLinkedHashMap::into_map` converts it into a `LinkedHashMap` when these are needed.
+pub struct SmallLinkedHashMap<K, V, const N: usize, S = DefaultHashBuilder> {
+    // At most one of `inline` and `map` is non-empty at any time.  While `map` is empty, entries
+    // are inserted into `inline` until it is full.
+    inline: InlineVec<(K, V), N>,
+    map: LinkedHashMap<K, V, S>,
+}
+
+impl<K, V, const N: usize> SmallLinkedHashMap<K, V, N> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, const N: usize, S> SmallLinkedHashMap<K, V, N, S> {
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            inline: InlineVec::new(),
+            map: LinkedHashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty map with room for at least `capacity` entries.
+    ///
+    /// Nothing is allocated unless `capacity` is greater than `N`.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let map = if capacity > N {
+            LinkedHashMap::with_capacity_and_hasher(capacity, hash_builder)
+        } else {
+            LinkedHashMap::with_hasher(hash_builder)
+        };
+        Self {
+            inline: InlineVec::new(),
+            map,
+        }
+    }
+
+    /// Returns whether the entries of this map are currently stored inline.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inline.len() + self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.map.capacity().max(N)
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Removes every entry from the map.
+    ///
+    /// Storage allocated for the spilled representation is kept, use
+    /// `SmallLinkedHashMap::shrink_to_fit` to release it.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inline.clear();
+        self.map.clear();
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inline: self.inline.as_slice().iter(),
+            map: self.map.iter(),
+        }
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inline: self.inline.as_mut_slice().iter_mut(),
+            map: self.map.iter_mut(),
+        }
+    }
+
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    #[inline]
+    pub fn front(&self) -> Option<(&K, &V)> {
+        match self.inline.as_slice().first() {
+            Some((key, value)) => Some((key, value)),
+            None => self.map.front(),
+        }
+    }
+
+    #[inline]
+    pub fn back(&self) -> Option<(&K, &V)> {
+        match self.inline.as_slice().last() {
+            Some((key, value)) => Some((key, value)),
+            None => self.map.back(),
+        }
+    }
+
+    /// Removes every entry from the map, returning them in list order.
+    ///
+    /// Storage allocated for the spilled representation is kept, as with
+    /// `SmallLinkedHashMap::clear`.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V, N> {
+        Drain {
+            inline: mem::replace(&mut self.inline, InlineVec::new()).into_iter(),
+            map: self.map.drain(),
+        }
+    }
+
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.inline.retain(|(key, value)| f(key, value));
+        self.map.retain(f);
+    }
+
+    /// Sorts the entries of the map with the given comparison function.
+    ///
+    /// This sort is stable.  Entries stored inline are sorted in place by insertion sort, which
+    /// does not allocate.
+    #[inline]
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        insertion_sort_by(self.inline.as_mut_slice(), |(ak, av), (bk, bv)| {
+            cmp(ak, av, bk, bv) == Ordering::Less
+        });
+        self.map.sort_by(cmp);
+    }
+
+    #[inline]
+    pub fn sort_by_key<T, F>(&mut self, mut f: F)
+    where
+        T: Ord,
+        F: FnMut(&K, &V) -> T,
+    {
+        insertion_sort_by(self.inline.as_mut_slice(), |(ak, av), (bk, bv)| {
+            f(ak, av) < f(bk, bv)
+        });
+        self.map.sort_by_key(f);
+    }
+
+    /// Similar to `SmallLinkedHashMap::sort_by_key`, but calls `f` only once per entry.
+    #[inline]
+    pub fn sort_by_cached_key<T, F>(&mut self, mut f: F)
+    where
+        T: Ord,
+        F: FnMut(&K, &V) -> T,
+    {
+        if self.inline.len() > 1 {
+            let mut keyed = InlineVec::<(T, (K, V)), N>::new();
+            for (k, v) in mem::replace(&mut self.inline, InlineVec::new()) {
+                keyed.push((f(&k, &v), (k, v)));
+            }
+            insertion_sort_by(keyed.as_mut_slice(), |(a, _), (b, _)| a < b);
+            for (_, entry) in keyed {
+                self.inline.push(entry);
+            }
+        }
+        self.map.sort_by_cached_key(f);
+    }
+
+    #[inline]
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| k1.cmp(k2));
+    }
+
+    /// Sorts the entries of the map with the given comparison function, but might not preserve
+    /// the order of equal entries.
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.inline
+            .as_mut_slice()
+            .sort_unstable_by(|(ak, av), (bk, bv)| cmp(ak, av, bk, bv));
+        self.map.sort_unstable_by(cmp);
+    }
+
+    /// Reverses the order of the entries in the map.
+    #[inline]
+    pub fn reverse(&mut self) {
+        self.inline.as_mut_slice().reverse();
+        self.map.reverse();
+    }
+}
+
+impl<K, V, const N: usize, S> SmallLinkedHashMap<K, V, N, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, N, S> {
+        if !self.map.is_empty() {
+            let hash = linked_hash_map::hash_key(self.map.hasher(), &key);
+            return match self.map.raw_entry_mut().from_key_hashed_nocheck(hash, &key) {
+                RawEntryMut::Occupied(entry) => Entry::Occupied(OccupiedEntry {
+                    inner: OccupiedInner::Map(entry),
+                }),
+                RawEntryMut::Vacant(entry) => Entry::Vacant(VacantEntry {
+                    inner: VacantInner::Map { entry, hash, key },
+                }),
+            };
+        }
+
+        match self.inline_position(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry {
+                inner: OccupiedInner::Inline {
+                    entries: &mut self.inline,
+                    index,
+                },
+            }),
+            None => Entry::Vacant(VacantEntry {
+                inner: VacantInner::Inline { map: self, key },
+            }),
+        }
+    }
+
+    /// Similar to `SmallLinkedHashMap::entry`, but takes a borrowed key, which is only converted
+    /// into an owned key if a new entry is inserted.
+    #[inline]
+    pub fn entry_ref<'b, Q>(&mut self, key: &'b Q) -> EntryRef<'_, 'b, K, Q, V, N, S>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if !self.map.is_empty() {
+            let hash = linked_hash_map::hash_key(self.map.hasher(), key);
+            return match self.map.raw_entry_mut().from_key_hashed_nocheck(hash, key) {
+                RawEntryMut::Occupied(entry) => EntryRef::Occupied(OccupiedEntryRef {
+                    key,
+                    entry: OccupiedEntry {
+                        inner: OccupiedInner::Map(entry),
+                    },
+                }),
+                RawEntryMut::Vacant(entry) => EntryRef::Vacant(VacantEntryRef {
+                    inner: VacantRefInner::Map { entry, hash, key },
+                }),
+            };
+        }
+
+        match self.inline_position(key) {
+            Some(index) => EntryRef::Occupied(OccupiedEntryRef {
+                key,
+                entry: OccupiedEntry {
+                    inner: OccupiedInner::Inline {
+                        entries: &mut self.inline,
+                        index,
+                    },
+                },
+            }),
+            None => EntryRef::Vacant(VacantEntryRef {
+                inner: VacantRefInner::Inline { map: self, key },
+            }),
+        }
+    }
+
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if self.map.is_empty() {
+            self.inline
+                .as_slice()
+                .iter()
+                .find(|(key, _)| k.equivalent(key))
+                .map(|(key, value)| (key, value))
+        } else {
+            self.map.get_key_value(k)
+        }
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_key_value(k).is_some()
+    }
+
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if self.map.is_empty() {
+            self.inline
+                .as_mut_slice()
+                .iter_mut()
+                .find(|(key, _)| k.equivalent(key))
+                .map(|(_, value)| value)
+        } else {
+            self.map.get_mut(k)
+        }
+    }
+
+    /// Inserts the given key / value pair at the *back* of the list.
+    ///
+    /// Returns the previously set value, if one existed prior to this call, in which case the
+    /// existing entry is moved to the back.  If the entries are stored inline and there is no room
+    /// left for a new one, this moves every entry out of line first.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if !self.map.is_empty() {
+            return self.map.insert(k, v);
+        }
+
+        match self.inline_position(&k) {
+            Some(index) => {
+                let entries = &mut self.inline.as_mut_slice()[index..];
+                entries.rotate_left(1);
+                let last = entries.len() - 1;
+                Some(mem::replace(&mut entries[last].1, v))
+            }
+            None => {
+                self.insert_new(k, v);
+                None
+            }
+        }
+    }
+
+    /// Similar to `SmallLinkedHashMap::insert`, but returns an error rather than panicking or
+    /// aborting if the entries need to be moved out of line and room for them cannot be allocated.
+    #[inline]
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<Option<V>, TryReserveError> {
+        if !self.map.is_empty() {
+            return self.map.try_insert(k, v);
+        }
+
+        if self.inline.len() == N && self.inline_position(&k).is_none() {
+            // Reserve room for the spilled entries first, so that `insert` does not allocate.
+            self.map.try_reserve(N + 1)?;
+        }
+        Ok(self.insert(k, v))
+    }
+
+    /// If the given key is not in this map, inserts the key / value pair at the *back* of the list
+    /// and returns `None`, otherwise, replaces the existing value with the given value *without*
+    /// moving the entry and returns the previous value.
+    #[inline]
+    pub fn replace(&mut self, k: K, v: V) -> Option<V> {
+        if !self.map.is_empty() {
+            return self.map.replace(k, v);
+        }
+
+        match self.inline_position(&k) {
+            Some(index) => Some(mem::replace(&mut self.inline.as_mut_slice()[index].1, v)),
+            None => {
+                self.insert_new(k, v);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if self.map.is_empty() {
+            let index = self.inline_position(k)?;
+            Some(self.inline.remove(index))
+        } else {
+            self.map.remove_entry(k)
+        }
+    }
+
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        if self.inline.len() > 0 {
+            Some(self.inline.remove(0))
+        } else {
+            self.map.pop_front()
+        }
+    }
+
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<(K, V)> {
+        match self.inline.pop() {
+            Some(entry) => Some(entry),
+            None => self.map.pop_back(),
+        }
+    }
+
+    /// If an entry with this key exists, move it to the front of the list and return a reference to
+    /// the value.
+    #[inline]
+    pub fn to_front<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if self.map.is_empty() {
+            let index = self.inline_position(k)?;
+            let entries = &mut self.inline.as_mut_slice()[..=index];
+            entries.rotate_right(1);
+            Some(&mut entries[0].1)
+        } else {
+            self.map.to_front(k)
+        }
+    }
+
+    /// If an entry with this key exists, move it to the back of the list and return a reference to
+    /// the value.
+    #[inline]
+    pub fn to_back<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if self.map.is_empty() {
+            let index = self.inline_position(k)?;
+            let entries = &mut self.inline.as_mut_slice()[index..];
+            entries.rotate_left(1);
+            let last = entries.len() - 1;
+            Some(&mut entries[last].1)
+        } else {
+            self.map.to_back(k)
+        }
+    }
+
+    /// If entries with both keys exist, move the entry for `k` so that it is immediately before
+    /// the entry for `anchor` in the list and return a reference to its value.
+    #[inline]
+    pub fn move_before<Q>(&mut self, k: &Q, anchor: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if !self.map.is_empty() {
+            return self.map.move_before(k, anchor);
+        }
+
+        let index = self.inline_position(k)?;
+        let anchor = self.inline_position(anchor)?;
+        let entries = self.inline.as_mut_slice();
+        let index = match index.cmp(&anchor) {
+            Ordering::Less => {
+                entries[index..anchor].rotate_left(1);
+                anchor - 1
+            }
+            Ordering::Greater => {
+                entries[anchor..=index].rotate_right(1);
+                anchor
+            }
+            Ordering::Equal => index,
+        };
+        Some(&mut entries[index].1)
+    }
+
+    /// If entries with both keys exist, move the entry for `k` so that it is immediately after
+    /// the entry for `anchor` in the list and return a reference to its value.
+    #[inline]
+    pub fn move_after<Q>(&mut self, k: &Q, anchor: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        if !self.map.is_empty() {
+            return self.map.move_after(k, anchor);
+        }
+
+        let index = self.inline_position(k)?;
+        let anchor = self.inline_position(anchor)?;
+        let entries = self.inline.as_mut_slice();
+        let index = match index.cmp(&anchor) {
+            Ordering::Less => {
+                entries[index..=anchor].rotate_left(1);
+                anchor
+            }
+            Ordering::Greater => {
+                entries[anchor + 1..=index].rotate_right(1);
+                anchor + 1
+            }
+            Ordering::Equal => index,
+        };
+        Some(&mut entries[index].1)
+    }
+
+    /// Returns an iterator which walks the map in list order, removing and yielding every entry for
+    /// which `pred` returns `true`.
+    ///
+    /// Entries are only removed as the iterator is advanced.  If it is dropped before being fully
+    /// consumed, the remaining entries are simply kept.
+    #[inline]
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F, N, S>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let inner = if self.map.is_empty() {
+            ExtractIfInner::Inline {
+                entries: &mut self.inline,
+                index: 0,
+                pred,
+            }
+        } else {
+            ExtractIfInner::Map(self.map.extract_if(pred))
+        };
+        ExtractIf { inner }
+    }
+
+    /// Similar to `SmallLinkedHashMap::retain`, but visits the entries in list order.
+    #[inline]
+    pub fn retain_with_order<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.inline.retain(|(key, value)| f(key, value));
+        self.map.retain_with_order(f);
+    }
+
+    /// Reserves room for at least `additional` more entries.
+    ///
+    /// This only allocates if the entries would no longer fit inline, and does not by itself move
+    /// the entries out of line.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        if self.map.is_empty() {
+            let required = self.inline.len().saturating_add(additional);
+            if required > N {
+                self.map.reserve(required);
+            }
+        } else {
+            self.map.reserve(additional);
+        }
+    }
+
+    /// Similar to `SmallLinkedHashMap::reserve`, but returns an error rather than panicking or
+    /// aborting if the room cannot be allocated.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if self.map.is_empty() {
+            let required = self.inline.len().saturating_add(additional);
+            if required > N {
+                self.map.try_reserve(required)?;
+            }
+            Ok(())
+        } else {
+            self.map.try_reserve(additional)
+        }
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    ///
+    /// If the map has been spilled out of line but its entries now fit within `N`, they are moved
+    /// back inline and the out of line storage is released.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        if self.map.len() <= N {
+            while let Some((k, v)) = self.map.pop_front() {
+                self.inline.push((k, v));
+            }
+        }
+        self.map.shrink_to_fit();
+    }
+
+    /// Converts this map into a `LinkedHashMap`, preserving the order of its entries.
+    #[inline]
+    pub fn into_map(mut self) -> LinkedHashMap<K, V, S> {
+        if self.map.is_empty() {
+            self.spill(0);
+        }
+        self.map
+    }
+
+    #[inline]
+    fn inline_position<Q>(&self, k: &Q) -> Option<usize>
+    where
+        Q: Equivalent<K> + ?Sized,
+    {
+        self.inline
+            .as_slice()
+            .iter()
+            .position(|(key, _)| k.equivalent(key))
+    }
+
+    // Moves every inline entry into `map`, reserving room for `additional` more entries.  `map`
+    // must be empty.
+    fn spill(&mut self, additional: usize) {
+        debug_assert!(self.map.is_empty());
+        self.map
+            .reserve(self.inline.len().saturating_add(additional));
+        for (k, v) in mem::replace(&mut self.inline, InlineVec::new()) {
+            self.insert_missing(k, v);
+        }
+    }
+
+    // Inserts an entry with a key that is not yet present while the entries are stored inline,
+    // spilling them out of line if there is no room left.
+    fn insert_new(&mut self, k: K, v: V) -> &mut V {
+        if self.inline.len() < N {
+            return &mut self.inline.push((k, v)).1;
+        }
+        self.spill(1);
+        self.insert_missing(k, v)
+    }
+
+    // Inserts an entry into `map` with a key which is known to be missing, hashing it once and
+    // without comparing it against any other key.
+    fn insert_missing(&mut self, k: K, v: V) -> &mut V {
+        let hash = linked_hash_map::hash_key(self.map.hasher(), &k);
+        match self.map.raw_entry_mut().from_hash(hash, |_| false) {
+            RawEntryMut::Vacant(vacant) => vacant.insert_hashed_nocheck(hash, k, v).1,
+            RawEntryMut::Occupied(_) => unreachable!(),
+        }
+    }
+}
+
+impl<K, V, const N: usize, S> From<LinkedHashMap<K, V, S>> for SmallLinkedHashMap<K, V, N, S> {
+    /// Wraps an existing `LinkedHashMap`, its entries stay out of line until
+    /// `SmallLinkedHashMap::shrink_to_fit` is called.
+    #[inline]
+    fn from(map: LinkedHashMap<K, V, S>) -> Self {
+        Self {
+            inline: InlineVec::new(),
+            map,
+        }
+    }
+}
+
+impl<K, V, const N: usize, S: Default> Default for SmallLinkedHashMap<K, V, N, S> {
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, const N: usize, S> FromIterator<(K, V)> for SmallLinkedHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut map = Self::default();
+        map.reserve(iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, const N: usize, S> fmt::Debug for SmallLinkedHashMap<K, V, N, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, const N: usize, S: BuildHasher> PartialEq
+    for SmallLinkedHashMap<K, V, N, S>
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, const N: usize, S: BuildHasher> Eq for SmallLinkedHashMap<K, V, N, S> {}
+
+impl<K: Hash + Eq, V: Hash, const N: usize, S: BuildHasher> Hash
+    for SmallLinkedHashMap<K, V, N, S>
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, h: &mut H) {
+        for e in self.iter() {
+            e.hash(h);
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize, S, Q> Index<&'a Q> for SmallLinkedHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: Hash + Equivalent<K> + ?Sized,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, index: &'a Q) -> &V {
+        self.get(index).expect("no entry found for key")
+    }
+}
+
+impl<'a, K, V, const N: usize, S, Q> IndexMut<&'a Q> for SmallLinkedHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: Hash + Equivalent<K> + ?Sized,
+{
+    #[inline]
+    fn index_mut(&mut self, index: &'a Q) -> &mut V {
+        self.get_mut(index).expect("no entry found for key")
+    }
+}
+
+impl<K, V, const N: usize, S> Clone for SmallLinkedHashMap<K, V, N, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inline: self.inline.clone(),
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, const N: usize, S: BuildHasher> Extend<(K, V)>
+    for SmallLinkedHashMap<K, V, N, S>
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize, S> Extend<(&'a K, &'a V)> for SmallLinkedHashMap<K, V, N, S>
+where
+    K: 'a + Hash + Eq + Copy,
+    V: 'a + Copy,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        for (&k, &v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+pub enum Entry<'a, K, V, const N: usize, S> {
+    Occupied(OccupiedEntry<'a, K, V, N, S>),
+    Vacant(VacantEntry<'a, K, V, N, S>),
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize, S> fmt::Debug for Entry<'_, K, V, N, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Entry::Vacant(ref v) => f.debug_tuple("Entry").field(v).finish(),
+            Entry::Occupied(ref o) => f.debug_tuple("Entry").field(o).finish(),
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize, S> Entry<'a, K, V, N, S> {
+    /// If this entry is vacant, inserts a new entry with the given value and returns a reference to
+    /// it.
+    ///
+    /// If this entry is occupied, this method *moves the occupied entry to the back of the list*
+    /// and returns a reference to the existing value.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        self.or_insert_with(|| default)
+    }
+
+    /// Similar to `Entry::or_insert`, but accepts a function to construct a new value if this entry
+    /// is vacant.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Similar to `Entry::or_insert_with`, but the function is given a reference to the key that
+    /// will be inserted.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Similar to `Entry::or_insert`, but inserts the default value if this entry is vacant.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        K: Eq + Hash,
+        V: Default,
+        S: BuildHasher,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// If this entry is occupied, moves it to the back of the list.  Does nothing if this entry is
+    /// vacant.
+    #[inline]
+    pub fn to_back(&mut self) {
+        if let Entry::Occupied(entry) = self {
+            entry.to_back();
+        }
+    }
+
+    /// If this entry is occupied, moves it to the front of the list.  Does nothing if this entry
+    /// is vacant.
+    #[inline]
+    pub fn to_front(&mut self) {
+        if let Entry::Occupied(entry) = self {
+            entry.to_front();
+        }
+    }
+
+    #[inline]
+    pub fn key(&self) -> &K {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    #[inline]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+pub struct OccupiedEntry<'a, K, V, const N: usize, S> {
+    inner: OccupiedInner<'a, K, V, N, S>,
+}
+
+enum OccupiedInner<'a, K, V, const N: usize, S> {
+    Inline {
+        entries: &'a mut InlineVec<(K, V), N>,
+        index: usize,
+    },
+    Map(linked_hash_map::RawOccupiedEntryMut<'a, K, V, S>),
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize, S> fmt::Debug for OccupiedEntry<'_, K, V, N, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> OccupiedEntry<'a, K, V, N, S> {
+    #[inline]
+    pub fn key(&self) -> &K {
+        match self.inner {
+            OccupiedInner::Inline { ref entries, index } => &entries.as_slice()[index].0,
+            OccupiedInner::Map(ref entry) => entry.key(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> &V {
+        match self.inner {
+            OccupiedInner::Inline { ref entries, index } => &entries.as_slice()[index].1,
+            OccupiedInner::Map(ref entry) => entry.get(),
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        match self.inner {
+            OccupiedInner::Inline {
+                ref mut entries,
+                index,
+            } => &mut entries.as_mut_slice()[index].1,
+            OccupiedInner::Map(ref mut entry) => entry.get_mut(),
+        }
+    }
+
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        match self.inner {
+            OccupiedInner::Inline { entries, index } => &mut entries.as_mut_slice()[index].1,
+            OccupiedInner::Map(entry) => entry.into_mut(),
+        }
+    }
+
+    #[inline]
+    pub fn to_back(&mut self) {
+        match self.inner {
+            OccupiedInner::Inline {
+                ref mut entries,
+                ref mut index,
+            } => {
+                entries.as_mut_slice()[*index..].rotate_left(1);
+                *index = entries.len() - 1;
+            }
+            OccupiedInner::Map(ref mut entry) => entry.to_back(),
+        }
+    }
+
+    #[inline]
+    pub fn to_front(&mut self) {
+        match self.inner {
+            OccupiedInner::Inline {
+                ref mut entries,
+                ref mut index,
+            } => {
+                entries.as_mut_slice()[..=*index].rotate_right(1);
+                *index = 0;
+            }
+            OccupiedInner::Map(ref mut entry) => entry.to_front(),
+        }
+    }
+
+    /// Replaces this entry's value with the provided value, returning the old value.
+    ///
+    /// The entry is *not* moved.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    #[inline]
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        match self.inner {
+            OccupiedInner::Inline { entries, index } => entries.remove(index),
+            OccupiedInner::Map(entry) => entry.remove_entry(),
+        }
+    }
+}
+
+pub struct VacantEntry<'a, K, V, const N: usize, S> {
+    inner: VacantInner<'a, K, V, N, S>,
+}
+
+enum VacantInner<'a, K, V, const N: usize, S> {
+    Inline {
+        map: &'a mut SmallLinkedHashMap<K, V, N, S>,
+        key: K,
+    },
+    Map {
+        entry: linked_hash_map::RawVacantEntryMut<'a, K, V, S>,
+        hash: u64,
+        key: K,
+    },
+}
+
+impl<K: fmt::Debug, V, const N: usize, S> fmt::Debug for VacantEntry<'_, K, V, N, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> VacantEntry<'a, K, V, N, S> {
+    #[inline]
+    pub fn key(&self) -> &K {
+        match self.inner {
+            VacantInner::Inline { ref key, .. } | VacantInner::Map { ref key, .. } => key,
+        }
+    }
+
+    #[inline]
+    pub fn into_key(self) -> K {
+        match self.inner {
+            VacantInner::Inline { key, .. } | VacantInner::Map { key, .. } => key,
+        }
+    }
+
+    /// Inserts the key for this vacant entry paired with the given value as a new entry at the
+    /// *back* of the list.
+    ///
+    /// If the entries are stored inline and there is no room left for a new one, this moves every
+    /// entry out of line first.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        match self.inner {
+            VacantInner::Inline { map, key } => map.insert_new(key, value),
+            VacantInner::Map { entry, hash, key } => {
+                entry.insert_hashed_nocheck(hash, key, value).1
+            }
+        }
+    }
+}
+
+pub enum EntryRef<'a, 'b, K, Q: ?Sized, V, const N: usize, S> {
+    Occupied(OccupiedEntryRef<'a, 'b, K, Q, V, N, S>),
+    Vacant(VacantEntryRef<'a, 'b, K, Q, V, N, S>),
+}
+
+impl<K, Q, V, const N: usize, S> fmt::Debug for EntryRef<'_, '_, K, Q, V, N, S>
+where
+    K: fmt::Debug,
+    Q: fmt::Debug + ?Sized,
+    V: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            EntryRef::Vacant(ref v) => f.debug_tuple("EntryRef").field(v).finish(),
+            EntryRef::Occupied(ref o) => f.debug_tuple("EntryRef").field(o).finish(),
+        }
+    }
+}
+
+impl<'a, 'b, K, Q: ?Sized, V, const N: usize, S> EntryRef<'a, 'b, K, Q, V, N, S> {
+    /// If this entry is vacant, inserts a new entry with an owned key and the given value and
+    /// returns a reference to it.
+    ///
+    /// If this entry is occupied, this method *moves the occupied entry to the back of the list*
+    /// and returns a reference to the existing value.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V
+    where
+        K: Eq + Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        self.or_insert_with(|| default)
+    }
+
+    /// Similar to `EntryRef::or_insert`, but accepts a function to construct a new value if this
+    /// entry is vacant.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V
+    where
+        K: Eq + Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        match self {
+            EntryRef::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            EntryRef::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Similar to `EntryRef::or_insert_with`, but the function is given a reference to the
+    /// borrowed key.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&Q) -> V>(self, default: F) -> &'a mut V
+    where
+        K: Eq + Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        match self {
+            EntryRef::Occupied(mut entry) => {
+                entry.to_back();
+                entry.into_mut()
+            }
+            EntryRef::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Similar to `EntryRef::or_insert`, but inserts the default value if this entry is vacant.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        K: Eq + Hash + From<&'b Q>,
+        V: Default,
+        S: BuildHasher,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// If this entry is occupied, moves it to the back of the list.  Does nothing if this entry is
+    /// vacant.
+    #[inline]
+    pub fn to_back(&mut self) {
+        if let EntryRef::Occupied(entry) = self {
+            entry.to_back();
+        }
+    }
+
+    /// If this entry is occupied, moves it to the front of the list.  Does nothing if this entry
+    /// is vacant.
+    #[inline]
+    pub fn to_front(&mut self) {
+        if let EntryRef::Occupied(entry) = self {
+            entry.to_front();
+        }
+    }
+
+    /// Returns the borrowed key provided to `SmallLinkedHashMap::entry_ref`.
+    #[inline]
+    pub fn key(&self) -> &'b Q {
+        match *self {
+            EntryRef::Occupied(ref entry) => entry.lookup_key(),
+            EntryRef::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    #[inline]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            EntryRef::Occupied(mut entry) => {
+                f(entry.get_mut());
+                EntryRef::Occupied(entry)
+            }
+            EntryRef::Vacant(entry) => EntryRef::Vacant(entry),
+        }
+    }
+}
+
+pub struct OccupiedEntryRef<'a, 'b, K, Q: ?Sized, V, const N: usize, S> {
+    key: &'b Q,
+    entry: OccupiedEntry<'a, K, V, N, S>,
+}
+
+impl<K, Q, V, const N: usize, S> fmt::Debug for OccupiedEntryRef<'_, '_, K, Q, V, N, S>
+where
+    K: fmt::Debug,
+    Q: ?Sized,
+    V: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntryRef")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<'a, 'b, K, Q: ?Sized, V, const N: usize, S> OccupiedEntryRef<'a, 'b, K, Q, V, N, S> {
+    /// Returns the key stored in the map, as opposed to the borrowed key provided to
+    /// `SmallLinkedHashMap::entry_ref`.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Returns the borrowed key provided to `SmallLinkedHashMap::entry_ref`.
+    #[inline]
+    pub fn lookup_key(&self) -> &'b Q {
+        self.key
+    }
+
+    #[inline]
+    pub fn get(&self) -> &V {
+        self.entry.get()
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        self.entry.get_mut()
+    }
+
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        self.entry.into_mut()
+    }
+
+    #[inline]
+    pub fn to_back(&mut self) {
+        self.entry.to_back()
+    }
+
+    #[inline]
+    pub fn to_front(&mut self) {
+        self.entry.to_front()
+    }
+
+    /// Replaces this entry's value with the provided value, returning the old value.
+    ///
+    /// The entry is *not* moved.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        self.entry.insert(value)
+    }
+
+    #[inline]
+    pub fn remove(self) -> V {
+        self.entry.remove()
+    }
+
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        self.entry.remove_entry()
+    }
+}
+
+pub struct VacantEntryRef<'a, 'b, K, Q: ?Sized, V, const N: usize, S> {
+    inner: VacantRefInner<'a, 'b, K, Q, V, N, S>,
+}
+
+enum VacantRefInner<'a, 'b, K, Q: ?Sized, V, const N: usize, S> {
+    Inline {
+        map: &'a mut SmallLinkedHashMap<K, V, N, S>,
+        key: &'b Q,
+    },
+    Map {
+        entry: linked_hash_map::RawVacantEntryMut<'a, K, V, S>,
+        hash: u64,
+        key: &'b Q,
+    },
+}
+
+impl<K, Q, V, const N: usize, S> fmt::Debug for VacantEntryRef<'_, '_, K, Q, V, N, S>
+where
+    Q: fmt::Debug + ?Sized,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntryRef").field(&self.key()).finish()
+    }
+}
+
+impl<'a, 'b, K, Q: ?Sized, V, const N: usize, S> VacantEntryRef<'a, 'b, K, Q, V, N, S> {
+    #[inline]
+    pub fn key(&self) -> &'b Q {
+        match self.inner {
+            VacantRefInner::Inline { key, .. } | VacantRefInner::Map { key, .. } => key,
+        }
+    }
+
+    /// Converts the borrowed key into an owned key, and inserts it paired with the given value as
+    /// a new entry at the *back* of the list.
+    ///
+    /// If the entries are stored inline and there is no room left for a new one, this moves every
+    /// entry out of line first.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V
+    where
+        K: Eq + Hash + From<&'b Q>,
+        S: BuildHasher,
+    {
+        match self.inner {
+            VacantRefInner::Inline { map, key } => map.insert_new(K::from(key), value),
+            VacantRefInner::Map { entry, hash, key } => {
+                entry.insert_hashed_nocheck(hash, K::from(key), value).1
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inline: slice::Iter<'a, (K, V)>,
+    map: linked_hash_map::Iter<'a, K, V>,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            inline: self.inline.clone(),
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        match self.inline.next() {
+            Some((key, value)) => Some((key, value)),
+            None => self.map.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        match self.map.next_back() {
+            Some(entry) => Some(entry),
+            None => self.inline.next_back().map(|(key, value)| (key, value)),
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inline.len() + self.map.len()
+    }
+}
+
+pub struct IterMut<'a, K, V> {
+    inline: slice::IterMut<'a, (K, V)>,
+    map: linked_hash_map::IterMut<'a, K, V>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IterMut<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inline = self
+            .inline
+            .as_slice()
+            .iter()
+            .map(|(key, value)| (key, value));
+        f.debug_list()
+            .entries(inline)
+            .entries(self.map.iter())
+            .finish()
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        match self.inline.next() {
+            Some((key, value)) => Some((key, value)),
+            None => self.map.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a mut V)> {
+        match self.map.next_back() {
+            Some(entry) => Some(entry),
+            None => self.inline.next_back().map(|(key, value)| (&*key, value)),
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inline.len() + self.map.len()
+    }
+}
+
+pub struct IntoIter<K, V, const N: usize> {
+    inline: InlineIntoIter<(K, V), N>,
+    map: linked_hash_map::IntoIter<K, V>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for IntoIter<K, V, N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inline = self
+            .inline
+            .as_slice()
+            .iter()
+            .map(|(key, value)| (key, value));
+        f.debug_list()
+            .entries(inline)
+            .entries(self.map.iter())
+            .finish()
+    }
+}
+
+impl<K, V, const N: usize> Iterator for IntoIter<K, V, N> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        match self.inline.next() {
+            Some(entry) => Some(entry),
+            None => self.map.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<K, V, const N: usize> DoubleEndedIterator for IntoIter<K, V, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        match self.map.next_back() {
+            Some(entry) => Some(entry),
+            None => self.inline.next_back(),
+        }
+    }
+}
+
+impl<K, V, const N: usize> ExactSizeIterator for IntoIter<K, V, N> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inline.len() + self.map.len()
+    }
+}
+
+pub struct Drain<'a, K, V, const N: usize> {
+    inline: InlineIntoIter<(K, V), N>,
+    map: linked_hash_map::Drain<'a, K, V>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for Drain<'_, K, V, N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inline = self
+            .inline
+            .as_slice()
+            .iter()
+            .map(|(key, value)| (key, value));
+        f.debug_list()
+            .entries(inline)
+            .entries(self.map.iter())
+            .finish()
+    }
+}
+
+impl<K, V, const N: usize> Iterator for Drain<'_, K, V, N> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        match self.inline.next() {
+            Some(entry) => Some(entry),
+            None => self.map.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (len, _) = self.map.size_hint();
+        let len = self.inline.len() + len;
+        (len, Some(len))
+    }
+}
+
+impl<K, V, const N: usize> DoubleEndedIterator for Drain<'_, K, V, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        match self.map.next_back() {
+            Some(entry) => Some(entry),
+            None => self.inline.next_back(),
+        }
+    }
+}
+
+pub struct ExtractIf<'a, K, V, F, const N: usize, S> {
+    inner: ExtractIfInner<'a, K, V, F, N, S>,
+}
+
+enum ExtractIfInner<'a, K, V, F, const N: usize, S> {
+    Inline {
+        entries: &'a mut InlineVec<(K, V), N>,
+        // The next entry to visit.
+        index: usize,
+        pred: F,
+    },
+    Map(linked_hash_map::ExtractIf<'a, K, V, F, S>),
+}
+
+impl<K, V, F, const N: usize, S> Iterator for ExtractIf<'_, K, V, F, N, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        match self.inner {
+            ExtractIfInner::Inline {
+                ref mut entries,
+                ref mut index,
+                ref mut pred,
+            } => {
+                while *index < entries.len() {
+                    let (key, value) = &mut entries.as_mut_slice()[*index];
+                    if pred(key, value) {
+                        return Some(entries.remove(*index));
+                    }
+                    *index += 1;
+                }
+                None
+            }
+            ExtractIfInner::Map(ref mut inner) => inner.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.inner {
+            ExtractIfInner::Inline {
+                ref entries, index, ..
+            } => (0, Some(entries.len() - index)),
+            ExtractIfInner::Map(ref inner) => inner.size_hint(),
+        }
+    }
+}
+
+impl<K, V, F, const N: usize, S> fmt::Debug for ExtractIf<'_, K, V, F, N, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractIf").finish_non_exhaustive()
+    }
+}
+
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for Keys<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a K> {
+        self.inner.next().map(|e| e.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a K> {
+        self.inner.next_back().map(|e| e.0)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for Values<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|e| e.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|e| e.1)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ValuesMut<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.inner.next().map(|e| e.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back().map(|e| e.1)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a SmallLinkedHashMap<K, V, N, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a mut SmallLinkedHashMap<K, V, N, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize, S> IntoIterator for SmallLinkedHashMap<K, V, N, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V, N> {
+        IntoIter {
+            inline: self.inline.into_iter(),
+            map: self.map.into_iter(),
+        }
+    }
+}
+
+// A stable sort which does not allocate, only meant for the short slices stored inline.
+fn insertion_sort_by<T, F>(values: &mut [T], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for i in 1..values.len() {
+        let mut j = i;
+        while j > 0 && is_less(&values[j], &values[j - 1]) {
+            values.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+// A vector with a fixed inline capacity of `N`, only the first `len` slots of which are
+// initialized.
+struct InlineVec<T, const N: usize> {
+    len: usize,
+    slots: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> InlineVec<T, N> {
+    #[inline]
+    fn new() -> Self {
+        InlineVec {
+            len: 0,
+            // An array of `MaybeUninit` does not require initialization.
+            slots: unsafe { MaybeUninit::uninit().assume_init() },
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.slots.as_ptr() as *const T, self.len) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.slots.as_mut_ptr() as *mut T, self.len) }
+    }
+
+    // Panics if the vector is full.
+    #[inline]
+    fn push(&mut self, value: T) -> &mut T {
+        let slot = self.slots[self.len].write(value);
+        self.len += 1;
+        slot
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.slots[self.len].assume_init_read() })
+    }
+
+    // Removes the value at `index`, shifting every following value down by one.
+    #[inline]
+    fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "index out of bounds");
+        unsafe {
+            let ptr = self.slots.as_mut_ptr().add(index);
+            let value = (*ptr).assume_init_read();
+            ptr::copy(ptr.add(1), ptr, self.len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            let tail = &mut self.as_mut_slice()[len..] as *mut [T];
+            // Shorten the vector before dropping the tail, in case a destructor panics.
+            self.len = len;
+            unsafe { ptr::drop_in_place(tail) };
+        }
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    fn retain<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        let len = self.len;
+        let mut removed = 0;
+        {
+            let values = self.as_mut_slice();
+            for i in 0..len {
+                if !f(&mut values[i]) {
+                    removed += 1;
+                } else if removed > 0 {
+                    values.swap(i - removed, i);
+                }
+            }
+        }
+        // Every removed value has been swapped past the retained ones, in order.
+        self.truncate(len - removed);
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for InlineVec<T, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let mut vec = InlineVec::new();
+        for value in self.as_slice() {
+            vec.push(value.clone());
+        }
+        vec
+    }
+}
+
+impl<T, const N: usize> Drop for InlineVec<T, N> {
+    #[inline]
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> IntoIterator for InlineVec<T, N> {
+    type Item = T;
+    type IntoIter = InlineIntoIter<T, N>;
+
+    #[inline]
+    fn into_iter(self) -> InlineIntoIter<T, N> {
+        let this = mem::ManuallyDrop::new(self);
+        InlineIntoIter {
+            start: 0,
+            end: this.len,
+            slots: unsafe { ptr::read(&this.slots) },
+        }
+    }
+}
+
+// Owning iterator over an `InlineVec`, where only the slots in `start..end` are initialized.
+struct InlineIntoIter<T, const N: usize> {
+    start: usize,
+    end: usize,
+    slots: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> InlineIntoIter<T, N> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        unsafe {
+            slice::from_raw_parts(
+                self.slots.as_ptr().add(self.start) as *const T,
+                self.end - self.start,
+            )
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+impl<T, const N: usize> Iterator for InlineIntoIter<T, N> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(unsafe { self.slots[self.start - 1].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for InlineIntoIter<T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { self.slots[self.end].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> Drop for InlineIntoIter<T, N> {
+    #[inline]
+    fn drop(&mut self) {
+        let remaining = ptr::slice_from_raw_parts_mut(
+            unsafe { self.slots.as_mut_ptr().add(self.start) } as *mut T,
+            self.len(),
+        );
+        self.start = self.end;
+        unsafe { ptr::drop_in_place(remaining) };
+    }
+}
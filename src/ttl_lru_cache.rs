@@ -0,0 +1,466 @@
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash},
+    time::Duration,
+};
+
+use crate::linked_hash_map::{self, RawEntryMut};
+use crate::lru_cache::LruCache;
+use crate::{Allocator, DefaultHashBuilder, Equivalent, Global};
+
+/// A source of monotonic time for a `TtlLruCache`.
+///
+/// The returned `Duration` is measured from an arbitrary but fixed origin, and must never decrease.
+/// This is implemented for every `Fn() -> Duration`, so a clock backed by `std::time::Instant` or a
+/// manually advanced clock for testing can both be written as a closure.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> Duration,
+{
+    #[inline]
+    fn now(&self) -> Duration {
+        self()
+    }
+}
+
+/// An `LruCache` whose entries also expire once their time-to-live has passed.
+///
+/// Every entry records the time at which it expires when it is inserted, using either the TTL of the
+/// cache or a TTL given for that entry alone.  Expired entries are never returned, but they are only
+/// removed lazily, when they are looked up through a `&mut self` method, evicted for space like any
+/// other entry, or removed by `TtlLruCache::purge_expired`.  Until then, they still count towards
+/// `TtlLruCache::len` and the capacity of the cache.
+pub struct TtlLruCache<K, V, C, S = DefaultHashBuilder, A: Allocator = Global> {
+    // Every value is stored along with the time at which it expires, or `None` if that time is too
+    // far in the future to be represented.
+    cache: LruCache<K, (V, Option<Duration>), S, A>,
+    clock: C,
+    ttl: Duration,
+}
+
+impl<K: Eq + Hash, V, C: Clock> TtlLruCache<K, V, C> {
+    #[inline]
+    pub fn new(capacity: usize, ttl: Duration, clock: C) -> Self {
+        TtlLruCache {
+            cache: LruCache::new(capacity),
+            clock,
+            ttl,
+        }
+    }
+}
+
+impl<K, V, C, S> TtlLruCache<K, V, C, S> {
+    #[inline]
+    pub fn with_hasher(capacity: usize, ttl: Duration, clock: C, hash_builder: S) -> Self {
+        TtlLruCache {
+            cache: LruCache::with_hasher(capacity, hash_builder),
+            clock,
+            ttl,
+        }
+    }
+}
+
+impl<K, V, C, S, A: Allocator + Clone> TtlLruCache<K, V, C, S, A> {
+    #[inline]
+    pub fn with_hasher_in(
+        capacity: usize,
+        ttl: Duration,
+        clock: C,
+        hash_builder: S,
+        alloc: A,
+    ) -> Self {
+        TtlLruCache {
+            cache: LruCache::with_hasher_in(capacity, hash_builder, alloc),
+            clock,
+            ttl,
+        }
+    }
+}
+
+impl<K, V, C, S, A: Allocator> TtlLruCache<K, V, C, S, A> {
+    /// Returns a reference to the allocator used by this cache.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.cache.allocator()
+    }
+
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns the TTL given to entries inserted with `TtlLruCache::insert`.
+    #[inline]
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Sets the TTL given to entries inserted with `TtlLruCache::insert` from now on.
+    ///
+    /// Entries which are already in the cache keep the expiry time they were inserted with.
+    #[inline]
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cache.capacity()
+    }
+
+    /// Returns the number of entries in the cache, including any expired entries which have not
+    /// been removed yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+impl<K, V, C: Clock, S, A: Allocator> TtlLruCache<K, V, C, S, A> {
+    /// Returns an iterator over the entries which have not expired, from least to most recently
+    /// used.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.cache.iter(),
+            now: self.clock.now(),
+        }
+    }
+
+    /// Removes every expired entry, walking the LRU list from the front, and returns how many were
+    /// removed.
+    #[inline]
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let len = self.cache.len();
+        self.cache
+            .retain(|_, (_, expires)| !is_expired(*expires, now));
+        len - self.cache.len()
+    }
+
+    /// Retains only the entries which have not expired and for which the given function returns
+    /// true.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let now = self.clock.now();
+        self.cache
+            .retain(|key, (value, expires)| !is_expired(*expires, now) && f(key, value));
+    }
+
+    #[inline]
+    fn expires_at(&self, ttl: Duration) -> Option<Duration> {
+        self.clock.now().checked_add(ttl)
+    }
+}
+
+impl<K: Eq + Hash, V, C: Clock, S: BuildHasher, A: Allocator> TtlLruCache<K, V, C, S, A> {
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.peek(key).is_some()
+    }
+
+    /// Insert a new value into the `TtlLruCache`, which expires after the TTL of the cache.
+    ///
+    /// If necessary, will remove the value at the front of the LRU list to make room.  Returns the
+    /// previous value for this key, if it had not expired.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.insert_with_ttl(k, v, self.ttl)
+    }
+
+    /// Insert a new value into the `TtlLruCache`, which expires after the given TTL instead of the
+    /// TTL of the cache.
+    #[inline]
+    pub fn insert_with_ttl(&mut self, k: K, v: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        let (old, expires) = self.cache.insert(k, (v, now.checked_add(ttl)))?;
+        if is_expired(expires, now) {
+            None
+        } else {
+            Some(old)
+        }
+    }
+
+    /// Get the value for the given key, *without* marking the value as recently used and moving it
+    /// to the back of the LRU list.
+    ///
+    /// An expired entry is not returned, but is left in the cache since this takes `&self`.
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (value, expires) = self.cache.peek(k)?;
+        if is_expired(*expires, self.clock.now()) {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Get the value for the given key mutably, *without* marking the value as recently used and
+    /// moving it to the back of the LRU list.
+    ///
+    /// An expired entry is removed instead.
+    #[inline]
+    pub fn peek_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.lookup(k, false)
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list.
+    ///
+    /// An expired entry is removed instead.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get_mut(k).map(|v| &*v)
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list.
+    ///
+    /// An expired entry is removed instead.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.lookup(k, true)
+    }
+
+    /// Returns how long the entry for the given key has left before it expires.
+    ///
+    /// Returns `Some(Duration::MAX)` for entries whose expiry time is too far in the future to be
+    /// represented, and None for missing or expired entries.
+    #[inline]
+    pub fn remaining_ttl<Q>(&self, k: &Q) -> Option<Duration>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (_, expires) = self.cache.peek(k)?;
+        match expires {
+            Some(expires) => expires
+                .checked_sub(self.clock.now())
+                .filter(|remaining| !remaining.is_zero()),
+            None => Some(Duration::MAX),
+        }
+    }
+
+    /// Sets a new TTL for the entry with the given key, measured from now, and returns whether the
+    /// entry was found.
+    ///
+    /// An expired entry is removed instead.
+    #[inline]
+    pub fn set_entry_ttl<Q>(&mut self, k: &Q, ttl: Duration) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let expires_at = self.expires_at(ttl);
+        match self.lookup_entry(k, false) {
+            Some((_, expires)) => {
+                *expires = expires_at;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the entry for the given key, returning its value if it had not expired.
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes the entry for the given key, returning it if it had not expired.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (key, (value, expires)) = self.cache.remove_entry(k)?;
+        if is_expired(expires, self.clock.now()) {
+            None
+        } else {
+            Some((key, value))
+        }
+    }
+
+    /// Remove the least recently used entry which has not expired and return it.
+    ///
+    /// Any expired entries in front of it are removed as well.  If there is no such entry this will
+    /// return None.
+    #[inline]
+    pub fn remove_lru(&mut self) -> Option<(K, V)> {
+        let now = self.clock.now();
+        while let Some((key, (value, expires))) = self.cache.remove_lru() {
+            if !is_expired(expires, now) {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    /// Set the new cache capacity for the `TtlLruCache`.
+    ///
+    /// If there are more entries in the `TtlLruCache` than the new capacity will allow, they are
+    /// removed.
+    #[inline]
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.cache.set_capacity(capacity);
+    }
+
+    #[inline]
+    fn lookup<Q>(&mut self, k: &Q, to_back: bool) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.lookup_entry(k, to_back).map(|(v, _)| v)
+    }
+
+    // Finds the entry for the given key, removing it if it has expired.
+    #[inline]
+    fn lookup_entry<Q>(&mut self, k: &Q, to_back: bool) -> Option<&mut (V, Option<Duration>)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let now = self.clock.now();
+        match self.cache.raw_entry_mut().from_key(k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                if is_expired(occupied.get().1, now) {
+                    occupied.remove();
+                    return None;
+                }
+                if to_back {
+                    occupied.to_back();
+                }
+                Some(occupied.into_mut())
+            }
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+}
+
+impl<K, V, C, S, A> Clone for TtlLruCache<K, V, C, S, A>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    C: Clone,
+    S: BuildHasher + Clone,
+    A: Allocator + Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        TtlLruCache {
+            cache: self.cache.clone(),
+            clock: self.clock.clone(),
+            ttl: self.ttl,
+        }
+    }
+}
+
+impl<K, V, C: Clock, S, A: Allocator> fmt::Debug for TtlLruCache<K, V, C, S, A>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter().rev()).finish()
+    }
+}
+
+impl<'a, K, V, C: Clock, S, A: Allocator> IntoIterator for &'a TtlLruCache<K, V, C, S, A> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a `TtlLruCache` which had not expired when it was created.
+pub struct Iter<'a, K, V> {
+    inner: linked_hash_map::Iter<'a, K, (V, Option<Duration>)>,
+    now: Duration,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+            now: self.now,
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let now = self.now;
+        self.inner
+            .find(|(_, (_, expires))| !is_expired(*expires, now))
+            .map(|(k, (v, _))| (k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        let now = self.now;
+        self.inner
+            .rfind(|(_, (_, expires))| !is_expired(*expires, now))
+            .map(|(k, (v, _))| (k, v))
+    }
+}
+
+#[inline]
+fn is_expired(expires: Option<Duration>, now: Duration) -> bool {
+    match expires {
+        Some(expires) => expires <= now,
+        None => false,
+    }
+}
@@ -0,0 +1,589 @@
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use crate::linked_hash_map::{self, hash_key, LinkedHashMap, RawEntryMut};
+use crate::{Allocator, DefaultHashBuilder, Equivalent, Global};
+
+/// Computes the weight of an entry in a `WeightedLruCache`.
+///
+/// This is implemented for every `Fn(&K, &V) -> usize`.
+pub trait Weigher<K, V> {
+    fn weigh(&self, key: &K, value: &V) -> usize;
+}
+
+impl<K, V, F> Weigher<K, V> for F
+where
+    F: Fn(&K, &V) -> usize,
+{
+    #[inline]
+    fn weigh(&self, key: &K, value: &V) -> usize {
+        self(key, value)
+    }
+}
+
+/// An LRU cache which limits the total weight of its entries rather than their number.
+///
+/// The weight of every entry is computed by a `Weigher` when it is inserted, and again whenever it
+/// may have been modified, which is when a `WeightGuard` returned by `WeightedLruCache::get_mut` or
+/// `WeightedLruCache::peek_mut` is dropped.  Whenever the total weight exceeds the maximum weight,
+/// entries are evicted from the least recently used end until it fits again.  An entry which is
+/// heavier than the maximum weight on its own is never kept.
+pub struct WeightedLruCache<K, V, W, S = DefaultHashBuilder, A: Allocator = Global> {
+    // Every value is stored along with its last computed weight.
+    map: LinkedHashMap<K, (V, usize), S, A>,
+    weigher: W,
+    max_weight: usize,
+    weight: usize,
+}
+
+impl<K: Eq + Hash, V, W: Weigher<K, V>> WeightedLruCache<K, V, W> {
+    #[inline]
+    pub fn with_weigher(max_weight: usize, weigher: W) -> Self {
+        Self::with_weigher_and_hasher(max_weight, weigher, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, W, S> WeightedLruCache<K, V, W, S> {
+    #[inline]
+    pub fn with_weigher_and_hasher(max_weight: usize, weigher: W, hash_builder: S) -> Self {
+        WeightedLruCache {
+            map: LinkedHashMap::with_hasher(hash_builder),
+            weigher,
+            max_weight,
+            weight: 0,
+        }
+    }
+}
+
+impl<K, V, W, S, A: Allocator + Clone> WeightedLruCache<K, V, W, S, A> {
+    #[inline]
+    pub fn with_weigher_and_hasher_in(
+        max_weight: usize,
+        weigher: W,
+        hash_builder: S,
+        alloc: A,
+    ) -> Self {
+        WeightedLruCache {
+            map: LinkedHashMap::with_hasher_in(hash_builder, alloc),
+            weigher,
+            max_weight,
+            weight: 0,
+        }
+    }
+}
+
+impl<K, V, W, S, A: Allocator> WeightedLruCache<K, V, W, S, A> {
+    /// Returns a reference to the allocator used by this cache.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.map.allocator()
+    }
+
+    #[inline]
+    pub fn weigher(&self) -> &W {
+        &self.weigher
+    }
+
+    #[inline]
+    pub fn max_weight(&self) -> usize {
+        self.max_weight
+    }
+
+    /// Returns the total weight of every entry in the cache.
+    #[inline]
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.weight = 0;
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.map.iter(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, W: Weigher<K, V>, S: BuildHasher, A: Allocator>
+    WeightedLruCache<K, V, W, S, A>
+{
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Insert a new value into the `WeightedLruCache`, at the most recently used end.
+    ///
+    /// If necessary, will remove entries from the front of the LRU list until the total weight fits
+    /// within the maximum weight again.  If the entry is heavier than the maximum weight on its own,
+    /// it is returned as an error instead and the cache is left unchanged.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Result<Option<V>, (K, V)> {
+        let weight = self.weigher.weigh(&k, &v);
+        if weight > self.max_weight {
+            return Err((k, v));
+        }
+
+        let old = match self.map.raw_entry_mut().from_key(&k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                occupied.to_back();
+                let (old_value, old_weight) = occupied.replace_value((v, weight));
+                self.weight -= old_weight;
+                Some(old_value)
+            }
+            RawEntryMut::Vacant(vacant) => {
+                vacant.insert(k, (v, weight));
+                None
+            }
+        };
+        // The new entry is at the back, so it is only reached once everything else is evicted, at
+        // which point it fits.
+        self.make_room(weight, None);
+        self.weight += weight;
+        Ok(old)
+    }
+
+    /// Get the value for the given key, *without* marking the value as recently used and moving it
+    /// to the back of the LRU list.
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.get(k).map(|(v, _)| v)
+    }
+
+    /// Get the value for the given key mutably, *without* marking the value as recently used and
+    /// moving it to the back of the LRU list.
+    ///
+    /// The entry is weighed again once the returned guard is dropped.
+    #[inline]
+    pub fn peek_mut<Q>(&mut self, k: &Q) -> Option<WeightGuard<'_, K, V, W, S, A>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.guard(k, false)
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        match self.map.raw_entry_mut().from_key(k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                occupied.to_back();
+                Some(&occupied.into_mut().0)
+            }
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    /// Retrieve the given key, marking it as recently used and moving it to the back of the LRU
+    /// list.
+    ///
+    /// The entry is weighed again once the returned guard is dropped.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<WeightGuard<'_, K, V, W, S, A>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.guard(k, true)
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (key, (value, weight)) = self.map.remove_entry(k)?;
+        self.weight -= weight;
+        Some((key, value))
+    }
+
+    /// Remove the least recently used entry and return it.
+    ///
+    /// If the `WeightedLruCache` is empty this will return None.
+    #[inline]
+    pub fn remove_lru(&mut self) -> Option<(K, V)> {
+        let (key, (value, weight)) = self.map.pop_front()?;
+        self.weight -= weight;
+        Some((key, value))
+    }
+
+    /// Changes the maximum weight, evicting the least recently used entries until the total weight
+    /// fits.
+    #[inline]
+    pub fn set_max_weight(&mut self, max_weight: usize) {
+        self.max_weight = max_weight;
+        self.evict_to_fit();
+    }
+
+    /// Retains only the entries for which the given function returns true.
+    ///
+    /// Every retained entry is weighed again afterwards, since the function may have modified it.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let weigher = &self.weigher;
+        let max_weight = self.max_weight;
+        self.map.retain(|key, (value, weight)| {
+            if !f(key, value) {
+                return false;
+            }
+            *weight = weigher.weigh(key, value);
+            *weight <= max_weight
+        });
+
+        // Keep the most recently used entries which fit together, summing from the back so that the
+        // total never exceeds the maximum weight and so can never overflow.
+        let mut total = 0usize;
+        let mut keep = 0;
+        for (_, &(_, weight)) in self.map.iter().rev() {
+            match total.checked_add(weight) {
+                Some(sum) if sum <= max_weight => {
+                    total = sum;
+                    keep += 1;
+                }
+                _ => break,
+            }
+        }
+        for _ in keep..self.map.len() {
+            self.map.pop_front();
+        }
+        self.weight = total;
+    }
+
+    #[inline]
+    fn evict_to_fit(&mut self) {
+        self.make_room(0, None);
+    }
+
+    // Evicts from the front of the LRU list until `weight` more fits within the maximum weight,
+    // which it must not exceed by itself.  If the entry with the key at `entry` is reached, which
+    // is not counted in the total weight, it is evicted too and false is returned.
+    #[inline]
+    fn make_room(&mut self, weight: usize, entry: Option<*const K>) -> bool {
+        let max_weight = self.max_weight - weight;
+        while self.weight > max_weight {
+            let is_entry = match (entry, self.map.front()) {
+                (Some(entry), Some((front, _))) => ptr::eq(front, entry),
+                _ => false,
+            };
+            match self.map.pop_front() {
+                Some(_) if is_entry => return false,
+                Some((_, (_, weight))) => self.weight -= weight,
+                None => break,
+            }
+        }
+        true
+    }
+
+    #[inline]
+    fn guard<Q>(&mut self, k: &Q, to_back: bool) -> Option<WeightGuard<'_, K, V, W, S, A>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = hash_key(self.map.hasher(), k);
+        let (key, entry) = match self.map.raw_entry_mut().from_key_hashed_nocheck(hash, k) {
+            RawEntryMut::Occupied(mut occupied) => {
+                if to_back {
+                    occupied.to_back();
+                }
+                occupied.into_key_value()
+            }
+            RawEntryMut::Vacant(_) => return None,
+        };
+        Some(WeightGuard {
+            key: NonNull::from(key),
+            entry: NonNull::from(entry),
+            hash,
+            cache: self,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<K, V, W, S, A> Clone for WeightedLruCache<K, V, W, S, A>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    W: Clone,
+    S: BuildHasher + Clone,
+    A: Allocator + Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        WeightedLruCache {
+            map: self.map.clone(),
+            weigher: self.weigher.clone(),
+            max_weight: self.max_weight,
+            weight: self.weight,
+        }
+    }
+}
+
+impl<K, V, W, S, A: Allocator> fmt::Debug for WeightedLruCache<K, V, W, S, A>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter().rev()).finish()
+    }
+}
+
+impl<K, V, W, S, A: Allocator> IntoIterator for WeightedLruCache<K, V, W, S, A> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, A>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V, A> {
+        IntoIter {
+            inner: self.map.into_iter(),
+        }
+    }
+}
+
+impl<'a, K, V, W, S, A: Allocator> IntoIterator for &'a WeightedLruCache<K, V, W, S, A> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// A mutable reference to a value in a `WeightedLruCache`, which weighs the entry again when
+/// dropped.
+///
+/// If the entry has become heavier than the maximum weight of the cache, it is removed.  Otherwise,
+/// least recently used entries are evicted until the total weight fits again, which may include
+/// this entry if it was returned by `WeightedLruCache::peek_mut`.
+pub struct WeightGuard<'a, K, V, W, S, A>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    // Both point into `cache`, which is not otherwise accessed until the guard is dropped.
+    key: NonNull<K>,
+    entry: NonNull<(V, usize)>,
+    hash: u64,
+    cache: &'a mut WeightedLruCache<K, V, W, S, A>,
+    marker: PhantomData<&'a mut V>,
+}
+
+impl<K, V, W, S, A> WeightGuard<'_, K, V, W, S, A>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    #[inline]
+    pub fn key(&self) -> &K {
+        unsafe { self.key.as_ref() }
+    }
+}
+
+impl<K, V, W, S, A> Deref for WeightGuard<'_, K, V, W, S, A>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    type Target = V;
+
+    #[inline]
+    fn deref(&self) -> &V {
+        unsafe { &self.entry.as_ref().0 }
+    }
+}
+
+impl<K, V, W, S, A> DerefMut for WeightGuard<'_, K, V, W, S, A>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut V {
+        unsafe { &mut self.entry.as_mut().0 }
+    }
+}
+
+impl<K, V, W, S, A> fmt::Debug for WeightGuard<'_, K, V, W, S, A>
+where
+    K: Eq + Hash + fmt::Debug,
+    V: fmt::Debug,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeightGuard")
+            .field("key", self.key())
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+impl<K, V, W, S, A> Drop for WeightGuard<'_, K, V, W, S, A>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        let key = self.key.as_ptr() as *const K;
+        let (value, weight) = unsafe { self.entry.as_mut() };
+        let new_weight = self.cache.weigher.weigh(unsafe { &*key }, value);
+        let old_weight = mem::replace(weight, new_weight);
+        self.cache.weight -= old_weight;
+
+        if new_weight > self.cache.max_weight {
+            // Find this exact entry again by the address of its key.
+            if let RawEntryMut::Occupied(occupied) = self
+                .cache
+                .map
+                .raw_entry_mut()
+                .from_hash(self.hash, |k| ptr::eq(k, key))
+            {
+                occupied.remove();
+            }
+        } else if self.cache.make_room(new_weight, Some(key)) {
+            self.cache.weight += new_weight;
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: linked_hash_map::Iter<'a, K, (V, usize)>,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.inner.next().map(|(k, (v, _))| (k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        self.inner.next_back().map(|(k, (v, _))| (k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+pub struct IntoIter<K, V, A: Allocator = Global> {
+    inner: linked_hash_map::IntoIter<K, (V, usize), A>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, A: Allocator> fmt::Debug for IntoIter<K, V, A> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.inner.iter().map(|(k, (v, _))| (k, v)))
+            .finish()
+    }
+}
+
+impl<K, V, A: Allocator> Iterator for IntoIter<K, V, A> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next().map(|(k, (v, _))| (k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, A: Allocator> DoubleEndedIterator for IntoIter<K, V, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        self.inner.next_back().map(|(k, (v, _))| (k, v))
+    }
+}
+
+impl<K, V, A: Allocator> ExactSizeIterator for IntoIter<K, V, A> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
@@ -0,0 +1,170 @@
+#![cfg(any(feature = "alloc", feature = "foldhash"))]
+
+use std::{cell::Cell, hash::BuildHasherDefault, rc::Rc};
+
+use hashlink::{array_linked_hash_map::CapacityError, ArrayLinkedHashMap};
+
+#[test]
+fn test_insert_and_get() {
+    let mut map = ArrayLinkedHashMap::<_, _, 3>::new();
+    assert_eq!(map.capacity(), 3);
+    assert_eq!(map.insert(1, 10), Ok(None));
+    assert_eq!(map.insert(2, 20), Ok(None));
+    assert_eq!(map.insert(3, 30), Ok(None));
+    assert!(map.is_full());
+
+    assert_eq!(map.insert(4, 40), Err(CapacityError((4, 40))));
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get(&4), None);
+
+    assert_eq!(map.get(&2), Some(&20));
+    *map.get_mut(&3).unwrap() += 1;
+    assert_eq!(map[&3], 31);
+
+    // Replacing an existing key succeeds even when full, and moves it to the back.
+    assert_eq!(map.insert(1, 11), Ok(Some(10)));
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [(&2, &20), (&3, &31), (&1, &11)]
+    );
+    assert_eq!(map.replace(2, 21), Ok(Some(20)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 3, 1]);
+
+    assert_eq!(map.remove(&3), Some(31));
+    assert_eq!(map.insert(4, 40), Ok(None));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1, 4]);
+}
+
+#[test]
+fn test_order() {
+    let mut map = ArrayLinkedHashMap::<_, _, 8>::new();
+    for i in 0..5 {
+        map.insert(i, i).unwrap();
+    }
+    assert_eq!(map.front(), Some((&0, &0)));
+    assert_eq!(map.back(), Some((&4, &4)));
+
+    assert_eq!(map.to_front(&3), Some(&mut 3));
+    assert_eq!(map.to_back(&0), Some(&mut 0));
+    assert_eq!(map.to_back(&9), None);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [3, 1, 2, 4, 0]);
+
+    assert_eq!(map.pop_front(), Some((3, 3)));
+    assert_eq!(map.pop_back(), Some((0, 0)));
+    assert_eq!(map.values().rev().copied().collect::<Vec<_>>(), [4, 2, 1]);
+
+    map.retain(|&k, v| {
+        *v *= 10;
+        k != 2
+    });
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &10), (&4, &40)]);
+
+    let mut iter = map.clone().into_iter();
+    assert_eq!(iter.len(), 2);
+    assert_eq!(iter.next_back(), Some((4, 40)));
+    assert_eq!(iter.next(), Some((1, 10)));
+    assert_eq!(iter.next(), None);
+
+    assert_eq!(format!("{:?}", map), "{1: 10, 4: 40}");
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.front(), None);
+}
+
+#[test]
+fn test_zero_capacity() {
+    let mut map = ArrayLinkedHashMap::<u32, u32, 0>::new();
+    assert_eq!(map.insert(1, 1), Err(CapacityError((1, 1))));
+    assert_eq!(map.get(&1), None);
+    assert_eq!(map.remove(&1), None);
+    assert_eq!(map.pop_front(), None);
+}
+
+#[test]
+fn test_collisions() {
+    // Every key has the same hash, so every lookup has to probe past every other entry.
+    let mut map = ArrayLinkedHashMap::<u32, u32, 16, BuildHasherDefault<ConstHasher>>::default();
+    for i in 0..16 {
+        map.insert(i, i).unwrap();
+    }
+    for i in (0..16).step_by(3) {
+        assert_eq!(map.remove(&i), Some(i));
+    }
+    for i in 0..16 {
+        assert_eq!(map.get(&i).is_some(), i % 3 != 0);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_matches_linked_hash_map() {
+    let mut map = ArrayLinkedHashMap::<u32, u32, 32>::new();
+    let mut expected = hashlink::LinkedHashMap::new();
+
+    // Simple LCG, so that keys collide and probe runs wrap around the index.
+    let mut state = 0x2545f491u32;
+    let mut next = || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        state >> 16
+    };
+
+    for i in 0..10_000 {
+        let key = next() % 48;
+        match next() % 4 {
+            0 | 1 => {
+                let result = map.insert(key, i);
+                if expected.contains_key(&key) || expected.len() < 32 {
+                    assert_eq!(result, Ok(expected.insert(key, i)));
+                } else {
+                    assert_eq!(result, Err(CapacityError((key, i))));
+                }
+            }
+            2 => assert_eq!(map.remove(&key), expected.remove(&key)),
+            _ => assert_eq!(map.to_front(&key), expected.to_front(&key)),
+        }
+        assert!(map.iter().eq(expected.iter()));
+    }
+    for (k, v) in &expected {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
+
+#[test]
+fn test_drops() {
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let mut map = ArrayLinkedHashMap::<_, _, 4>::new();
+    for i in 0..4 {
+        map.insert(i, Counted(drops.clone())).ok().unwrap();
+    }
+    let rejected = map.insert(4, Counted(drops.clone()));
+    drop(rejected);
+    assert_eq!(drops.get(), 1);
+
+    map.remove(&0);
+    assert_eq!(drops.get(), 2);
+
+    let mut iter = map.into_iter();
+    iter.next();
+    assert_eq!(drops.get(), 3);
+    drop(iter);
+    assert_eq!(drops.get(), 5);
+}
+
+#[derive(Default)]
+struct ConstHasher;
+
+impl std::hash::Hasher for ConstHasher {
+    fn finish(&self) -> u64 {
+        0x8000_0000_0000_0000
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
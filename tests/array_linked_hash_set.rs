@@ -0,0 +1,42 @@
+#![cfg(any(feature = "alloc", feature = "foldhash"))]
+
+use hashlink::{array_linked_hash_map::CapacityError, ArrayLinkedHashSet};
+
+#[test]
+fn test_insert_and_order() {
+    let mut set = ArrayLinkedHashSet::<_, 3>::new();
+    assert_eq!(set.insert("a"), Ok(true));
+    assert_eq!(set.insert("b"), Ok(true));
+    assert_eq!(set.insert("c"), Ok(true));
+    assert_eq!(set.insert("d"), Err(CapacityError("d")));
+
+    // Inserting an existing value moves it to the back.
+    assert_eq!(set.insert("a"), Ok(false));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), ["b", "c", "a"]);
+
+    assert!(set.to_front("a"));
+    assert!(!set.to_back("d"));
+    assert_eq!(set.front(), Some(&"a"));
+    assert_eq!(set.back(), Some(&"c"));
+    assert!(set.contains("b"));
+
+    assert!(set.remove("b"));
+    assert_eq!(set.take("c"), Some("c"));
+    assert_eq!(set.insert("d"), Ok(true));
+    assert_eq!(set.pop_back(), Some("d"));
+    assert_eq!(set.pop_front(), Some("a"));
+    assert!(set.is_empty());
+}
+
+#[test]
+fn test_retain_and_iter() {
+    let mut set = ArrayLinkedHashSet::<_, 8>::new();
+    for i in 0..8 {
+        set.insert(i).unwrap();
+    }
+    set.retain(|&v| v % 2 == 1);
+    assert_eq!(set.iter().rev().copied().collect::<Vec<_>>(), [7, 5, 3, 1]);
+    assert_eq!(set.clone(), set);
+    assert_eq!(format!("{:?}", set), "{1, 3, 5, 7}");
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 3, 5, 7]);
+}
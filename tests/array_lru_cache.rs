@@ -0,0 +1,41 @@
+#![cfg(any(feature = "alloc", feature = "foldhash"))]
+
+use hashlink::ArrayLruCache;
+
+#[test]
+fn test_evict_lru() {
+    let mut cache = ArrayLruCache::<_, _, 2>::new();
+    assert_eq!(cache.insert("a", 1), None);
+    assert_eq!(cache.insert("b", 2), None);
+    assert_eq!(cache.get("a"), Some(&1));
+
+    // "b" is now the least recently used entry.
+    assert_eq!(cache.insert("c", 3), None);
+    assert_eq!(cache.len(), 2);
+    assert!(!cache.contains_key("b"));
+
+    // Peeking does not mark "a" as recently used, so it is evicted next.
+    assert_eq!(cache.peek("a"), Some(&1));
+    assert_eq!(cache.insert("d", 4), None);
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&"c", &3), (&"d", &4)]);
+
+    assert_eq!(cache.insert("c", 5), Some(3));
+    assert_eq!(cache.remove_lru(), Some(("d", 4)));
+    assert_eq!(format!("{:?}", cache), "{\"c\": 5}");
+}
+
+#[test]
+fn test_zero_capacity() {
+    let mut cache = ArrayLruCache::<u32, u32, 0>::new();
+    assert_eq!(cache.insert(1, 1), None);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_extend() {
+    let cache: ArrayLruCache<_, _, 3> = (0..10).map(|i| (i, i)).collect();
+    assert_eq!(
+        cache.into_iter().collect::<Vec<_>>(),
+        [(7, 7), (8, 8), (9, 9)]
+    );
+}
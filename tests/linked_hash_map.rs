@@ -1,3 +1,5 @@
+#![cfg(feature = "alloc")]
+
 use hashlink::{linked_hash_map, LinkedHashMap};
 
 #[allow(dead_code)]
@@ -30,6 +32,18 @@ fn assert_covariance() {
         d
     }
 
+    fn range<'a, 'new>(
+        v: linked_hash_map::Range<'a, &'static str, &'static str>,
+    ) -> linked_hash_map::Range<'a, &'new str, &'new str> {
+        v
+    }
+
+    fn cursor<'a, 'new>(
+        v: linked_hash_map::Cursor<'a, &'static str, &'static str>,
+    ) -> linked_hash_map::Cursor<'a, &'new str, &'new str> {
+        v
+    }
+
     fn raw_entry_builder<'a, 'new>(
         v: linked_hash_map::RawEntryBuilder<'a, &'static str, &'static str, ()>,
     ) -> linked_hash_map::RawEntryBuilder<'a, &'new str, &'new str, ()> {
@@ -576,6 +590,40 @@ fn test_shrink_to_fit_resize() {
     }
 }
 
+#[test]
+fn test_shrink_to_fit_compact() {
+    let mut map = LinkedHashMap::new();
+    for i in 0..100 {
+        map.insert(i, i * 10);
+    }
+    map.retain(|&k, _| k % 3 == 0);
+    map.to_front(&51);
+    map.to_back(&3);
+    map.shrink_to_fit();
+
+    let expected = [51]
+        .iter()
+        .copied()
+        .chain((0..100).filter(|&k| k % 3 == 0 && k != 51 && k != 3))
+        .chain([3].iter().copied())
+        .collect::<Vec<_>>();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), expected);
+    for &k in &expected {
+        assert_eq!(map[&k], k * 10);
+    }
+
+    map.insert(1000, 1);
+    assert_eq!(map.pop_front(), Some((51, 510)));
+    assert_eq!(map.pop_back(), Some((1000, 1)));
+    assert_eq!(map.back(), Some((&3, &30)));
+
+    map.clear();
+    map.shrink_to_fit();
+    assert!(map.is_empty());
+    map.insert(1, 1);
+    assert_eq!(map.front(), Some((&1, &1)));
+}
+
 #[test]
 fn test_cursor_mut_current() {
     let mut map = LinkedHashMap::new();
@@ -820,3 +868,1218 @@ fn test_cursor_back_mut() {
     assert!(cursor.current().is_some());
     assert_eq!(cursor.current().unwrap().1, &mut 3);
 }
+
+#[cfg(feature = "allocator-api2")]
+#[test]
+fn test_allocator() {
+    use core::{alloc::Layout, cell::Cell, ptr::NonNull};
+    use std::rc::Rc;
+
+    use allocator_api2::alloc::{AllocError, Allocator, Global};
+
+    #[derive(Clone, Default)]
+    struct CountingAlloc(Rc<Cell<usize>>);
+
+    unsafe impl Allocator for CountingAlloc {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = Global.allocate(layout)?;
+            self.0.set(self.0.get() + 1);
+            Ok(ptr)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.set(self.0.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    let alloc = CountingAlloc::default();
+    let live = alloc.0.clone();
+
+    let mut map = LinkedHashMap::new_in(alloc.clone());
+    assert_eq!(live.get(), 0);
+    for i in 0..100 {
+        map.insert(i, i * 10);
+    }
+    assert!(live.get() > 0);
+    map.retain(|&k, _| k % 2 == 0);
+    map.shrink_to_fit();
+    assert_eq!(
+        map.iter().map(|(&k, _)| k).collect::<Vec<_>>(),
+        (0..100).step_by(2).collect::<Vec<_>>()
+    );
+
+    let cloned = map.clone();
+    assert_eq!(map, cloned);
+    drop(map);
+    assert_eq!(cloned.into_iter().count(), 50);
+    assert_eq!(live.get(), 0);
+
+    let mut set = hashlink::LinkedHashSet::with_capacity_in(4, alloc.clone());
+    set.insert("a");
+    set.insert("b");
+    assert!(live.get() > 0);
+    drop(set);
+
+    let mut cache = hashlink::LruCache::new_in(2, alloc);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    cache.insert(3, 3);
+    assert_eq!(cache.len(), 2);
+    assert!(!cache.contains_key(&1));
+    drop(cache);
+    assert_eq!(live.get(), 0);
+}
+
+#[cfg(feature = "allocator-api2")]
+#[test]
+fn test_try_insert_alloc_failure() {
+    use core::{alloc::Layout, cell::Cell, ptr::NonNull};
+    use std::rc::Rc;
+
+    use allocator_api2::alloc::{AllocError, Allocator, Global};
+
+    // Fails every allocation once its budget runs out.
+    #[derive(Clone)]
+    struct LimitedAlloc(Rc<Cell<usize>>);
+
+    unsafe impl Allocator for LimitedAlloc {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let budget = self.0.get();
+            if budget == 0 {
+                return Err(AllocError);
+            }
+            self.0.set(budget - 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    let budget = Rc::new(Cell::new(usize::MAX));
+    let mut map = LinkedHashMap::with_capacity_in(2, LimitedAlloc(budget.clone()));
+    assert_eq!(map.try_insert(1, 10), Ok(None));
+    assert_eq!(map.try_insert(2, 20), Ok(None));
+
+    budget.set(0);
+    assert!(map.try_insert(3, 30).is_err());
+    assert!(map.try_entry(3).is_err());
+    assert!(map.try_extend(vec![(3, 30)].into_iter()).is_err());
+    assert!(map.try_clone().is_err());
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [(1, 10), (2, 20)]
+    );
+
+    // Replacing existing entries never needs to allocate.
+    assert_eq!(map.try_insert(1, 11), Ok(Some(10)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+    match map.try_entry(2).unwrap() {
+        linked_hash_map::Entry::Occupied(mut entry) => *entry.get_mut() += 1,
+        linked_hash_map::Entry::Vacant(_) => panic!(),
+    }
+    assert_eq!(map[&2], 21);
+
+    // A failure partway through a multi-element extend leaves the map untouched, including the
+    // values and order of keys that would have been replaced.
+    let before = map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>();
+    budget.set(1);
+    assert!(map
+        .try_extend(vec![(2, 0), (3, 30), (4, 40), (1, 0), (5, 50)])
+        .is_err());
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        before
+    );
+    budget.set(1);
+    assert!(map
+        .try_extend((2..40).filter(|_| true).map(|i| (i, 0)))
+        .is_err());
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        before
+    );
+
+    budget.set(usize::MAX);
+    assert_eq!(map.try_insert(3, 30), Ok(None));
+    let cloned = map.try_clone().unwrap();
+    assert_eq!(map, cloned);
+
+    // Sorting allocates its scratch space with the map's allocator.
+    budget.set(10);
+    map.sort_by_key(|&k, _| k % 2);
+    assert_eq!(budget.get(), 9);
+    map.sort_by_cached_key(|&k, _| k);
+    assert_eq!(budget.get(), 7);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+    let mut set = hashlink::LinkedHashSet::with_capacity_in(1, LimitedAlloc(budget.clone()));
+    assert_eq!(set.try_insert(1), Ok(true));
+    budget.set(0);
+    assert!(set.try_extend(vec![1, 2].into_iter()).is_err());
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1]);
+    assert_eq!(set.try_insert(1), Ok(false));
+
+    budget.set(usize::MAX);
+    let mut cache = hashlink::LruCache::new_in(2, LimitedAlloc(budget.clone()));
+    assert_eq!(cache.try_insert(1, 10), Ok(None));
+    assert_eq!(cache.try_insert(2, 20), Ok(None));
+    budget.set(0);
+    assert!(cache.try_clone().is_err());
+    assert_eq!(cache.try_insert(1, 11), Ok(Some(10)));
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_try_insert() {
+    let mut map = LinkedHashMap::new();
+    assert_eq!(map.try_insert(1, 10), Ok(None));
+    assert_eq!(map.try_insert(2, 20), Ok(None));
+    assert_eq!(map.try_insert(1, 11), Ok(Some(10)));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+
+    *map.try_entry(3).unwrap().or_insert(0) += 30;
+    map.try_extend((4..6).map(|i| (i, i * 10))).unwrap();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1, 3, 4, 5]);
+
+    let cloned = map.try_clone().unwrap();
+    assert_eq!(map, cloned);
+    assert_eq!(
+        cloned.keys().copied().collect::<Vec<_>>(),
+        map.keys().copied().collect::<Vec<_>>()
+    );
+}
+
+#[cfg(feature = "indexed")]
+#[test]
+fn test_indexed() {
+    fn check(map: &LinkedHashMap<u32, u32>, model: &[u32]) {
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), model);
+        for (i, k) in model.iter().enumerate() {
+            assert_eq!(map.get_index(i), Some((k, &(k * 10))));
+            assert_eq!(map.index_of(k), Some(i));
+        }
+        assert_eq!(map.get_index(model.len()), None);
+    }
+
+    let mut map = LinkedHashMap::new();
+    let mut model = Vec::new();
+
+    // A small deterministic generator, so we exercise many interleavings of operations.
+    let mut seed = 0x2545_f491_u32;
+    let mut rand = move |n: u32| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed % n
+    };
+
+    for _ in 0..2000 {
+        match rand(11) {
+            0 | 1 => {
+                let k = rand(64);
+                map.insert(k, k * 10);
+                model.retain(|&m| m != k);
+                model.push(k);
+            }
+            2 => {
+                let k = rand(64);
+                map.remove(&k);
+                model.retain(|&m| m != k);
+            }
+            3 => {
+                let k = rand(64);
+                if map.to_front(&k).is_some() {
+                    model.retain(|&m| m != k);
+                    model.insert(0, k);
+                }
+            }
+            4 if !model.is_empty() => {
+                let len = model.len() as u32;
+                let (from, to) = (rand(len) as usize, rand(len) as usize);
+                map.move_index(from, to);
+                let k = model.remove(from);
+                model.insert(to, k);
+            }
+            5 if !model.is_empty() => {
+                let i = rand(model.len() as u32) as usize;
+                let k = model.remove(i);
+                assert_eq!(map.remove_index(i), Some((k, k * 10)));
+            }
+            6 => {
+                let m = rand(5) + 2;
+                map.retain(|&k, _| k % m != 0);
+                model.retain(|&k| k % m != 0);
+            }
+            7 => map.shrink_to_fit(),
+            8 => {
+                map.reverse();
+                model.reverse();
+            }
+            9 => {
+                let n = rand(model.len() as u32 + 1) as usize;
+                map.rotate_left(n);
+                model.rotate_left(n);
+            }
+            10 => {
+                let (a, b) = (rand(64), rand(64));
+                if let (Some(i), Some(j)) = (
+                    model.iter().position(|&k| k == a),
+                    model.iter().position(|&k| k == b),
+                ) {
+                    assert!(map.swap_positions(&a, &b));
+                    model.swap(i, j);
+                }
+            }
+            _ => {}
+        }
+        check(&map, &model);
+    }
+
+    if let Some((_, v)) = map.get_index_mut(0) {
+        *v += 1;
+    }
+    assert_eq!(map.front().map(|(_, v)| *v), Some(model[0] * 10 + 1));
+    assert_eq!(map.remove_index(model.len()), None);
+
+    map.clear();
+    assert_eq!(map.get_index(0), None);
+    map.insert(1, 10);
+    map.insert(2, 20);
+    check(&map, &[1, 2]);
+    map.drain();
+    map.insert(3, 30);
+    check(&map, &[3]);
+}
+
+#[test]
+fn test_sort() {
+    let mut map: LinkedHashMap<i32, &str> = vec![(3, "c"), (1, "b"), (4, "a"), (2, "b"), (5, "a")]
+        .into_iter()
+        .collect();
+
+    map.sort_keys();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+    assert_eq!(map.get(&3), Some(&"c"));
+
+    map.sort_by(|_, v1, _, v2| v1.cmp(v2));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [4, 5, 1, 2, 3]);
+
+    map.sort_by_key(|&k, _| -k);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [5, 4, 3, 2, 1]);
+
+    map.sort_unstable_by(|k1, _, k2, _| k1.cmp(k2));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+    let mut calls = 0;
+    map.sort_by_cached_key(|_, &v| {
+        calls += 1;
+        v
+    });
+    assert_eq!(calls, 5);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [4, 5, 1, 2, 3]);
+    assert_eq!(
+        map.iter().rev().map(|(&k, _)| k).collect::<Vec<_>>(),
+        [3, 2, 1, 5, 4]
+    );
+
+    map.insert(0, "d");
+    map.to_front(&3);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [3, 4, 5, 1, 2, 0]);
+    for k in 0..6 {
+        assert!(map.contains_key(&k));
+    }
+}
+
+#[test]
+fn test_sort_panic() {
+    let mut map: LinkedHashMap<i32, i32> = (0..10).rev().map(|i| (i, i)).collect();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.sort_by_key(|&k, _| {
+            if k == 3 {
+                panic!("panic in key function");
+            }
+            k
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        (0..10).rev().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_reverse_rotate() {
+    fn keys(map: &LinkedHashMap<i32, i32>) -> Vec<i32> {
+        let forward = map.keys().copied().collect::<Vec<_>>();
+        let mut backward = map.keys().rev().copied().collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        forward
+    }
+
+    let mut map: LinkedHashMap<i32, i32> = (0..5).map(|i| (i, i * 10)).collect();
+    map.reverse();
+    assert_eq!(keys(&map), [4, 3, 2, 1, 0]);
+    assert_eq!(map.front(), Some((&4, &40)));
+    assert_eq!(map.back(), Some((&0, &0)));
+
+    map.rotate_left(2);
+    assert_eq!(keys(&map), [2, 1, 0, 4, 3]);
+    map.rotate_left(4);
+    assert_eq!(keys(&map), [3, 2, 1, 0, 4]);
+    map.rotate_right(1);
+    assert_eq!(keys(&map), [4, 3, 2, 1, 0]);
+    map.rotate_left(0);
+    map.rotate_right(5);
+    assert_eq!(keys(&map), [4, 3, 2, 1, 0]);
+
+    map.insert(5, 50);
+    assert_eq!(keys(&map), [4, 3, 2, 1, 0, 5]);
+    assert_eq!(map.pop_front(), Some((4, 40)));
+    assert_eq!(map.get(&2), Some(&20));
+
+    let mut empty = LinkedHashMap::<i32, i32>::new();
+    empty.reverse();
+    empty.rotate_left(0);
+    assert!(empty.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_rotate_out_of_bounds() {
+    let mut map: LinkedHashMap<i32, i32> = (0..5).map(|i| (i, i)).collect();
+    map.rotate_left(6);
+}
+
+#[test]
+fn test_swap_and_move() {
+    let mut map: LinkedHashMap<i32, i32> = (0..5).map(|i| (i, i * 10)).collect();
+    let keys = |map: &LinkedHashMap<i32, i32>| map.keys().copied().collect::<Vec<_>>();
+
+    assert!(map.swap_positions(&0, &4));
+    assert_eq!(keys(&map), [4, 1, 2, 3, 0]);
+    assert!(map.swap_positions(&1, &2));
+    assert_eq!(keys(&map), [4, 2, 1, 3, 0]);
+    assert!(map.swap_positions(&1, &2));
+    assert_eq!(keys(&map), [4, 1, 2, 3, 0]);
+    assert!(map.swap_positions(&3, &3));
+    assert!(!map.swap_positions(&3, &7));
+    assert_eq!(keys(&map), [4, 1, 2, 3, 0]);
+
+    assert_eq!(map.move_before(&0, &1), Some(&mut 0));
+    assert_eq!(keys(&map), [4, 0, 1, 2, 3]);
+    assert_eq!(map.move_after(&4, &3), Some(&mut 40));
+    assert_eq!(keys(&map), [0, 1, 2, 3, 4]);
+    assert_eq!(map.move_after(&2, &1), Some(&mut 20));
+    assert_eq!(keys(&map), [0, 1, 2, 3, 4]);
+    assert_eq!(map.move_before(&3, &1), Some(&mut 30));
+    assert_eq!(keys(&map), [0, 3, 1, 2, 4]);
+    assert_eq!(map.move_before(&3, &3), Some(&mut 30));
+    assert_eq!(map.move_before(&3, &9), None);
+    assert_eq!(map.move_after(&9, &3), None);
+    assert_eq!(keys(&map), [0, 3, 1, 2, 4]);
+    assert_eq!(map.back(), Some((&4, &40)));
+}
+
+#[test]
+fn test_append_prepend() {
+    use hashlink::linked_hash_map::CollisionPolicy;
+
+    fn pairs(map: &LinkedHashMap<i32, &'static str>) -> Vec<(i32, &'static str)> {
+        map.iter().map(|(&k, &v)| (k, v)).collect()
+    }
+
+    fn maps() -> (
+        LinkedHashMap<i32, &'static str>,
+        LinkedHashMap<i32, &'static str>,
+    ) {
+        (
+            vec![(1, "a"), (2, "a"), (3, "a")].into_iter().collect(),
+            vec![(4, "b"), (2, "b"), (5, "b")].into_iter().collect(),
+        )
+    }
+
+    let (mut ours, mut theirs) = maps();
+    ours.append(&mut theirs, CollisionPolicy::KeepOurs);
+    assert!(theirs.is_empty());
+    assert_eq!(
+        pairs(&ours),
+        [(1, "a"), (2, "a"), (3, "a"), (4, "b"), (5, "b")]
+    );
+
+    let (mut ours, mut theirs) = maps();
+    ours.append(&mut theirs, CollisionPolicy::TakeTheirs);
+    assert_eq!(
+        pairs(&ours),
+        [(1, "a"), (2, "b"), (3, "a"), (4, "b"), (5, "b")]
+    );
+
+    let (mut ours, mut theirs) = maps();
+    ours.append(&mut theirs, CollisionPolicy::TakeTheirsAndMove);
+    assert_eq!(
+        pairs(&ours),
+        [(1, "a"), (3, "a"), (4, "b"), (2, "b"), (5, "b")]
+    );
+
+    let (mut ours, mut theirs) = maps();
+    ours.prepend(&mut theirs, CollisionPolicy::KeepOurs);
+    assert!(theirs.is_empty());
+    assert_eq!(
+        pairs(&ours),
+        [(4, "b"), (5, "b"), (1, "a"), (2, "a"), (3, "a")]
+    );
+
+    let (mut ours, mut theirs) = maps();
+    ours.prepend(&mut theirs, CollisionPolicy::TakeTheirs);
+    assert_eq!(
+        pairs(&ours),
+        [(4, "b"), (5, "b"), (1, "a"), (2, "b"), (3, "a")]
+    );
+
+    let (mut ours, mut theirs) = maps();
+    ours.prepend(&mut theirs, CollisionPolicy::TakeTheirsAndMove);
+    assert_eq!(
+        pairs(&ours),
+        [(4, "b"), (2, "b"), (5, "b"), (1, "a"), (3, "a")]
+    );
+
+    // Moving the entries already at the ends of the list.
+    let (mut ours, _) = maps();
+    let mut theirs = vec![(3, "b"), (6, "b")].into_iter().collect();
+    ours.append(&mut theirs, CollisionPolicy::TakeTheirsAndMove);
+    assert_eq!(pairs(&ours), [(1, "a"), (2, "a"), (3, "b"), (6, "b")]);
+    let mut theirs = vec![(6, "c"), (1, "c"), (3, "c")].into_iter().collect();
+    ours.prepend(&mut theirs, CollisionPolicy::TakeTheirsAndMove);
+    assert_eq!(pairs(&ours), [(6, "c"), (1, "c"), (3, "c"), (2, "a")]);
+
+    let mut empty = LinkedHashMap::new();
+    let (mut ours, _) = maps();
+    empty.prepend(&mut ours, CollisionPolicy::KeepOurs);
+    assert_eq!(pairs(&empty), [(1, "a"), (2, "a"), (3, "a")]);
+    empty.append(&mut ours, CollisionPolicy::KeepOurs);
+    assert_eq!(empty.len(), 3);
+}
+
+#[test]
+fn test_split_off() {
+    let mut map: LinkedHashMap<i32, i32> = (0..10).map(|i| (i, i * 10)).collect();
+
+    assert!(map.split_off(&20).is_none());
+    assert_eq!(map.len(), 10);
+
+    let back = map.split_off(&6).unwrap();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+    assert_eq!(back.keys().copied().collect::<Vec<_>>(), [6, 7, 8, 9]);
+    assert_eq!(back.get(&7), Some(&70));
+    assert!(!map.contains_key(&7));
+
+    let front = map.split_off_front(2);
+    assert_eq!(front.keys().copied().collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 3, 4, 5]);
+    assert_eq!(front.get(&1), Some(&10));
+    assert!(!map.contains_key(&0));
+
+    let all = map.split_off(&2).unwrap();
+    assert!(map.is_empty());
+    assert_eq!(all.keys().copied().collect::<Vec<_>>(), [2, 3, 4, 5]);
+
+    map.insert(11, 110);
+    assert_eq!(map.split_off_front(0).len(), 0);
+    assert_eq!(map.split_off_front(1).len(), 1);
+    assert!(map.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_split_off_front_out_of_bounds() {
+    let mut map: LinkedHashMap<i32, i32> = (0..3).map(|i| (i, i)).collect();
+    map.split_off_front(4);
+}
+
+#[test]
+fn test_cursor() {
+    let map: LinkedHashMap<i32, i32> = (1..=3).map(|i| (i, i * 10)).collect();
+
+    let mut c = map.cursor_front();
+    assert_eq!(c.current(), Some((&1, &10)));
+    assert_eq!(c.peek_prev(), None);
+    assert_eq!(c.peek_next(), Some((&2, &20)));
+    c.move_next();
+    let saved = c;
+    c.move_next();
+    assert_eq!(c.current(), Some((&3, &30)));
+    c.move_next();
+    assert_eq!(c.current(), None);
+    c.move_next();
+    assert_eq!(c.current(), Some((&1, &10)));
+    assert_eq!(saved.current(), Some((&2, &20)));
+
+    let mut c = map.cursor_back();
+    assert_eq!(c.current(), Some((&3, &30)));
+    c.move_prev();
+    c.move_prev();
+    assert_eq!(c.current(), Some((&1, &10)));
+    c.move_prev();
+    assert_eq!(c.current(), None);
+    assert_eq!(c.peek_prev(), Some((&3, &30)));
+
+    let c = map.cursor_at(&2).unwrap();
+    assert_eq!(c.peek_prev(), Some((&1, &10)));
+    assert_eq!(c.current(), Some((&2, &20)));
+    assert_eq!(c.peek_next(), Some((&3, &30)));
+    assert!(map.cursor_at(&4).is_none());
+
+    let empty = LinkedHashMap::<i32, i32>::new();
+    let mut c = empty.cursor_front();
+    assert_eq!(c.current(), None);
+    c.move_prev();
+    assert_eq!(c.peek_next(), None);
+    assert_eq!(empty.cursor_back().current(), None);
+    assert!(empty.cursor_at(&1).is_none());
+}
+
+#[test]
+fn test_cursor_at_mut() {
+    let mut map: LinkedHashMap<i32, i32> = (1..=3).map(|i| (i, i * 10)).collect();
+    assert!(map.cursor_at_mut(&4).is_none());
+
+    let mut c = map.cursor_at_mut(&2).unwrap();
+    assert_eq!(c.current(), Some((&2, &mut 20)));
+    if let Some((_, v)) = c.current() {
+        *v = 25;
+    }
+    c.insert_after(4, 40);
+    c.move_prev();
+    c.insert_before(0, 0);
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [(0, 0), (1, 10), (2, 25), (4, 40), (3, 30)]
+    );
+
+    let mut empty = LinkedHashMap::<i32, i32>::new();
+    assert!(empty.cursor_at_mut(&1).is_none());
+}
+
+#[test]
+fn test_cursor_mut_remove_current() {
+    let mut map: LinkedHashMap<i32, i32> = (1..=4).map(|i| (i, i * 10)).collect();
+
+    let mut c = map.cursor_at_mut(&2).unwrap();
+    assert_eq!(c.remove_current(), Some((2, 20)));
+    assert_eq!(c.current(), Some((&3, &mut 30)));
+    assert_eq!(c.remove_current_and_move_prev(), Some((3, 30)));
+    assert_eq!(c.current(), Some((&1, &mut 10)));
+    c.move_prev();
+    assert_eq!(c.remove_current(), None);
+    assert_eq!(c.current(), None);
+
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 4]);
+    assert!(!map.contains_key(&2));
+    assert!(!map.contains_key(&3));
+    map.insert(5, 50);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 4, 5]);
+
+    // Remove everything while walking the list, as when evicting from an LRU list.
+    let mut c = map.cursor_front_mut();
+    while c.remove_current().is_some() {}
+    assert!(map.is_empty());
+    assert_eq!(map.iter().next(), None);
+}
+
+#[test]
+fn test_cursor_mut_replace_key() {
+    let mut map: LinkedHashMap<String, i32> = vec![("a".to_owned(), 1), ("b".to_owned(), 2)]
+        .into_iter()
+        .collect();
+
+    let mut c = map.cursor_front_mut();
+    assert_eq!(c.replace_key("c".to_owned()), Ok("a".to_owned()));
+    assert_eq!(c.replace_key("b".to_owned()), Err("b".to_owned()));
+    assert_eq!(c.replace_key("c".to_owned()), Ok("c".to_owned()));
+    c.move_prev();
+    assert_eq!(c.replace_key("d".to_owned()), Err("d".to_owned()));
+
+    assert_eq!(
+        map.iter()
+            .map(|(k, &v)| (k.as_str(), v))
+            .collect::<Vec<_>>(),
+        [("c", 1), ("b", 2)]
+    );
+    assert_eq!(map.get("c"), Some(&1));
+    assert_eq!(map.get("a"), None);
+    map.insert("a".to_owned(), 3);
+    assert_eq!(map.len(), 3);
+}
+
+#[test]
+fn test_cursor_mut_swap() {
+    let mut map: LinkedHashMap<i32, i32> = (1..=3).map(|i| (i, i)).collect();
+
+    let mut c = map.cursor_front_mut();
+    assert!(!c.swap_with_prev());
+    assert!(c.swap_with_next());
+    assert_eq!(c.current(), Some((&1, &mut 1)));
+    assert!(c.swap_with_next());
+    assert!(!c.swap_with_next());
+    assert!(c.swap_with_prev());
+    c.move_next();
+    c.move_next();
+    assert_eq!(c.current(), None);
+    assert!(!c.swap_with_next());
+    assert!(!c.swap_with_prev());
+
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1, 3]);
+    assert_eq!(map.keys().rev().copied().collect::<Vec<_>>(), [3, 1, 2]);
+}
+
+#[test]
+fn test_cursor_mut_splice_and_split() {
+    let mut map: LinkedHashMap<i32, i32> = (1..=4).map(|i| (i, i)).collect();
+    let other: LinkedHashMap<i32, i32> = vec![(10, 10), (3, 30), (11, 11)].into_iter().collect();
+
+    let mut c = map.cursor_at_mut(&2).unwrap();
+    c.splice_after(other);
+    assert_eq!(c.current(), Some((&2, &mut 2)));
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [(1, 1), (2, 2), (10, 10), (3, 30), (11, 11), (4, 4)]
+    );
+
+    let mut c = map.cursor_at_mut(&3).unwrap();
+    let tail = c.split_after();
+    assert_eq!(c.current(), Some((&3, &mut 30)));
+    assert_eq!(c.peek_next(), None);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 10, 3]);
+    assert_eq!(tail.keys().copied().collect::<Vec<_>>(), [11, 4]);
+    assert_eq!(tail.get(&11), Some(&11));
+    assert!(!map.contains_key(&4));
+
+    let mut c = map.cursor_back_mut();
+    c.move_next();
+    c.splice_after(tail);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        [11, 4, 1, 2, 10, 3]
+    );
+
+    let mut c = map.cursor_back_mut();
+    c.move_next();
+    let all = c.split_after();
+    assert!(map.is_empty());
+    assert_eq!(all.len(), 6);
+}
+
+#[test]
+fn test_range() {
+    let map: LinkedHashMap<i32, i32> = (0..6).map(|i| (i, i * 10)).collect();
+    let keys = |r: linked_hash_map::Range<'_, i32, i32>| r.map(|(&k, _)| k).collect::<Vec<_>>();
+
+    assert_eq!(keys(map.iter_from(&3).unwrap()), [3, 4, 5]);
+    assert_eq!(keys(map.iter_from(&0).unwrap()), [0, 1, 2, 3, 4, 5]);
+    assert_eq!(keys(map.iter_until(&3).unwrap()), [0, 1, 2]);
+    assert_eq!(keys(map.iter_until(&0).unwrap()), []);
+    assert_eq!(keys(map.range(&1, &4).unwrap()), [1, 2, 3]);
+    assert_eq!(keys(map.range(&2, &2).unwrap()), []);
+    assert_eq!(keys(map.range(&2, &3).unwrap()), [2]);
+    assert!(map.iter_from(&6).is_none());
+    assert!(map.iter_until(&6).is_none());
+    assert!(map.range(&1, &6).is_none());
+
+    assert_eq!(
+        map.range(&1, &5)
+            .unwrap()
+            .rev()
+            .map(|(&k, _)| k)
+            .collect::<Vec<_>>(),
+        [4, 3, 2, 1]
+    );
+    let mut r = map.range(&1, &5).unwrap();
+    assert_eq!(r.next(), Some((&1, &10)));
+    assert_eq!(r.next_back(), Some((&4, &40)));
+    assert_eq!(r.next(), Some((&2, &20)));
+    assert_eq!(r.next_back(), Some((&3, &30)));
+    assert_eq!(r.next(), None);
+    assert_eq!(r.next_back(), None);
+
+    // Out of order ranges are empty.
+    assert_eq!(keys(map.range(&4, &1).unwrap()), []);
+    assert_eq!(keys(map.range(&1, &0).unwrap()), []);
+    assert_eq!(keys(map.range(&5, &4).unwrap()), []);
+    assert_eq!(keys(map.range(&5, &0).unwrap()), []);
+    let mut r = map.range(&4, &2).unwrap();
+    assert_eq!(r.next_back(), None);
+    assert_eq!(r.next(), None);
+}
+
+#[test]
+fn test_range_mut() {
+    let mut map: LinkedHashMap<i32, i32> = (0..6).map(|i| (i, i)).collect();
+
+    for (_, v) in map.iter_from_mut(&4).unwrap() {
+        *v += 100;
+    }
+    for (_, v) in map.iter_until_mut(&1).unwrap() {
+        *v += 200;
+    }
+    for (_, v) in map.range_mut(&2, &4).unwrap().rev() {
+        *v += 300;
+    }
+    assert!(map.range_mut(&2, &9).is_none());
+    assert_eq!(map.range_mut(&4, &2).unwrap().count(), 0);
+    assert_eq!(
+        map.values().copied().collect::<Vec<_>>(),
+        [200, 1, 302, 303, 104, 105]
+    );
+}
+
+#[test]
+fn test_drain_range() {
+    let mut map: LinkedHashMap<i32, i32> = (0..8).map(|i| (i, i * 10)).collect();
+
+    assert!(map.drain_range(&2, &9).is_none());
+    assert_eq!(map.len(), 8);
+
+    assert_eq!(
+        map.drain_range(&2, &5).unwrap().collect::<Vec<_>>(),
+        [(2, 20), (3, 30), (4, 40)]
+    );
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 5, 6, 7]);
+    assert!(!map.contains_key(&3));
+
+    // Entries are removed even if the iterator is dropped early.
+    {
+        let mut drain = map.drain_range(&5, &7).unwrap();
+        assert_eq!(drain.next_back(), Some((6, 60)));
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 7]);
+    assert_eq!(map.drain_range(&1, &1).unwrap().count(), 0);
+
+    // Out of order ranges drain nothing.
+    assert_eq!(map.drain_range(&1, &0).unwrap().count(), 0);
+    assert_eq!(map.drain_range(&7, &1).unwrap().count(), 0);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 7]);
+
+    assert_eq!(
+        map.drain_range(&1, &7).unwrap().collect::<Vec<_>>(),
+        [(1, 10)]
+    );
+    map.insert(8, 80);
+    map.insert(9, 90);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 7, 8, 9]);
+    assert_eq!(map.get(&9), Some(&90));
+}
+
+#[test]
+fn test_extract_if() {
+    let mut map: LinkedHashMap<i32, i32> = (0..8).map(|i| (i, i * 10)).collect();
+
+    assert_eq!(
+        map.extract_if(|&k, v| {
+            *v += 1;
+            k % 2 == 0
+        })
+        .collect::<Vec<_>>(),
+        [(0, 1), (2, 21), (4, 41), (6, 61)]
+    );
+    assert_eq!(
+        map.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [(1, 11), (3, 31), (5, 51), (7, 71)]
+    );
+    assert!(!map.contains_key(&4));
+
+    // Only the entries yielded before the iterator is dropped are removed.
+    {
+        let mut iter = map.extract_if(|_, _| true);
+        assert_eq!(iter.next(), Some((1, 11)));
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [3, 5, 7]);
+
+    assert_eq!(map.extract_if(|_, _| false).count(), 0);
+    map.insert(8, 80);
+    map.insert(3, 30);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [5, 7, 8, 3]);
+    assert_eq!(map.extract_if(|_, _| true).count(), 4);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_get_many_mut() {
+    let mut map: LinkedHashMap<String, i32> = LinkedHashMap::new();
+    map.insert("a".to_owned(), 10);
+    map.insert("b".to_owned(), 20);
+    map.insert("c".to_owned(), 30);
+
+    {
+        let [a, c] = map.get_many_mut(["a", "c"]).unwrap();
+        *a -= 5;
+        *c += 5;
+    }
+    assert_eq!(map["a"], 5);
+    assert_eq!(map["c"], 35);
+
+    assert!(map.get_many_mut(["a", "d"]).is_none());
+    assert!(map.get_many_mut(["b", "a", "b"]).is_none());
+    assert_eq!(map.get_many_mut::<str, 0>([]), Some([]));
+
+    let [b, a] = unsafe { map.get_many_unchecked_mut(["b", "a"]) }.unwrap();
+    std::mem::swap(a, b);
+    assert_eq!(
+        map.iter()
+            .map(|(k, &v)| (k.as_str(), v))
+            .collect::<Vec<_>>(),
+        [("a", 20), ("b", 5), ("c", 35)]
+    );
+    assert!(unsafe { map.get_many_unchecked_mut(["z"]) }.is_none());
+}
+
+#[test]
+fn test_entry_api() {
+    let mut map: LinkedHashMap<i32, Vec<i32>> = LinkedHashMap::new();
+
+    map.entry(1).or_default().push(1);
+    map.entry(2).or_insert_with_key(|&k| vec![k * 10]).push(2);
+    map.entry(1).or_default().push(3);
+    assert_eq!(
+        map.iter().map(|(&k, v)| (k, v.clone())).collect::<Vec<_>>(),
+        [(2, vec![20, 2]), (1, vec![1, 3])]
+    );
+
+    let mut entry = map.entry(2);
+    entry.to_back();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2]);
+    let mut entry = map.entry(2);
+    entry.to_front();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+    map.entry(5).to_front();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+
+    match map.entry(3) {
+        linked_hash_map::Entry::Vacant(entry) => entry.insert_front(vec![3]),
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+    };
+    match map.entry(4) {
+        linked_hash_map::Entry::Vacant(entry) => {
+            let mut occupied = entry.insert_entry(vec![4]);
+            assert_eq!(occupied.key(), &4);
+            occupied.get_mut().push(40);
+            occupied.to_front();
+        }
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [4, 3, 2, 1]);
+    assert_eq!(map[&4], [4, 40]);
+
+    match map.entry(6) {
+        linked_hash_map::Entry::Vacant(entry) => {
+            assert_eq!(entry.insert_before(&2, vec![6]).unwrap(), &[6]);
+        }
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [4, 3, 6, 2, 1]);
+
+    match map.entry(7) {
+        linked_hash_map::Entry::Vacant(entry) => {
+            let (entry, value) = entry.insert_before(&8, vec![7]).unwrap_err();
+            assert_eq!(entry.key(), &7);
+            entry.insert(value);
+        }
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [4, 3, 6, 2, 1, 7]);
+
+    let mut empty: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    match empty.entry(1) {
+        linked_hash_map::Entry::Vacant(entry) => *entry.insert_front(1) += 1,
+        linked_hash_map::Entry::Occupied(_) => panic!(),
+    }
+    assert_eq!(empty[&1], 2);
+}
+
+#[test]
+fn test_inserted_entry() {
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    map.insert(1, 1);
+    if let linked_hash_map::Entry::Vacant(entry) = map.entry(2) {
+        let mut entry = entry.insert_entry(2);
+        entry.to_front();
+        assert_eq!(entry.insert(20), 2);
+        assert_eq!(
+            format!("{:?}", entry),
+            "OccupiedEntry { key: 2, value: 20 }"
+        );
+
+        // There is no other key to replace the inserted one with.
+        let (entry, value) = entry.insert_entry(21).unwrap_err();
+        assert_eq!(value, 21);
+        let entry = entry.replace_key().unwrap_err();
+        assert_eq!(entry.get(), &20);
+    }
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &1), (&2, &20)]);
+
+    if let linked_hash_map::Entry::Vacant(entry) = map.entry(3) {
+        assert_eq!(entry.insert_entry(3).remove_entry(), (3, 3));
+    }
+    assert_eq!(map.len(), 2);
+
+    match map.entry(1) {
+        linked_hash_map::Entry::Occupied(entry) => {
+            assert_eq!(entry.insert_entry(10).unwrap(), (1, 1));
+        }
+        linked_hash_map::Entry::Vacant(_) => panic!(),
+    }
+    match map.entry(2) {
+        linked_hash_map::Entry::Occupied(entry) => assert_eq!(entry.replace_key().unwrap(), 2),
+        linked_hash_map::Entry::Vacant(_) => panic!(),
+    }
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&2, &20), (&1, &10)]);
+}
+
+#[test]
+fn test_entry_ref() {
+    let mut map: LinkedHashMap<String, usize> = LinkedHashMap::new();
+
+    for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+        *map.entry_ref(word).or_default() += 1;
+    }
+    assert_eq!(map.len(), 8);
+    assert_eq!(map["the"], 2);
+    // Occupied entries are moved to the back, just like with `entry`.
+    assert_eq!(map.keys().nth(5).map(String::as_str), Some("the"));
+
+    assert_eq!(map.entry_ref("fox").key(), "fox");
+    assert_eq!(map.entry_ref("cat").key(), "cat");
+    map.entry_ref("fox").and_modify(|v| *v = 10).to_front();
+    assert_eq!(map.front(), Some((&"fox".to_owned(), &10)));
+    map.entry_ref("cat").and_modify(|v| *v = 10).to_front();
+    assert!(!map.contains_key("cat"));
+
+    assert_eq!(*map.entry_ref("cat").or_insert_with_key(|k| k.len()), 3);
+    assert_eq!(map.back(), Some((&"cat".to_owned(), &3)));
+
+    match map.entry_ref("emu") {
+        linked_hash_map::EntryRef::Vacant(entry) => {
+            assert_eq!(entry.key(), "emu");
+            entry.insert_front(7);
+        }
+        linked_hash_map::EntryRef::Occupied(_) => panic!(),
+    }
+    assert_eq!(map.front(), Some((&"emu".to_owned(), &7)));
+
+    match map.entry_ref("gnu") {
+        linked_hash_map::EntryRef::Vacant(entry) => {
+            let mut entry = entry.insert_entry(1);
+            assert_eq!(entry.key(), "gnu");
+            entry.to_front();
+        }
+        linked_hash_map::EntryRef::Occupied(_) => panic!(),
+    }
+    assert_eq!(map.front(), Some((&"gnu".to_owned(), &1)));
+
+    match map.entry_ref("gnu") {
+        linked_hash_map::EntryRef::Occupied(entry) => assert_eq!(entry.remove_entry().1, 1),
+        linked_hash_map::EntryRef::Vacant(_) => panic!(),
+    }
+    assert!(!map.contains_key("gnu"));
+    assert_eq!(*map.entry_ref("emu").or_insert(0), 7);
+    assert_eq!(map.back(), Some((&"emu".to_owned(), &7)));
+}
+
+#[test]
+fn test_occupied_entry_ref_replace() {
+    let mut map: LinkedHashMap<String, usize> = LinkedHashMap::new();
+    map.insert("a".to_owned(), 1);
+    map.insert("b".to_owned(), 2);
+
+    match map.entry_ref("a") {
+        linked_hash_map::EntryRef::Occupied(entry) => {
+            assert_eq!(entry.lookup_key(), "a");
+            assert_eq!(entry.replace_entry(10), ("a".to_owned(), 1));
+        }
+        linked_hash_map::EntryRef::Vacant(_) => panic!(),
+    }
+    assert_eq!(map.front(), Some((&"a".to_owned(), &10)));
+
+    match map.entry_ref("a") {
+        linked_hash_map::EntryRef::Occupied(entry) => {
+            assert_eq!(entry.insert_entry(11), ("a".to_owned(), 10));
+        }
+        linked_hash_map::EntryRef::Vacant(_) => panic!(),
+    }
+    assert_eq!(map.back(), Some((&"a".to_owned(), &11)));
+
+    match map.entry_ref("b") {
+        linked_hash_map::EntryRef::Occupied(entry) => assert_eq!(entry.replace_key(), "b"),
+        linked_hash_map::EntryRef::Vacant(_) => panic!(),
+    }
+    assert_eq!(map["b"], 2);
+}
+
+#[test]
+fn test_equivalent() {
+    use hashlink::Equivalent;
+
+    // Looks up a `(String, u32)` key with a `(&str, u32)`, which `Borrow` cannot express.
+    #[derive(Hash)]
+    struct Pair<'a>(&'a str, u32);
+
+    impl Equivalent<(String, u32)> for Pair<'_> {
+        fn equivalent(&self, key: &(String, u32)) -> bool {
+            self.0 == key.0 && self.1 == key.1
+        }
+    }
+
+    let mut map: LinkedHashMap<(String, u32), i32> = LinkedHashMap::new();
+    map.insert(("a".to_owned(), 1), 10);
+    map.insert(("a".to_owned(), 2), 20);
+    map.insert(("b".to_owned(), 1), 30);
+
+    assert_eq!(map.get(&Pair("a", 2)), Some(&20));
+    assert_eq!(map[&Pair("b", 1)], 30);
+    assert!(!map.contains_key(&Pair("b", 2)));
+
+    *map.get_mut(&Pair("a", 1)).unwrap() += 1;
+    assert!(map.to_front(&Pair("b", 1)).is_some());
+    assert_eq!(map.remove(&Pair("a", 2)), Some(20));
+    assert_eq!(
+        map.iter()
+            .map(|(k, &v)| (k.0.as_str(), k.1, v))
+            .collect::<Vec<_>>(),
+        [("b", 1, 30), ("a", 1, 11)]
+    );
+
+    // Ordinary `Borrow` lookups still work through the blanket impl.
+    let mut map: LinkedHashMap<String, i32> = LinkedHashMap::new();
+    map.insert("a".to_owned(), 1);
+    assert_eq!(map.get("a"), Some(&1));
+}
+
+#[test]
+fn test_free_list() {
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    assert_eq!(map.free_nodes(), 0);
+    assert_eq!(map.allocated_bytes(), 0);
+
+    map.extend((0..10).map(|i| (i, i)));
+    assert_eq!(map.free_nodes(), 0);
+    let bytes = map.allocated_bytes();
+    assert!(bytes > 0);
+
+    map.retain(|&k, _| k % 2 == 0);
+    assert_eq!(map.free_nodes(), 5);
+    assert_eq!(map.allocated_bytes(), bytes);
+
+    // Inserting reuses free nodes.
+    map.insert(10, 10);
+    assert_eq!(map.free_nodes(), 4);
+
+    map.shrink_free_list(2);
+    assert_eq!(map.free_nodes(), 2);
+    assert!(map.allocated_bytes() < bytes);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 4, 6, 8, 10]);
+    map.shrink_free_list(5);
+    assert_eq!(map.free_nodes(), 2);
+
+    map.reserve_nodes(8);
+    assert_eq!(map.free_nodes(), 8);
+    map.reserve(8);
+    let bytes = map.allocated_bytes();
+    for i in 11..19 {
+        map.insert(i, i);
+    }
+    assert_eq!(map.free_nodes(), 0);
+    assert_eq!(map.allocated_bytes(), bytes);
+    assert_eq!(map.len(), 14);
+
+    map.retain(|&k, _| k < 4);
+    map.shrink_to(6);
+    assert_eq!(map.free_nodes(), 0);
+    assert!(map.capacity() >= 6);
+    assert!(map.allocated_bytes() < bytes);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2]);
+    map.insert(1, 1);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 1]);
+
+    map.clear();
+    map.shrink_to(0);
+    assert_eq!(map.allocated_bytes(), 0);
+
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    map.reserve_nodes(3);
+    assert_eq!(map.free_nodes(), 3);
+    map.shrink_free_list(0);
+    assert_eq!(map.free_nodes(), 0);
+    map.insert(1, 1);
+    assert_eq!(map[&1], 1);
+
+    // Clearing releases the free nodes, but not their memory.
+    map.reserve_nodes(4);
+    map.reserve(4);
+    let bytes = map.allocated_bytes();
+    map.clear();
+    assert_eq!(map.free_nodes(), 0);
+    assert_eq!(map.allocated_bytes(), bytes);
+    for i in 0..5 {
+        map.insert(i, i);
+    }
+    assert_eq!(map.allocated_bytes(), bytes);
+}
+
+#[test]
+fn test_rehash_counts() {
+    use std::{
+        cell::Cell,
+        collections::hash_map::DefaultHasher,
+        hash::{BuildHasher, Hasher},
+        rc::Rc,
+    };
+
+    #[derive(Clone, Default)]
+    struct CountingState(Rc<Cell<usize>>);
+
+    struct CountingHasher(DefaultHasher, Rc<Cell<usize>>);
+
+    impl Hasher for CountingHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.write(bytes)
+        }
+
+        fn finish(&self) -> u64 {
+            self.1.set(self.1.get() + 1);
+            self.0.finish()
+        }
+    }
+
+    impl BuildHasher for CountingState {
+        type Hasher = CountingHasher;
+
+        fn build_hasher(&self) -> CountingHasher {
+            CountingHasher(DefaultHasher::new(), self.0.clone())
+        }
+    }
+
+    let state = CountingState::default();
+    let hashes = state.0.clone();
+    let mut map = LinkedHashMap::with_hasher(state);
+    for i in 0..1000 {
+        map.insert(i.to_string(), i);
+    }
+    let inserted = hashes.get();
+
+    map.reserve(10_000);
+    map.retain_with_order(|_, &mut v| v % 3 != 0);
+    map.shrink_to_fit();
+
+    // With cached hashes, keys are only hashed when they are inserted or looked up.
+    #[cfg(feature = "cached-hashes")]
+    assert_eq!(hashes.get(), inserted);
+    #[cfg(not(feature = "cached-hashes"))]
+    assert!(hashes.get() > inserted);
+
+    assert_eq!(map.len(), 666);
+    assert_eq!(map.get("998"), Some(&998));
+    assert!(!map.contains_key("999"));
+}
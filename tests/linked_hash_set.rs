@@ -1,3 +1,5 @@
+#![cfg(feature = "alloc")]
+
 use hashlink::linked_hash_set::{self, LinkedHashSet};
 use hashlink::DefaultHashBuilder;
 
@@ -547,3 +549,44 @@ fn test_order_equality() {
     set2.to_front(&4);
     assert_eq!(set1, set2);
 }
+
+#[test]
+fn test_sort() {
+    let mut set: LinkedHashSet<i32> = vec![3, 1, 4, 2, 5].into_iter().collect();
+
+    set.sort();
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+    set.sort_by(|a, b| b.cmp(a));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [5, 4, 3, 2, 1]);
+
+    set.sort_by_key(|&v| v % 2);
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [4, 2, 5, 3, 1]);
+
+    set.sort_unstable_by(|a, b| a.cmp(b));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+    set.sort_by_cached_key(|&v| (v - 3).abs());
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [3, 2, 4, 1, 5]);
+    assert!(set.contains(&5));
+}
+
+#[test]
+fn test_extract_if() {
+    let mut set: LinkedHashSet<i32> = (0..8).collect();
+
+    assert_eq!(
+        set.extract_if(|&v| v % 3 == 0).collect::<Vec<_>>(),
+        [0, 3, 6]
+    );
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2, 4, 5, 7]);
+
+    {
+        let mut iter = set.extract_if(|&v| v > 1);
+        assert_eq!(iter.next(), Some(2));
+    }
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 4, 5, 7]);
+
+    assert!(set.insert(2));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 4, 5, 7, 2]);
+}
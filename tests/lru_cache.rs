@@ -1,4 +1,8 @@
-use hashlink::LruCache;
+#![cfg(feature = "alloc")]
+
+use std::{cell::RefCell, rc::Rc};
+
+use hashlink::{lru_cache::EvictionCause, LruCache};
 
 #[test]
 fn test_put_and_get() {
@@ -164,3 +168,291 @@ fn test_entry() {
 
     assert!(cache.len() <= 5);
 }
+
+#[test]
+fn test_extract_if() {
+    let mut cache = LruCache::new(4);
+
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    cache.insert(4, 40);
+    assert_eq!(cache.get(&1), Some(&10));
+
+    assert_eq!(
+        cache.extract_if(|_, v| *v >= 30).collect::<Vec<_>>(),
+        [(3, 30), (4, 40)]
+    );
+    assert_eq!(cache.len(), 2);
+
+    // Extraction does not change the recency order of the remaining entries.
+    assert_eq!(cache.extract_if(|_, _| false).count(), 0);
+    cache.insert(5, 50);
+    cache.insert(6, 60);
+    cache.insert(7, 70);
+    assert!(!cache.contains_key(&2));
+    assert_eq!(cache.remove_lru(), Some((1, 10)));
+}
+
+#[test]
+fn test_get_many_mut() {
+    let mut cache = LruCache::new(4);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    cache.insert(4, 40);
+
+    // A failed lookup leaves the LRU order untouched.
+    assert!(cache.get_many_mut([&1, &5]).is_none());
+    assert!(cache.get_many_mut([&1, &1]).is_none());
+
+    {
+        let [a, b] = cache.get_many_mut([&2, &1]).unwrap();
+        *a += 1;
+        *b += 1;
+    }
+    assert_eq!(
+        cache.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [(3, 30), (4, 40), (2, 21), (1, 11)]
+    );
+
+    let [c] = unsafe { cache.get_many_unchecked_mut([&3]) }.unwrap();
+    *c = 0;
+    cache.insert(5, 50);
+    assert_eq!(cache.remove_lru(), Some((2, 21)));
+    assert_eq!(cache.peek(&3), Some(&0));
+}
+
+#[test]
+fn test_entry_ref() {
+    let mut cache: LruCache<String, i32> = LruCache::new(2);
+
+    *cache.entry_ref("a").or_insert(0) += 1;
+    *cache.entry_ref("b").or_insert(0) += 1;
+    *cache.entry_ref("a").or_insert(0) += 1;
+    assert_eq!(cache.len(), 2);
+
+    *cache.entry_ref("c").or_insert(0) += 1;
+    assert_eq!(cache.len(), 3);
+    *cache.entry_ref("d").or_insert(0) += 1;
+    assert_eq!(cache.len(), 3);
+    assert!(!cache.contains_key("b"));
+    assert_eq!(cache.peek("a"), Some(&2));
+    assert_eq!(cache.peek("d"), Some(&1));
+}
+
+#[test]
+fn test_eviction_listener() {
+    let evicted = Rc::new(RefCell::new(Vec::new()));
+    let mut cache = LruCache::with_listener(2, {
+        let evicted = evicted.clone();
+        move |k: i32, v: i32, cause: EvictionCause| evicted.borrow_mut().push((k, v, cause))
+    });
+    let take = || std::mem::take(&mut *evicted.borrow_mut());
+
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    assert_eq!(take(), [(1, 10, EvictionCause::Capacity)]);
+
+    // Entries handed back to the caller are not passed to the listener.
+    assert_eq!(cache.insert(2, 21), Some(20));
+    assert_eq!(cache.remove(&3), Some(30));
+    assert_eq!(cache.remove_lru(), Some((2, 21)));
+    assert_eq!(take(), []);
+
+    cache.extend([(4, 40), (5, 50), (4, 41), (6, 60)]);
+    assert_eq!(
+        take(),
+        [
+            (4, 40, EvictionCause::Replaced),
+            (5, 50, EvictionCause::Capacity)
+        ]
+    );
+
+    *cache.entry(7).or_insert(0) += 70;
+    *cache.entry(8).or_insert(0) += 80;
+    assert_eq!(take(), [(4, 41, EvictionCause::Capacity)]);
+    assert_eq!(cache.len(), 3);
+
+    cache.set_capacity(1);
+    assert_eq!(
+        take(),
+        [
+            (6, 60, EvictionCause::Capacity),
+            (7, 70, EvictionCause::Capacity)
+        ]
+    );
+
+    cache.set_capacity(4);
+    cache.extend([(1, 1), (2, 2), (3, 3)]);
+    cache.retain(|&k, _| k % 2 == 0);
+    let mut removed = take();
+    removed.sort_by_key(|&(k, _, _)| k);
+    assert_eq!(
+        removed,
+        [
+            (1, 1, EvictionCause::Removed),
+            (3, 3, EvictionCause::Removed)
+        ]
+    );
+
+    cache.clear();
+    assert_eq!(
+        take(),
+        [
+            (8, 80, EvictionCause::Cleared),
+            (2, 2, EvictionCause::Cleared)
+        ]
+    );
+    assert!(cache.is_empty());
+
+    // Drained entries are handed to the caller, even the ones the iterator drops.
+    cache.extend([(1, 1), (2, 2)]);
+    assert_eq!(cache.drain().next(), Some((1, 1)));
+    assert!(cache.is_empty());
+    assert_eq!(take(), []);
+}
+
+#[test]
+fn test_with_listener_in() {
+    let mut evicted = Vec::new();
+    let mut cache = LruCache::with_listener_in(1, hashlink::Global, |k: i32, v: i32, _| {
+        evicted.push((k, v))
+    });
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    drop(cache);
+    assert_eq!(evicted, [(1, 1)]);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_stats() {
+    use hashlink::lru_cache::CacheStats;
+
+    let mut cache = LruCache::new(2);
+    assert_eq!(cache.stats(), CacheStats::default());
+
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(1, 11);
+    cache.insert(3, 30);
+    assert_eq!(cache.get(&1), Some(&11));
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.peek(&3), Some(&30));
+    assert_eq!(cache.get_mut(&4), None);
+    cache.extend([(3, 31), (5, 50)]);
+    assert_eq!(cache.remove(&5), Some(50));
+    assert_eq!(cache.remove(&5), None);
+    assert_eq!(cache.remove_lru(), Some((3, 31)));
+
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 2,
+            misses: 2,
+            inserts: 4,
+            updates: 2,
+            evictions: 2,
+            removals: 2,
+        }
+    );
+    assert_eq!(cache.clone().stats(), cache.stats());
+
+    cache.reset_stats();
+    assert_eq!(cache.stats(), CacheStats::default());
+
+    cache.get_or_insert_with(1, || 10);
+    cache.get_or_insert_with(1, || unreachable!());
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 1,
+            misses: 1,
+            inserts: 1,
+            ..CacheStats::default()
+        }
+    );
+    cache.insert(2, 20);
+    cache.reset_stats();
+    assert!(cache.get_many_mut([&1, &2]).is_some());
+    assert!(cache.get_many_mut([&1, &3]).is_none());
+    cache.clear();
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 3,
+            misses: 1,
+            removals: 2,
+            ..CacheStats::default()
+        }
+    );
+}
+
+#[test]
+fn test_get_or_insert_with() {
+    let evicted = Rc::new(RefCell::new(Vec::new()));
+    let mut cache = LruCache::with_listener(2, {
+        let evicted = evicted.clone();
+        move |k: String, v: i32, _: EvictionCause| evicted.borrow_mut().push((k, v))
+    });
+
+    *cache.get_or_insert_with("a".to_owned(), || 1) += 10;
+    *cache.get_or_insert_with_ref("b", || 2) += 20;
+    assert_eq!(
+        *cache.get_or_insert_with("a".to_owned(), || unreachable!()),
+        11
+    );
+    assert_eq!(cache.len(), 2);
+
+    // "b" is now the least recently used entry, and is evicted before "c" is inserted.
+    assert_eq!(*cache.get_or_insert_with_ref("c", || 3), 3);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(*evicted.borrow(), [("b".to_owned(), 22)]);
+    assert_eq!(
+        cache
+            .iter()
+            .map(|(k, &v)| (k.as_str(), v))
+            .collect::<Vec<_>>(),
+        [("a", 11), ("c", 3)]
+    );
+
+    // A failed loader leaves the cache untouched.
+    assert_eq!(
+        cache.try_get_or_insert_with("d".to_owned(), || Err("failed")),
+        Err("failed")
+    );
+    assert_eq!(cache.len(), 2);
+    assert_eq!(evicted.borrow().len(), 1);
+
+    assert_eq!(
+        cache.try_get_or_insert_with::<(), _>("a".to_owned(), || unreachable!()),
+        Ok(&mut 11)
+    );
+    assert_eq!(cache.remove_lru(), Some(("c".to_owned(), 3)));
+
+    let mut cache = LruCache::new(0);
+    assert_eq!(*cache.get_or_insert_with(1, || 1), 1);
+    assert_eq!(*cache.get_or_insert_with(2, || 2), 2);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_get_or_insert_with_over_capacity() {
+    let mut cache = LruCache::new(2);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    // The entry API may leave the cache one over capacity.
+    cache.entry(3).or_insert(3);
+    assert_eq!(cache.len(), 3);
+
+    assert_eq!(*cache.get_or_insert_with(4, || 4), 4);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.iter().map(|(&k, _)| k).collect::<Vec<_>>(), [3, 4]);
+
+    let mut cache = LruCache::new(0);
+    assert_eq!(*cache.get_or_insert_with(1, || 1), 1);
+    assert_eq!(*cache.get_or_insert_with(2, || 2), 2);
+    assert_eq!(cache.len(), 1);
+}
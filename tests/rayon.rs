@@ -0,0 +1,130 @@
+#![cfg(feature = "rayon")]
+
+use hashlink::{LinkedHashMap, LinkedHashSet, LruCache};
+use rayon::prelude::*;
+
+#[test]
+fn test_map_par_iter() {
+    let mut map: LinkedHashMap<i32, i32> = (0..1000).rev().map(|i| (i, i * 10)).collect();
+    map.to_front(&500);
+
+    let expected: Vec<(i32, i32)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+    assert_eq!(
+        map.par_iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(map.par_iter().len(), 1000);
+    assert_eq!(
+        map.par_keys().copied().collect::<Vec<_>>(),
+        map.keys().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(map.par_values().sum::<i32>(), map.values().sum::<i32>());
+
+    map.par_iter_mut().for_each(|(&k, v)| *v += k);
+    map.par_values_mut().for_each(|v| *v *= 2);
+    assert_eq!(map[&3], 66);
+    assert_eq!(map.front(), Some((&500, &11000)));
+
+    let expected: Vec<(i32, i32)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+    assert_eq!(map.clone().into_par_iter().collect::<Vec<_>>(), expected);
+    assert_eq!(map.par_drain().collect::<Vec<_>>(), expected);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_map_par_extend() {
+    let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    map.insert(5, 0);
+    map.par_extend((0..10).into_par_iter().map(|i| (i, i)));
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+    );
+    assert_eq!(map[&5], 5);
+
+    let other: LinkedHashMap<i32, i32> = vec![(20, 1), (10, 2)].into_iter().collect();
+    map.par_extend(other.par_iter());
+    assert_eq!(map.back(), Some((&10, &2)));
+
+    let collected: LinkedHashMap<i32, i32> =
+        (0..100).into_par_iter().map(|i| (i % 10, i)).collect();
+    assert_eq!(
+        collected.keys().copied().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(collected[&3], 93);
+}
+
+#[test]
+fn test_set_par_iter() {
+    let mut set: LinkedHashSet<i32> = (0..1000).rev().collect();
+
+    assert_eq!(
+        set.par_iter().copied().collect::<Vec<_>>(),
+        set.iter().copied().collect::<Vec<_>>()
+    );
+
+    set.par_extend(vec![1000, 5, 1001].into_par_iter());
+    assert_eq!(set.len(), 1002);
+    assert_eq!(set.back(), Some(&1001));
+    set.par_extend(vec![2000].par_iter());
+    assert_eq!(set.back(), Some(&2000));
+
+    let expected: Vec<i32> = set.iter().copied().collect();
+    assert_eq!(set.clone().into_par_iter().collect::<Vec<_>>(), expected);
+    assert_eq!(set.par_drain().collect::<Vec<_>>(), expected);
+    assert!(set.is_empty());
+
+    let collected: LinkedHashSet<i32> = (0..100).into_par_iter().map(|i| i % 7).collect();
+    assert_eq!(
+        collected.into_iter().collect::<Vec<_>>(),
+        (0..100)
+            .map(|i| i % 7)
+            .collect::<LinkedHashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_lru_cache_par_iter() {
+    let mut cache = LruCache::new(3);
+    cache.insert(1, 10);
+    cache.insert(2, 20);
+    cache.insert(3, 30);
+    cache.get(&1);
+
+    assert_eq!(
+        cache.par_iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+        [(2, 20), (3, 30), (1, 10)]
+    );
+    cache.par_iter_mut().for_each(|(_, v)| *v += 1);
+    cache.par_values_mut().for_each(|v| *v *= 2);
+    assert_eq!(cache.peek(&2), Some(&42));
+
+    cache.par_extend(vec![(4, 40), (5, 50)].into_par_iter());
+    assert_eq!(cache.len(), 3);
+    assert_eq!(
+        cache.clone().into_par_iter().collect::<Vec<_>>(),
+        [(1, 22), (4, 40), (5, 50)]
+    );
+    assert_eq!(cache.par_drain().count(), 3);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_lru_cache_with_listener_par_iter() {
+    let mut evicted = Vec::new();
+    let mut cache = LruCache::with_listener(2, |k: i32, v: i32, _| evicted.push((k, v)));
+    cache.par_extend(vec![(1, 10), (2, 20), (3, 30)].into_par_iter());
+    cache.par_values_mut().for_each(|v| *v += 1);
+    assert_eq!(
+        (&cache)
+            .into_par_iter()
+            .map(|(&k, &v)| (k, v))
+            .collect::<Vec<_>>(),
+        [(2, 21), (3, 31)]
+    );
+    drop(cache);
+    assert_eq!(evicted, [(1, 10)]);
+}
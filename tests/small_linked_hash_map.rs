@@ -0,0 +1,308 @@
+#![cfg(feature = "alloc")]
+
+use std::{cell::Cell, rc::Rc};
+
+use hashlink::{
+    small_linked_hash_map::{Entry, EntryRef},
+    LinkedHashMap, SmallLinkedHashMap,
+};
+
+#[test]
+fn test_inline_insert_and_get() {
+    let mut map = SmallLinkedHashMap::<_, _, 4>::new();
+    assert!(map.is_inline());
+    assert_eq!(map.capacity(), 4);
+
+    assert_eq!(map.insert(1, 10), None);
+    assert_eq!(map.insert(2, 20), None);
+    assert_eq!(map.insert(3, 30), None);
+    assert!(map.is_inline());
+    assert_eq!(map.len(), 3);
+
+    assert_eq!(map.get(&2), Some(&20));
+    assert_eq!(map.get(&4), None);
+    assert!(map.contains_key(&3));
+    *map.get_mut(&1).unwrap() += 1;
+    assert_eq!(map[&1], 11);
+
+    // Inserting an existing key moves it to the back, just like `LinkedHashMap`.
+    assert_eq!(map.insert(1, 12), Some(11));
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [(&2, &20), (&3, &30), (&1, &12)]
+    );
+
+    // `replace` leaves the entry where it is.
+    assert_eq!(map.replace(2, 21), Some(20));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 3, 1]);
+
+    assert_eq!(map.remove(&3), Some(30));
+    assert_eq!(map.remove(&3), None);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+}
+
+#[test]
+fn test_spill_and_shrink() {
+    let mut map = SmallLinkedHashMap::<_, _, 2>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    assert!(map.is_inline());
+
+    map.insert("c", 3);
+    assert!(!map.is_inline());
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert_eq!(map.get("b"), Some(&2));
+
+    map.remove("a");
+    map.shrink_to_fit();
+    assert!(map.is_inline());
+    assert_eq!(map.capacity(), 2);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["b", "c"]);
+
+    let map = map.into_map();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["b", "c"]);
+
+    let mut map = SmallLinkedHashMap::<_, _, 1>::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+    let map = map.into_map();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert_eq!(map.get("c"), Some(&3));
+}
+
+#[test]
+fn test_order() {
+    let mut map: SmallLinkedHashMap<_, _, 4> = (0..4).map(|i| (i, i)).collect();
+    assert_eq!(map.front(), Some((&0, &0)));
+    assert_eq!(map.back(), Some((&3, &3)));
+
+    assert_eq!(map.to_front(&2), Some(&mut 2));
+    assert_eq!(map.to_back(&0), Some(&mut 0));
+    assert_eq!(map.to_back(&7), None);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1, 3, 0]);
+
+    assert_eq!(map.pop_front(), Some((2, 2)));
+    assert_eq!(map.pop_back(), Some((0, 0)));
+    assert_eq!(map.keys().rev().copied().collect::<Vec<_>>(), [3, 1]);
+
+    map.extend((4..8).map(|i| (i, i)));
+    assert!(!map.is_inline());
+    map.to_front(&7);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [7, 1, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_entry() {
+    let mut map = SmallLinkedHashMap::<_, _, 2>::new();
+    *map.entry("a").or_insert(0) += 1;
+    *map.entry("b").or_default() += 2;
+    *map.entry("a").or_insert(0) += 1;
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&"b", &2), (&"a", &2)]);
+
+    match map.entry("b") {
+        Entry::Occupied(mut entry) => {
+            entry.to_back();
+            assert_eq!(entry.insert(3), 2);
+        }
+        Entry::Vacant(_) => panic!(),
+    }
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&"a", &2), (&"b", &3)]);
+
+    // A vacant entry on a full map spills on insertion.
+    match map.entry("c") {
+        Entry::Vacant(entry) => *entry.insert(4) += 1,
+        Entry::Occupied(_) => panic!(),
+    }
+    assert!(!map.is_inline());
+    assert_eq!(map["c"], 5);
+
+    match map.entry("a") {
+        Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), ("a", 2)),
+        Entry::Vacant(_) => panic!(),
+    }
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["b", "c"]);
+}
+
+#[test]
+fn test_retain_and_iter() {
+    let mut map: SmallLinkedHashMap<_, _, 8> = (0..6).map(|i| (i, i * 10)).collect();
+    map.retain(|&k, v| {
+        *v += 1;
+        k % 2 == 0
+    });
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [(&0, &1), (&2, &21), (&4, &41)]
+    );
+
+    for v in map.values_mut() {
+        *v = 0;
+    }
+    assert!(map.values().all(|&v| v == 0));
+
+    let mut iter = map.clone().into_iter();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next_back(), Some((4, 0)));
+    assert_eq!(iter.next(), Some((0, 0)));
+    assert_eq!(iter.len(), 1);
+
+    assert_eq!(map, map.clone());
+    assert_eq!(format!("{:?}", map), "{0: 0, 2: 0, 4: 0}");
+}
+
+#[test]
+fn test_drops() {
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let mut map = SmallLinkedHashMap::<_, _, 4>::new();
+    for i in 0..4 {
+        map.insert(i, Counted(drops.clone()));
+    }
+    map.remove(&0);
+    assert_eq!(drops.get(), 1);
+    map.retain(|&k, _| k != 1);
+    assert_eq!(drops.get(), 2);
+
+    let mut iter = map.into_iter();
+    iter.next();
+    assert_eq!(drops.get(), 3);
+    drop(iter);
+    assert_eq!(drops.get(), 4);
+}
+
+// Runs the reordering and removal methods on a map which is inline when `N` is at least 6 and
+// spilled otherwise, checking them against `LinkedHashMap`.
+fn check_against_linked_hash_map<const N: usize>() {
+    fn pairs<const N: usize>(map: &SmallLinkedHashMap<i32, i32, N>) -> Vec<(i32, i32)> {
+        map.iter().map(|(&k, &v)| (k, v)).collect()
+    }
+
+    let entries = [(3, 0), (1, 1), (4, 2), (5, 3), (9, 4), (2, 5)];
+    let mut map: SmallLinkedHashMap<i32, i32, N> = entries.iter().copied().collect();
+    let mut model: LinkedHashMap<i32, i32> = entries.iter().copied().collect();
+    assert_eq!(map.is_inline(), N >= entries.len());
+
+    macro_rules! both {
+        ($($op:tt)*) => {{
+            let a = map.$($op)*;
+            let b = model.$($op)*;
+            assert_eq!(a, b);
+            assert_eq!(pairs(&map), model.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>());
+        }};
+    }
+
+    both!(move_before(&2, &1));
+    both!(move_before(&3, &9));
+    both!(move_after(&4, &2));
+    both!(move_after(&5, &3));
+    both!(move_after(&5, &5));
+    both!(move_before(&5, &7));
+    both!(reverse());
+    both!(sort_by_key(|_, &v| v % 2));
+    both!(sort_by_cached_key(|&k, _| -k));
+    both!(sort_by(|_, a, _, b| a.cmp(b)));
+    both!(sort_keys());
+    both!(sort_unstable_by(|a, _, b, _| b.cmp(a)));
+    both!(retain_with_order(|&k, v| {
+        *v += k;
+        k != 4
+    }));
+    both!(extract_if(|&k, _| k % 3 == 0).collect::<Vec<_>>());
+    both!(try_insert(1, 10));
+    both!(try_insert(3, 30));
+    both!(try_reserve(4));
+    both!(drain().collect::<Vec<_>>());
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_reordering_and_removal() {
+    check_against_linked_hash_map::<8>();
+    check_against_linked_hash_map::<2>();
+
+    let mut map = SmallLinkedHashMap::<_, _, 1>::new();
+    assert_eq!(map.try_reserve(1), Ok(()));
+    assert_eq!(map.try_insert(1, 1), Ok(None));
+    assert!(map.is_inline());
+    assert_eq!(map.try_insert(2, 2), Ok(None));
+    assert!(!map.is_inline());
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn test_extract_if_and_drain() {
+    let mut map: SmallLinkedHashMap<_, _, 8> = (0..6).map(|i| (i, i)).collect();
+    {
+        let mut iter = map.extract_if(|&k, _| k % 2 == 1);
+        assert_eq!(iter.next(), Some((1, 1)));
+    }
+    // Entries are only removed as the iterator advances.
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 2, 3, 4, 5]);
+
+    let mut drain = map.drain();
+    assert_eq!(drain.size_hint(), (5, Some(5)));
+    assert_eq!(drain.next_back(), Some((5, 5)));
+    assert_eq!(drain.next(), Some((0, 0)));
+    drop(drain);
+    assert!(map.is_empty());
+    assert!(map.is_inline());
+}
+
+#[test]
+fn test_entry_ref() {
+    let mut map = SmallLinkedHashMap::<String, i32, 2>::new();
+    *map.entry_ref("a").or_insert(0) += 1;
+    *map.entry_ref("b").or_default() += 2;
+    *map.entry_ref("a").or_insert(0) += 1;
+    assert_eq!(
+        map.iter()
+            .map(|(k, &v)| (k.as_str(), v))
+            .collect::<Vec<_>>(),
+        [("b", 2), ("a", 2)]
+    );
+
+    match map.entry_ref("b") {
+        EntryRef::Occupied(mut entry) => {
+            assert_eq!(entry.lookup_key(), "b");
+            entry.to_back();
+            assert_eq!(entry.insert(3), 2);
+        }
+        EntryRef::Vacant(_) => panic!(),
+    }
+    assert_eq!(
+        map.keys().map(String::as_str).collect::<Vec<_>>(),
+        ["a", "b"]
+    );
+
+    // A vacant entry on a full map spills on insertion.
+    match map.entry_ref("c") {
+        EntryRef::Vacant(entry) => {
+            assert_eq!(entry.key(), "c");
+            *entry.insert(4) += 1;
+        }
+        EntryRef::Occupied(_) => panic!(),
+    }
+    assert!(!map.is_inline());
+    assert_eq!(map["c"], 5);
+
+    *map.entry_ref("d").or_insert_with_key(|k| k.len() as i32) += 1;
+    match map.entry_ref("a") {
+        EntryRef::Occupied(entry) => assert_eq!(entry.remove_entry(), ("a".to_owned(), 2)),
+        EntryRef::Vacant(_) => panic!(),
+    }
+    assert_eq!(
+        map.iter()
+            .map(|(k, &v)| (k.as_str(), v))
+            .collect::<Vec<_>>(),
+        [("b", 3), ("c", 5), ("d", 2)]
+    );
+}
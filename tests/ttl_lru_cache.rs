@@ -0,0 +1,118 @@
+#![cfg(feature = "alloc")]
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use hashlink::TtlLruCache;
+
+fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+fn manual_clock() -> (Rc<Cell<Duration>>, impl Fn() -> Duration) {
+    let time = Rc::new(Cell::new(Duration::ZERO));
+    let clock = {
+        let time = time.clone();
+        move || time.get()
+    };
+    (time, clock)
+}
+
+#[test]
+fn test_expiry() {
+    let (time, clock) = manual_clock();
+    let mut cache = TtlLruCache::new(8, secs(10), clock);
+    assert_eq!(cache.insert(1, "a"), None);
+    time.set(secs(4));
+    assert_eq!(cache.insert(2, "b"), None);
+    assert_eq!(cache.remaining_ttl(&1), Some(secs(6)));
+
+    time.set(secs(9));
+    assert_eq!(cache.peek(&1), Some(&"a"));
+    assert_eq!(cache.get(&1), Some(&"a"));
+
+    // Getting an entry makes it recently used, but does not extend its TTL.
+    time.set(secs(10));
+    assert_eq!(cache.peek(&1), None);
+    assert!(!cache.contains_key(&1));
+    assert_eq!(cache.remaining_ttl(&1), None);
+    assert_eq!(cache.len(), 2);
+
+    // `get` removes the expired entry, `peek` leaves it alone.
+    assert_eq!(cache.get(&1), None);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&2), Some(&"b"));
+
+    time.set(secs(14));
+    assert_eq!(cache.peek_mut(&2), None);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_per_entry_ttl() {
+    let (time, clock) = manual_clock();
+    let mut cache = TtlLruCache::new(8, secs(10), clock);
+    cache.insert_with_ttl(1, 1, secs(2));
+    cache.insert(2, 2);
+    cache.insert_with_ttl(3, 3, Duration::MAX);
+    assert_eq!(cache.remaining_ttl(&3), Some(Duration::MAX));
+
+    time.set(secs(5));
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&2, &2), (&3, &3)]);
+    assert_eq!(format!("{:?}", cache), "{3: 3, 2: 2}");
+
+    // An expired previous value is not returned when replacing it.
+    assert_eq!(cache.insert(1, 10), None);
+    assert_eq!(cache.insert(1, 11), Some(10));
+
+    assert!(cache.set_entry_ttl(&2, secs(1)));
+    assert!(!cache.set_entry_ttl(&4, secs(1)));
+    cache.set_ttl(secs(100));
+    cache.insert(4, 4);
+
+    time.set(secs(1_000));
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [3]);
+    assert_eq!(cache.remove(&1), None);
+    assert_eq!(cache.remove(&3), Some(3));
+}
+
+#[test]
+fn test_purge_expired() {
+    let (time, clock) = manual_clock();
+    let mut cache = TtlLruCache::new(8, secs(10), clock);
+    for i in 0..6 {
+        cache.insert_with_ttl(i, i, secs(i));
+    }
+    // Make an expired entry the most recently used one.
+    assert_eq!(cache.get(&1), Some(&1));
+    time.set(secs(1));
+    assert_eq!(cache.purge_expired(), 2);
+
+    time.set(secs(3));
+    assert_eq!(cache.len(), 4);
+    assert_eq!(cache.purge_expired(), 2);
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [4, 5]);
+    assert_eq!(cache.purge_expired(), 0);
+
+    // `remove_lru` skips over expired entries.
+    cache.insert_with_ttl(6, 6, secs(100));
+    time.set(secs(5));
+    assert_eq!(cache.remove_lru(), Some((6, 6)));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_capacity() {
+    let (time, clock) = manual_clock();
+    let mut cache = TtlLruCache::new(2, secs(10), clock);
+    cache.insert(1, 1);
+    cache.insert(2, 2);
+    cache.insert(3, 3);
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [2, 3]);
+
+    cache.set_capacity(1);
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [3]);
+
+    time.set(secs(20));
+    cache.retain(|_, _| true);
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,178 @@
+#![cfg(feature = "alloc")]
+
+use hashlink::WeightedLruCache;
+
+fn by_len() -> impl Fn(&&'static str, &String) -> usize {
+    |_, v| v.len()
+}
+
+#[test]
+fn test_evict_until_fit() {
+    let mut cache = WeightedLruCache::with_weigher(10, by_len());
+    assert_eq!(cache.insert("a", "aaaa".to_owned()), Ok(None));
+    assert_eq!(cache.insert("b", "bbb".to_owned()), Ok(None));
+    assert_eq!(cache.insert("c", "cc".to_owned()), Ok(None));
+    assert_eq!(cache.weight(), 9);
+    assert_eq!(cache.len(), 3);
+
+    // Touching "a" makes "b" the least recently used entry.
+    assert_eq!(cache.get("a").map(String::as_str), Some("aaaa"));
+
+    // Needs 5, so both "b" and "c" have to go.
+    assert_eq!(cache.insert("d", "ddddd".to_owned()), Ok(None));
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        ["a", "d"]
+    );
+    assert_eq!(cache.weight(), 9);
+
+    // Replacing an entry accounts for the difference in weight.
+    assert_eq!(
+        cache.insert("a", "a".to_owned()),
+        Ok(Some("aaaa".to_owned()))
+    );
+    assert_eq!(cache.weight(), 6);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        ["d", "a"]
+    );
+
+    assert_eq!(cache.remove_lru(), Some(("d", "ddddd".to_owned())));
+    assert_eq!(cache.weight(), 1);
+    assert_eq!(cache.remove("a"), Some("a".to_owned()));
+    assert_eq!(cache.weight(), 0);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_reject_too_heavy() {
+    let mut cache = WeightedLruCache::with_weigher(4, by_len());
+    cache.insert("a", "aa".to_owned()).unwrap();
+    assert_eq!(
+        cache.insert("b", "bbbbb".to_owned()),
+        Err(("b", "bbbbb".to_owned()))
+    );
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.weight(), 2);
+
+    // A rejected replacement leaves the old value in place.
+    assert!(cache.insert("a", "aaaaa".to_owned()).is_err());
+    assert_eq!(cache.peek("a").map(String::as_str), Some("aa"));
+
+    // Entries of exactly the maximum weight fit, after evicting everything else.
+    assert_eq!(cache.insert("c", "cccc".to_owned()), Ok(None));
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), ["c"]);
+    assert_eq!(cache.weight(), 4);
+}
+
+#[test]
+fn test_guard_reweighs() {
+    let mut cache = WeightedLruCache::with_weigher(10, by_len());
+    cache.insert("a", "a".to_owned()).unwrap();
+    cache.insert("b", "b".to_owned()).unwrap();
+    cache.insert("c", "c".to_owned()).unwrap();
+
+    // `get_mut` moves "a" to the back, so growing it evicts "b".
+    {
+        let mut a = cache.get_mut("a").unwrap();
+        assert_eq!(*a.key(), "a");
+        a.push_str("aaaaaaaa");
+    }
+    assert_eq!(cache.weight(), 10);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        ["c", "a"]
+    );
+
+    // `peek_mut` leaves the order alone, so shrinking just lowers the weight.
+    cache.peek_mut("a").unwrap().truncate(2);
+    assert_eq!(cache.weight(), 3);
+    assert_eq!(
+        cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        ["c", "a"]
+    );
+
+    // An entry grown past the maximum weight is removed by itself.
+    cache.peek_mut("c").unwrap().push_str("cccccccccc");
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), ["a"]);
+    assert_eq!(cache.weight(), 2);
+
+    assert!(cache.get_mut("c").is_none());
+}
+
+#[test]
+fn test_set_max_weight_and_retain() {
+    let mut cache = WeightedLruCache::with_weigher(usize::MAX, |_: &u32, v: &u32| *v as usize);
+    for i in 1..=5 {
+        cache.insert(i, i).unwrap();
+    }
+    assert_eq!(cache.weight(), 15);
+
+    cache.set_max_weight(10);
+    assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [4, 5]);
+    assert_eq!(cache.weight(), 9);
+
+    cache.insert(1, 1).unwrap();
+    cache.retain(|&k, v| {
+        *v *= 2;
+        k != 1
+    });
+    // Both remaining entries doubled, which no longer fits, so the least recently used one goes.
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&5, &10)]);
+    assert_eq!(cache.weight(), 10);
+
+    cache.retain(|_, v| {
+        *v += 1;
+        true
+    });
+    assert!(cache.is_empty());
+    assert_eq!(cache.weight(), 0);
+
+    cache.insert(2, 2).unwrap();
+    cache.insert(3, 3).unwrap();
+    assert_eq!(format!("{:?}", cache), "{3: 3, 2: 2}");
+    assert_eq!(
+        cache.clone().into_iter().collect::<Vec<_>>(),
+        [(2, 2), (3, 3)]
+    );
+}
+
+#[test]
+fn test_huge_weights() {
+    let half = usize::MAX / 2 + 1;
+    let mut cache = WeightedLruCache::with_weigher(usize::MAX, |_: &u32, v: &usize| *v);
+    cache.insert(1, half).unwrap();
+    cache.insert(2, half).unwrap();
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&2, &half)]);
+    assert_eq!(cache.weight(), half);
+
+    // Replacing the only entry must not count its old weight.
+    cache.insert(2, usize::MAX).unwrap();
+    assert_eq!(cache.weight(), usize::MAX);
+
+    cache.insert(3, 1).unwrap();
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&3, &1)]);
+    cache.insert(4, half - 1).unwrap();
+    assert_eq!(cache.weight(), half);
+
+    // Growing an entry through a guard evicts the others first.
+    *cache.get_mut(&3).unwrap() = half + 1;
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&3, &(half + 1))]);
+    assert_eq!(cache.weight(), half + 1);
+
+    // With `peek_mut`, the grown entry itself is at the front and so is evicted.
+    *cache.peek_mut(&3).unwrap() = 1;
+    cache.insert(5, half - 1).unwrap();
+    assert_eq!(cache.weight(), half);
+    *cache.peek_mut(&3).unwrap() = half + 1;
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&5, &(half - 1))]);
+    assert_eq!(cache.weight(), half - 1);
+
+    cache.insert(6, 1).unwrap();
+    cache.retain(|_, v| {
+        *v = half;
+        true
+    });
+    assert_eq!(cache.iter().collect::<Vec<_>>(), [(&6, &half)]);
+    assert_eq!(cache.weight(), half);
+}